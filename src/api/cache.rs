@@ -0,0 +1,247 @@
+//! On-disk cache for slowly changing reference data.
+//!
+//! Only endpoints listed in [`CACHE_TTLS`] are cached. Entries live in a
+//! `cache` directory next to the profile's token file, one sub-directory per endpoint,
+//! and are keyed by base URL, endpoint and query parameters. Stale entries
+//! are revalidated with `If-None-Match`/`If-Modified-Since` so an unchanged
+//! resource costs a cheap 304 rather than a full download.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::auth::{backend, profile};
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+
+/// Cacheable endpoints and how long a cached response stays fresh
+pub const CACHE_TTLS: &[(&str, Duration)] = &[
+    ("categories", Duration::from_secs(DAY)),
+    ("currencies", Duration::from_secs(7 * DAY)),
+    ("cis_bands", Duration::from_secs(7 * DAY)),
+    ("ec_moss/sales_tax_rates", Duration::from_secs(7 * DAY)),
+    ("users/me", Duration::from_secs(HOUR)),
+    ("company", Duration::from_secs(HOUR)),
+];
+
+/// Time-to-live for an endpoint, or `None` if it is never cached
+pub fn ttl_for(endpoint: &str) -> Option<Duration> {
+    let endpoint = endpoint.trim_matches('/');
+    CACHE_TTLS
+        .iter()
+        .find(|(path, _)| *path == endpoint)
+        .map(|(_, ttl)| *ttl)
+}
+
+/// A cached response body and its validators
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub stored_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    pub body: Value,
+}
+
+impl CacheEntry {
+    /// Create an entry stored now
+    pub fn new(body: Value, etag: Option<String>, last_modified: Option<String>) -> Self {
+        Self {
+            stored_at: Utc::now(),
+            etag,
+            last_modified,
+            body,
+        }
+    }
+
+    /// Mark the entry as revalidated now
+    pub fn touch(&mut self) {
+        self.stored_at = Utc::now();
+    }
+
+    /// Whether the entry is younger than `ttl`
+    pub fn is_fresh(&self, ttl: Duration) -> bool {
+        let age = Utc::now().signed_duration_since(self.stored_at);
+        age.to_std().map(|age| age < ttl).unwrap_or(true)
+    }
+}
+
+/// Location of one cached response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheKey {
+    endpoint: String,
+    hash: String,
+}
+
+impl CacheKey {
+    /// Build a key from the base URL, endpoint and query parameters
+    pub fn new(base_url: &str, endpoint: &str, params: Option<&HashMap<String, String>>) -> Self {
+        let endpoint = endpoint.trim_matches('/').to_string();
+        let sorted: BTreeMap<_, _> = params.into_iter().flatten().collect();
+        let canonical = serde_json::json!([base_url, endpoint, sorted]).to_string();
+        let digest = Sha256::digest(canonical.as_bytes());
+        let hash = digest.iter().take(8).map(|b| format!("{:02x}", b)).collect();
+        Self { endpoint, hash }
+    }
+}
+
+/// Directory-backed response cache
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+}
+
+impl ResponseCache {
+    /// Use `dir` as the cache directory
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The cache directory for a profile, alongside its stored tokens
+    pub fn profile_dir(profile: &str) -> Result<PathBuf> {
+        Ok(profile::profile_dir(profile)?.join("cache"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn endpoint_dir(&self, endpoint: &str) -> PathBuf {
+        self.dir.join(endpoint.replace('/', "_"))
+    }
+
+    fn path(&self, key: &CacheKey) -> PathBuf {
+        self.endpoint_dir(&key.endpoint).join(format!("{}.json", key.hash))
+    }
+
+    /// Load an entry; unreadable or corrupt entries count as misses
+    pub fn load(&self, key: &CacheKey) -> Option<CacheEntry> {
+        let json = fs::read_to_string(self.path(key)).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// Store an entry, readable only by the owner since `users/me` and
+    /// `company` hold personal details
+    pub fn store(&self, key: &CacheKey, entry: &CacheEntry) -> Result<()> {
+        backend::ensure_private_dir(&self.dir)?;
+        let json = serde_json::to_string(entry).context("Failed to serialize cache entry")?;
+        backend::write_private(&self.path(key), json.as_bytes())
+    }
+
+    /// Drop cached responses made stale by a write to `endpoint`.
+    ///
+    /// A write to `categories/123` invalidates `categories`, and a write to
+    /// `company` invalidates `company`.
+    pub fn invalidate(&self, endpoint: &str) -> Result<()> {
+        let endpoint = endpoint.trim_matches('/');
+        for (path, _) in CACHE_TTLS {
+            let affected = endpoint == *path || endpoint.starts_with(&format!("{}/", path));
+            let dir = self.endpoint_dir(path);
+            if affected && dir.exists() {
+                fs::remove_dir_all(&dir)
+                    .with_context(|| format!("Failed to remove cache directory {}", dir.display()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove every cached response, returning how many were deleted
+    pub fn clear(&self) -> Result<usize> {
+        if !self.dir.exists() {
+            return Ok(0);
+        }
+        let mut removed = 0;
+        for entry in fs::read_dir(&self.dir).context("Failed to read cache directory")? {
+            let path = entry?.path();
+            if path.is_dir() {
+                removed += fs::read_dir(&path)?.count();
+            }
+        }
+        fs::remove_dir_all(&self.dir)
+            .with_context(|| format!("Failed to remove cache directory {}", self.dir.display()))?;
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn only_reference_endpoints_have_ttls() {
+        assert_eq!(ttl_for("categories"), Some(Duration::from_secs(DAY)));
+        assert_eq!(ttl_for("/users/me"), Some(Duration::from_secs(HOUR)));
+        assert_eq!(ttl_for("invoices"), None);
+        assert_eq!(ttl_for("company/tax_timeline"), None);
+    }
+
+    #[test]
+    fn key_depends_on_params_but_not_their_order() {
+        let a = HashMap::from([("a".to_string(), "1".to_string()), ("b".to_string(), "2".to_string())]);
+        let b = HashMap::from([("b".to_string(), "2".to_string()), ("a".to_string(), "1".to_string())]);
+        let base = "https://api.freeagent.com/v2";
+        assert_eq!(CacheKey::new(base, "categories", Some(&a)), CacheKey::new(base, "categories", Some(&b)));
+        assert_ne!(CacheKey::new(base, "categories", None), CacheKey::new(base, "categories", Some(&a)));
+        assert_ne!(
+            CacheKey::new(base, "categories", None),
+            CacheKey::new("https://api.sandbox.freeagent.com/v2", "categories", None)
+        );
+    }
+
+    #[test]
+    fn store_load_invalidate_and_clear() -> Result<()> {
+        let dir = tempdir()?;
+        let cache = ResponseCache::new(dir.path().join("cache"));
+        let categories = CacheKey::new("", "categories", None);
+        let company = CacheKey::new("", "company", None);
+        let entry = CacheEntry::new(json!({"categories": []}), Some("\"abc\"".to_string()), None);
+
+        cache.store(&categories, &entry)?;
+        cache.store(&company, &entry)?;
+        assert_eq!(cache.load(&categories), Some(entry.clone()));
+
+        cache.invalidate("categories/250")?;
+        assert_eq!(cache.load(&categories), None);
+        assert!(cache.load(&company).is_some());
+
+        assert_eq!(cache.clear()?, 1);
+        assert_eq!(cache.load(&company), None);
+        assert_eq!(cache.clear()?, 0);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn entries_are_private_to_the_owner() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir()?;
+        let cache = ResponseCache::new(dir.path().join("cache"));
+        let key = CacheKey::new("", "users/me", None);
+        cache.store(&key, &CacheEntry::new(json!({"user": {}}), None, None))?;
+
+        let mode = |path: &Path| fs::metadata(path).map(|m| m.permissions().mode() & 0o777);
+        assert_eq!(mode(&cache.path(&key))?, 0o600);
+        assert_eq!(mode(&cache.endpoint_dir("users/me"))?, 0o700);
+        assert_eq!(mode(&dir.path().join("cache"))?, 0o700);
+        Ok(())
+    }
+
+    #[test]
+    fn entries_expire_after_ttl() {
+        let mut entry = CacheEntry::new(json!({}), None, None);
+        assert!(entry.is_fresh(Duration::from_secs(60)));
+        entry.stored_at = Utc::now() - chrono::Duration::seconds(120);
+        assert!(!entry.is_fresh(Duration::from_secs(60)));
+    }
+}
@@ -0,0 +1,210 @@
+//! Record/replay of API traffic for offline use.
+//!
+//! In record mode every request/response pair is written to a directory of JSON
+//! files. In replay mode responses are served from that directory and no network
+//! requests are made, so commands run deterministically in tests and demos.
+//!
+//! Request headers (including the `Authorization` token) are never recorded.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Whether traffic is being written to or served from the cassette directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+/// A request as stored in a cassette
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Endpoint path relative to the API base URL, or an absolute URL
+    pub path: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub query: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+impl RecordedRequest {
+    /// Build a request description; `url` is made relative to `base_url` when possible
+    pub fn new(
+        method: &str,
+        url: &str,
+        base_url: &str,
+        params: Option<&HashMap<String, String>>,
+        body: Option<&Value>,
+    ) -> Self {
+        let path = url
+            .strip_prefix(base_url)
+            .map(|p| p.trim_start_matches('/').to_string())
+            .unwrap_or_else(|| url.to_string());
+
+        Self {
+            method: method.to_uppercase(),
+            path,
+            query: params
+                .map(|p| p.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+                .unwrap_or_default(),
+            body: body.cloned(),
+        }
+    }
+
+    /// File name for this request within the cassette directory
+    fn file_name(&self) -> String {
+        let canonical = serde_json::to_string(self).unwrap_or_default();
+        let digest = Sha256::digest(canonical.as_bytes());
+        let hash: String = digest.iter().take(6).map(|b| format!("{:02x}", b)).collect();
+
+        let path = self.path.split('?').next().unwrap_or_default();
+        let mut slug: String = path
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        slug.truncate(60);
+
+        format!("{}_{}_{}.json", self.method.to_lowercase(), slug.trim_matches('_'), hash)
+    }
+}
+
+/// A response as stored in a cassette
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub body: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+/// A directory of recorded interactions
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    dir: PathBuf,
+    mode: CassetteMode,
+}
+
+impl Cassette {
+    /// Record interactions into `dir`
+    pub fn record(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            mode: CassetteMode::Record,
+        }
+    }
+
+    /// Serve interactions from `dir`
+    pub fn replay(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            mode: CassetteMode::Replay,
+        }
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Save a request/response pair
+    pub fn save(&self, request: &RecordedRequest, response: &RecordedResponse) -> Result<()> {
+        fs::create_dir_all(&self.dir).with_context(|| {
+            format!("Failed to create cassette directory {}", self.dir.display())
+        })?;
+
+        let path = self.dir.join(request.file_name());
+        let interaction = Interaction {
+            request: request.clone(),
+            response: response.clone(),
+        };
+        let json = serde_json::to_string_pretty(&interaction)
+            .context("Failed to serialize recorded interaction")?;
+        fs::write(&path, json)
+            .with_context(|| format!("Failed to write cassette file {}", path.display()))
+    }
+
+    /// Load the recorded response for a request
+    pub fn load(&self, request: &RecordedRequest) -> Result<RecordedResponse> {
+        let path = self.dir.join(request.file_name());
+        let json = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "No recorded response for {} {} (expected {})",
+                request.method,
+                request.path,
+                path.display()
+            )
+        })?;
+        let interaction: Interaction = serde_json::from_str(&json)
+            .with_context(|| format!("Failed to parse cassette file {}", path.display()))?;
+        Ok(interaction.response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn request_path_is_relative_to_base_url() {
+        let request = RecordedRequest::new(
+            "get",
+            "https://api.freeagent.com/v2/invoices",
+            "https://api.freeagent.com/v2",
+            None,
+            None,
+        );
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "invoices");
+    }
+
+    #[test]
+    fn file_name_depends_on_query_and_body() {
+        let params = HashMap::from([("page".to_string(), "2".to_string())]);
+        let base = RecordedRequest::new("GET", "invoices", "", None, None);
+        let paged = RecordedRequest::new("GET", "invoices", "", Some(&params), None);
+        let body = json!({"invoice": {}});
+        let post = RecordedRequest::new("POST", "invoices", "", None, Some(&body));
+        assert_ne!(base.file_name(), paged.file_name());
+        assert_ne!(base.file_name(), post.file_name());
+        assert!(base.file_name().starts_with("get_invoices_"));
+    }
+
+    #[test]
+    fn save_then_load_roundtrip() -> Result<()> {
+        let dir = tempdir()?;
+        let cassette = Cassette::record(dir.path());
+        let request = RecordedRequest::new("GET", "company", "", None, None);
+        let response = RecordedResponse {
+            status: 200,
+            headers: BTreeMap::from([("content-type".to_string(), "application/json".to_string())]),
+            body: "{\"company\":{}}".to_string(),
+        };
+        cassette.save(&request, &response)?;
+
+        let replay = Cassette::replay(dir.path());
+        assert_eq!(replay.load(&request)?, response);
+
+        let missing = RecordedRequest::new("GET", "users", "", None, None);
+        let err = replay.load(&missing).unwrap_err();
+        assert!(err.to_string().contains("No recorded response for GET users"));
+        Ok(())
+    }
+}
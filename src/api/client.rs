@@ -1,15 +1,27 @@
 //! FreeAgent API client with automatic token refresh and rate limit handling.
 
 use anyhow::{Context, Result};
-use reqwest::{Client, Method, Response};
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use reqwest::{Client, Method, StatusCode};
 use serde::Serialize;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::io::Write;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+use crate::auth::profile::DEFAULT_PROFILE;
 use crate::auth::{api_url, OAuthManager, StoredTokens};
+use crate::api::error::ApiError;
+use crate::api::cache::{self, CacheEntry, CacheKey, ResponseCache};
+use crate::api::cassette::{Cassette, CassetteMode, RecordedRequest, RecordedResponse};
+use crate::api::download;
+use crate::api::logging::HttpLogger;
+use crate::api::pagination::{self, Pagination};
+use crate::api::rate_limit::RateLimiter;
 use crate::api::retry::{RateLimitInfo, RetryConfig};
 
 /// FreeAgent API client
@@ -19,26 +31,73 @@ pub struct FreeAgentClient {
     tokens: Arc<RwLock<StoredTokens>>,
     base_url: String,
     retry_config: RetryConfig,
+    pagination: Pagination,
+    cassette: Option<Cassette>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    cache: Option<ResponseCache>,
+    logger: Option<Arc<HttpLogger>>,
+    dry_run: bool,
 }
 
 impl FreeAgentClient {
-    /// Create a new API client
+    /// Create a new API client using the default profile's tokens
     pub async fn new(sandbox: bool) -> Result<Self> {
-        let oauth_manager = OAuthManager::new(sandbox)?;
+        Self::for_profile(DEFAULT_PROFILE, sandbox).await
+    }
+
+    /// Create a new API client using a named profile's tokens
+    pub async fn for_profile(profile: &str, sandbox: bool) -> Result<Self> {
+        let oauth_manager = OAuthManager::for_profile(profile, sandbox)?;
         let tokens = oauth_manager.get_valid_tokens().await?;
-        let base_url = api_url(sandbox).to_string();
+        let base_url = api_url(sandbox);
 
         let http_client = Client::builder()
             .user_agent("FreeAgent-CLI-Rust/1.0")
             .build()
             .context("Failed to create HTTP client")?;
 
+        let retry_config = RetryConfig::from_env();
+        let rate_limiter = RateLimiter::from_config(&retry_config).map(Arc::new);
+
         Ok(Self {
             http_client,
             oauth_manager,
             tokens: Arc::new(RwLock::new(tokens)),
             base_url,
+            retry_config,
+            pagination: Pagination::default(),
+            cassette: None,
+            rate_limiter,
+            cache: None,
+            logger: None,
+            dry_run: false,
+        })
+    }
+
+    /// Create a client that serves every request from a recorded cassette.
+    ///
+    /// No stored tokens are needed and no network requests are made.
+    pub fn replay(sandbox: bool, dir: impl Into<std::path::PathBuf>) -> Result<Self> {
+        let oauth_manager = OAuthManager::new(sandbox)?;
+        let tokens = StoredTokens::new(String::new(), String::new(), i64::from(i32::MAX), sandbox);
+
+        let http_client = Client::builder()
+            .user_agent("FreeAgent-CLI-Rust/1.0")
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            http_client,
+            oauth_manager,
+            tokens: Arc::new(RwLock::new(tokens)),
+            base_url: api_url(sandbox),
             retry_config: RetryConfig::from_env(),
+            pagination: Pagination::default(),
+            cassette: Some(Cassette::replay(dir)),
+            rate_limiter: None,
+            cache: None,
+            logger: None,
+            dry_run: false,
         })
     }
 
@@ -57,9 +116,54 @@ impl FreeAgentClient {
             tokens: Arc::new(RwLock::new(tokens)),
             base_url,
             retry_config: RetryConfig::default(),
+            pagination: Pagination::default(),
+            cassette: None,
+            rate_limiter: None,
+            cache: None,
+            logger: None,
+            dry_run: false,
         })
     }
 
+    /// Set how list requests follow further pages
+    pub fn with_pagination(mut self, pagination: Pagination) -> Self {
+        self.pagination = pagination;
+        self
+    }
+
+    /// Record every request/response pair into `dir`
+    pub fn with_recording(mut self, dir: impl Into<std::path::PathBuf>) -> Self {
+        self.cassette = Some(Cassette::record(dir));
+        self
+    }
+
+    /// Cache reference data responses in `cache`
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Describe POST, PUT and DELETE requests instead of sending them.
+    ///
+    /// GET requests are still sent so lookups made while building a request
+    /// keep working.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Log HTTP traffic through `logger`
+    pub fn with_logger(mut self, logger: Arc<HttpLogger>) -> Self {
+        self.logger = Some(logger);
+        self
+    }
+
+    /// Pace requests with `limiter`, which may be shared with other clients
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
     #[cfg(test)]
     pub(crate) fn with_retry_config(mut self, config: RetryConfig) -> Self {
         self.retry_config = config;
@@ -77,16 +181,193 @@ impl FreeAgentClient {
         Ok(tokens.access_token.clone())
     }
 
-    /// Build the full URL for an endpoint
-    fn build_url(&self, endpoint: &str) -> String {
-        format!("{}/{}", self.base_url, endpoint.trim_start_matches('/'))
+    /// Build the full URL for an endpoint.
+    ///
+    /// Absolute URLs, from `freeagent api` or a `Link` header, must share the
+    /// API's scheme, host and port, since every request carries the access token.
+    pub(crate) fn build_url(&self, endpoint: &str) -> Result<String> {
+        if !(endpoint.starts_with("http://") || endpoint.starts_with("https://")) {
+            return Ok(format!("{}/{}", self.base_url, endpoint.trim_start_matches('/')));
+        }
+
+        let url = reqwest::Url::parse(endpoint).with_context(|| format!("Invalid URL: {}", endpoint))?;
+        let base = reqwest::Url::parse(&self.base_url).context("Invalid API base URL")?;
+        if url.origin() != base.origin() {
+            anyhow::bail!(
+                "Refusing to send credentials to {}; only URLs on {} are allowed",
+                url.origin().ascii_serialization(),
+                base.origin().ascii_serialization()
+            );
+        }
+        Ok(endpoint.to_string())
     }
 
     /// Make a GET request
+    ///
+    /// Reference data endpoints are served from the response cache when one
+    /// is configured.
     pub async fn get(&self, endpoint: &str, params: Option<HashMap<String, String>>) -> Result<Value> {
+        if let (Some(cache), Some(ttl)) = (&self.cache, cache::ttl_for(endpoint)) {
+            return self.cached_get(cache, ttl, endpoint, params).await;
+        }
         self.request(Method::GET, endpoint, params, None::<()>).await
     }
 
+    /// Make a GET request against a list endpoint.
+    ///
+    /// Returns a single page unless `--all` or `--limit` is in effect, in which
+    /// case `Link: rel="next"` headers are followed and the pages are merged into
+    /// one collection.
+    pub async fn list(&self, endpoint: &str, params: Option<HashMap<String, String>>) -> Result<Value> {
+        self.list_with(self.pagination, endpoint, params).await
+    }
+
+    /// Like [`list`](Self::list), but hands each page to `on_page` as it
+    /// arrives instead of merging them, so large lists are never held in
+    /// memory
+    pub async fn list_pages(
+        &self,
+        endpoint: &str,
+        params: Option<HashMap<String, String>>,
+        on_page: impl FnMut(Value) -> Result<()>,
+    ) -> Result<()> {
+        self.fetch_pages(self.pagination, endpoint, params, on_page)
+            .await
+    }
+
+    /// Make a GET request against a list endpoint, following every page
+    /// (still capped by `--limit`)
+    pub async fn list_all(&self, endpoint: &str, params: Option<HashMap<String, String>>) -> Result<Value> {
+        let pagination = Pagination {
+            all: true,
+            ..self.pagination
+        };
+        self.list_with(pagination, endpoint, params).await
+    }
+
+    pub(crate) async fn list_with(
+        &self,
+        pagination: Pagination,
+        endpoint: &str,
+        params: Option<HashMap<String, String>>,
+    ) -> Result<Value> {
+        let mut merged: Option<Value> = None;
+        self.fetch_pages(pagination, endpoint, params, |page| {
+            match merged.as_mut() {
+                Some(merged) => pagination::merge_page(merged, page),
+                None => merged = Some(page),
+            }
+            Ok(())
+        })
+        .await?;
+        Ok(merged.unwrap_or_default())
+    }
+
+    /// Fetch the pages of a list, passing each to `on_page` in order
+    async fn fetch_pages(
+        &self,
+        pagination: Pagination,
+        endpoint: &str,
+        params: Option<HashMap<String, String>>,
+        mut on_page: impl FnMut(Value) -> Result<()>,
+    ) -> Result<()> {
+        if !pagination.is_enabled() {
+            return on_page(self.get(endpoint, params).await?);
+        }
+
+        let mut params = params.unwrap_or_default();
+        params
+            .entry("per_page".to_string())
+            .or_insert_with(|| pagination.per_page().to_string());
+
+        let (headers, mut page) = self
+            .request_with_headers(Method::GET, endpoint, Some(params), None::<()>)
+            .await?;
+        let total = pagination::total_count(&headers);
+        let mut next = pagination::next_link(&headers);
+        let mut fetched = 0;
+
+        loop {
+            if let Some(limit) = pagination.limit {
+                pagination::truncate(&mut page, limit - fetched);
+            }
+            fetched += pagination::record_count(&page);
+            on_page(page)?;
+
+            if pagination.limit.is_some_and(|limit| fetched >= limit) {
+                break;
+            }
+            if total.is_some_and(|total| fetched >= total) {
+                break;
+            }
+            let Some(url) = next.take() else {
+                break;
+            };
+
+            let (headers, next_page) = self
+                .request_with_headers(Method::GET, &url, None, None::<()>)
+                .await?;
+            if pagination::record_count(&next_page) == 0 {
+                break;
+            }
+            page = next_page;
+            next = pagination::next_link(&headers);
+        }
+
+        Ok(())
+    }
+
+    /// Stream a file from a pre-signed URL (such as an attachment's
+    /// `content_src`) to `path`, returning the number of bytes written.
+    ///
+    /// No `Authorization` header is sent: the URL carries its own signature
+    /// and points at storage outside the API.
+    pub async fn download(&self, url: &str, path: &std::path::Path) -> Result<u64> {
+        if self
+            .cassette
+            .as_ref()
+            .is_some_and(|c| c.mode() == CassetteMode::Replay)
+        {
+            anyhow::bail!("Downloading file content is not available in replay mode");
+        }
+
+        let request = self.http_client.get(url).build().map_err(ApiError::Network)?;
+        if let Some(logger) = &self.logger {
+            logger.request(&request, 0);
+        }
+        let started = Instant::now();
+        let mut response = self.http_client.execute(request).await.map_err(ApiError::Network)?;
+        let status = response.status();
+        if let Some(logger) = &self.logger {
+            logger.response(status, response.headers(), started.elapsed());
+        }
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(ApiError::from_response(status, &text).into());
+        }
+
+        download::ensure_parent(path)?;
+        let mut file = std::fs::File::create(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut written = 0u64;
+        let result: Result<()> = async {
+            while let Some(chunk) = response.chunk().await.map_err(ApiError::Network)? {
+                file.write_all(&chunk)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                written += chunk.len() as u64;
+            }
+            Ok(())
+        }
+        .await;
+
+        if let Err(err) = result {
+            // Don't leave a truncated file behind
+            let _ = std::fs::remove_file(path);
+            return Err(err);
+        }
+        Ok(written)
+    }
+
     /// Make a POST request
     pub async fn post<T: Serialize>(&self, endpoint: &str, body: Option<T>) -> Result<Value> {
         self.request(Method::POST, endpoint, None, body).await
@@ -102,18 +383,165 @@ impl FreeAgentClient {
         self.request(Method::DELETE, endpoint, None, None::<()>).await
     }
 
-    /// Make an HTTP request with automatic retry on rate limit
-    async fn request<T: Serialize>(
+    /// Make an HTTP request with any method, with the same auth, retry and
+    /// error handling as the typed helpers
+    pub async fn request<T: Serialize>(
         &self,
         method: Method,
         endpoint: &str,
         params: Option<HashMap<String, String>>,
         body: Option<T>,
     ) -> Result<Value> {
+        let (_, value) = self.request_with_headers(method, endpoint, params, body).await?;
+        Ok(value)
+    }
+
+    /// Make an HTTP request, returning the response headers alongside the body
+    async fn request_with_headers<T: Serialize>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        params: Option<HashMap<String, String>>,
+        body: Option<T>,
+    ) -> Result<(HeaderMap, Value)> {
+        let body = body
+            .map(|b| serde_json::to_value(b))
+            .transpose()
+            .context("Failed to serialize request body")?;
+
+        if self.dry_run && method != Method::GET {
+            return Ok((HeaderMap::new(), self.describe_request(&method, endpoint, params.as_ref(), body)?));
+        }
+
+        let (status, headers, text) = self
+            .send(method.clone(), endpoint, params, body, HeaderMap::new())
+            .await?;
+        let value = self.handle_response(status, &text)?;
+
+        if method != Method::GET {
+            if let Some(cache) = &self.cache {
+                if let Err(err) = cache.invalidate(endpoint) {
+                    eprintln!("Warning: {:#}", err);
+                }
+            }
+        }
+
+        Ok((headers, value))
+    }
+
+    /// Describe a request that `--dry-run` skipped
+    fn describe_request(
+        &self,
+        method: &Method,
+        endpoint: &str,
+        params: Option<&HashMap<String, String>>,
+        body: Option<Value>,
+    ) -> Result<Value> {
+        let url = self.build_url(endpoint)?;
+        let url = match params.filter(|p| !p.is_empty()) {
+            Some(params) => reqwest::Url::parse_with_params(&url, params)
+                .map(String::from)
+                .unwrap_or(url),
+            None => url,
+        };
+        Ok(serde_json::json!({
+            "dry_run": true,
+            "method": method.as_str(),
+            "url": url,
+            "body": body,
+        }))
+    }
+
+    /// GET a cacheable endpoint, serving fresh entries from disk and
+    /// revalidating stale ones with a conditional request
+    async fn cached_get(
+        &self,
+        cache: &ResponseCache,
+        ttl: Duration,
+        endpoint: &str,
+        params: Option<HashMap<String, String>>,
+    ) -> Result<Value> {
+        let key = CacheKey::new(&self.base_url, endpoint, params.as_ref());
+        let cached = cache.load(&key);
+        if let Some(entry) = cached.as_ref().filter(|entry| entry.is_fresh(ttl)) {
+            return Ok(entry.body.clone());
+        }
+
+        let mut conditional = HeaderMap::new();
+        if let Some(entry) = &cached {
+            if let Some(etag) = entry.etag.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+                conditional.insert(IF_NONE_MATCH, etag);
+            }
+            if let Some(modified) = entry
+                .last_modified
+                .as_deref()
+                .and_then(|v| HeaderValue::from_str(v).ok())
+            {
+                conditional.insert(IF_MODIFIED_SINCE, modified);
+            }
+        }
+
+        let (status, headers, text) = self
+            .send(Method::GET, endpoint, params, None, conditional)
+            .await?;
+        let entry = match cached {
+            Some(mut entry) if status == StatusCode::NOT_MODIFIED => {
+                entry.touch();
+                entry
+            }
+            _ => {
+                let body = self.handle_response(status, &text)?;
+                let header = |name| {
+                    headers
+                        .get(name)
+                        .and_then(|v: &HeaderValue| v.to_str().ok())
+                        .map(str::to_string)
+                };
+                CacheEntry::new(body, header(ETAG), header(LAST_MODIFIED))
+            }
+        };
+
+        if let Err(err) = cache.store(&key, &entry) {
+            eprintln!("Warning: {:#}", err);
+        }
+        Ok(entry.body)
+    }
+
+    /// Send a request with retries, returning the raw status, headers and body
+    async fn send(
+        &self,
+        method: Method,
+        endpoint: &str,
+        params: Option<HashMap<String, String>>,
+        body: Option<Value>,
+        extra_headers: HeaderMap,
+    ) -> Result<(StatusCode, HeaderMap, String)> {
+        let url = self.build_url(endpoint)?;
+        let recorded_request = self.cassette.as_ref().map(|_| {
+            RecordedRequest::new(
+                method.as_str(),
+                &url,
+                &self.base_url,
+                params.as_ref(),
+                body.as_ref(),
+            )
+        });
+
+        if let (Some(cassette), Some(recorded)) = (&self.cassette, &recorded_request) {
+            if cassette.mode() == CassetteMode::Replay {
+                if let Some(logger) = &self.logger {
+                    logger.replayed(method.as_str(), &url);
+                }
+                let response = cassette.load(recorded)?;
+                let status = StatusCode::from_u16(response.status)
+                    .context("Invalid status code in cassette")?;
+                return Ok((status, header_map(&response.headers), response.body));
+            }
+        }
+
         let mut attempt = 0;
 
         loop {
-            let url = self.build_url(endpoint);
             let token = self.get_access_token().await?;
 
             let mut request = self
@@ -121,7 +549,8 @@ impl FreeAgentClient {
                 .request(method.clone(), &url)
                 .header("Authorization", format!("Bearer {}", token))
                 .header("Accept", "application/json")
-                .header("Content-Type", "application/json");
+                .header("Content-Type", "application/json")
+                .headers(extra_headers.clone());
 
             if let Some(ref p) = params {
                 request = request.query(p);
@@ -131,8 +560,44 @@ impl FreeAgentClient {
                 request = request.json(b);
             }
 
-            let response = request.send().await.context("Request failed")?;
+            if let Some(limiter) = &self.rate_limiter {
+                limiter.acquire().await;
+            }
+
+            let request = request.build().map_err(ApiError::Network)?;
+            if let Some(logger) = &self.logger {
+                logger.request(&request, attempt);
+            }
+            let started = Instant::now();
+
+            let response = match self.http_client.execute(request).await {
+                Ok(response) => response,
+                Err(err) => {
+                    if let Some(logger) = &self.logger {
+                        logger.error(&err, started.elapsed());
+                    }
+                    if attempt < self.retry_config.max_retries
+                        && self.retry_config.should_retry_error(&method, &err)
+                    {
+                        let duration = self.retry_config.backoff_duration(attempt);
+                        eprintln!(
+                            "Request failed ({}). Retrying in {:.1} seconds (attempt {}/{})...",
+                            err,
+                            duration.as_secs_f64(),
+                            attempt + 1,
+                            self.retry_config.max_retries
+                        );
+                        tokio::time::sleep(duration).await;
+                        attempt += 1;
+                        continue;
+                    }
+                    return Err(ApiError::Network(err).into());
+                }
+            };
             let status = response.status();
+            if let Some(logger) = &self.logger {
+                logger.response(status, response.headers(), started.elapsed());
+            }
 
             // Check for rate limit before consuming the response
             if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
@@ -141,18 +606,22 @@ impl FreeAgentClient {
                     RateLimitInfo::from_response(response, fallback_retry_after_secs).await?;
 
                 if attempt >= self.retry_config.max_retries {
-                    anyhow::bail!(
-                        "Rate limit exceeded. Maximum retry attempts ({}) reached. Retry after {} seconds. API Response: {}",
-                        self.retry_config.max_retries,
-                        rate_limit.retry_after_secs,
-                        rate_limit.message
-                    );
+                    return Err(ApiError::RateLimited {
+                        attempts: self.retry_config.max_retries,
+                        retry_after_secs: rate_limit.retry_after_secs,
+                        message: rate_limit.message,
+                    }
+                    .into());
                 }
 
                 let retry_after_secs = rate_limit
                     .retry_after_secs
                     .min(self.retry_config.max_backoff_secs);
                 let duration = Duration::from_secs(retry_after_secs);
+                if let Some(limiter) = &self.rate_limiter {
+                    // Hold back concurrent requests too, not just this one
+                    limiter.pause(duration);
+                }
 
                 eprintln!(
                     "Rate limit exceeded. Waiting {} seconds before retry (attempt {}/{})...",
@@ -165,41 +634,76 @@ impl FreeAgentClient {
                 continue;
             }
             
+            if attempt < self.retry_config.max_retries
+                && self.retry_config.should_retry_status(&method, status)
+            {
+                let duration = self.retry_config.backoff_duration(attempt);
+                eprintln!(
+                    "Server error ({}). Retrying in {:.1} seconds (attempt {}/{})...",
+                    status,
+                    duration.as_secs_f64(),
+                    attempt + 1,
+                    self.retry_config.max_retries
+                );
+                tokio::time::sleep(duration).await;
+                attempt += 1;
+                continue;
+            }
+
             // Handle the response normally
-            return self.handle_response(response).await;
+            let headers = response.headers().clone();
+            let text = if status.is_success() {
+                response.text().await.map_err(ApiError::Network)?
+            } else {
+                response.text().await.unwrap_or_else(|_| "Unknown error".to_string())
+            };
+            if let Some(logger) = &self.logger {
+                logger.response_body(&text);
+            }
+
+            if let (Some(cassette), Some(recorded)) = (&self.cassette, &recorded_request) {
+                let response = RecordedResponse {
+                    status: status.as_u16(),
+                    headers: headers
+                        .iter()
+                        .filter_map(|(k, v)| Some((k.to_string(), v.to_str().ok()?.to_string())))
+                        .collect(),
+                    body: text.clone(),
+                };
+                cassette.save(recorded, &response)?;
+            }
+
+            return Ok((status, headers, text));
         }
     }
 
     /// Handle API response
-    async fn handle_response(&self, response: Response) -> Result<Value> {
-        let status = response.status();
-        
+    fn handle_response(&self, status: StatusCode, text: &str) -> Result<Value> {
         if status.is_success() {
-            if status == reqwest::StatusCode::NO_CONTENT {
-                return Ok(serde_json::json!({"success": true}));
-            }
-            
-            let text = response.text().await.context("Failed to read response body")?;
-            if text.is_empty() {
+            if status == StatusCode::NO_CONTENT || text.is_empty() {
                 return Ok(serde_json::json!({"success": true}));
             }
-            
-            serde_json::from_str(&text).context("Failed to parse JSON response")
+
+            serde_json::from_str(text).context("Failed to parse JSON response")
         } else {
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            
-            // Try to parse as JSON for better error messages
-            if let Ok(error_json) = serde_json::from_str::<Value>(&error_text) {
-                if let Some(errors) = error_json.get("errors") {
-                    anyhow::bail!("API Error ({}): {}", status, errors);
-                }
-            }
-            
-            anyhow::bail!("API Error ({}): {}", status, error_text)
+            Err(ApiError::from_response(status, text).into())
         }
     }
 }
 
+/// Rebuild a header map from recorded headers
+fn header_map(headers: &std::collections::BTreeMap<String, String>) -> HeaderMap {
+    headers
+        .iter()
+        .filter_map(|(k, v)| {
+            Some((
+                HeaderName::from_bytes(k.as_bytes()).ok()?,
+                HeaderValue::from_str(v).ok()?,
+            ))
+        })
+        .collect()
+}
+
 /// Helper to build query parameters
 pub struct QueryBuilder {
     params: HashMap<String, String>,
@@ -305,6 +809,176 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn client_list_returns_single_page_by_default() -> Result<()> {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/widgets");
+                then.status(200)
+                    .header("Link", "<http://example.invalid/v2/widgets?page=2>; rel=\"next\"")
+                    .json_body(json!({"widgets": [{"id": 1}]}));
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?;
+        let result = client.list("widgets", None).await?;
+        assert_eq!(result["widgets"].as_array().map(Vec::len), Some(1));
+        mock.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn client_refuses_next_links_to_other_hosts() -> Result<()> {
+        let server = MockServer::start_async().await;
+        let foreign = MockServer::start_async().await;
+        let stolen = foreign
+            .mock_async(|when, then| {
+                when.any_request();
+                then.status(200).json_body(json!({"widgets": [{"id": 2}]}));
+            })
+            .await;
+        let next = foreign.url("/v2/widgets?page=2");
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/widgets");
+                then.status(200)
+                    .header("Link", format!("<{}>; rel=\"next\"", next))
+                    .json_body(json!({"widgets": [{"id": 1}]}));
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?
+            .with_pagination(Pagination { all: true, limit: None });
+        let err = client.list("widgets", None).await.unwrap_err();
+        assert!(err.to_string().contains("Refusing to send credentials"), "{}", err);
+        stolen.assert_hits_async(0).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn client_list_follows_next_links_with_all() -> Result<()> {
+        let server = MockServer::start_async().await;
+        let next = server.url("/v2/widgets-next?page=2&per_page=100");
+        let first = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/v2/widgets")
+                    .query_param("per_page", "100");
+                then.status(200)
+                    .header("Link", format!("<{}>; rel=\"next\"", next))
+                    .header("X-Total-Count", "3")
+                    .json_body(json!({"widgets": [{"id": 1}, {"id": 2}]}));
+            })
+            .await;
+        let second = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/v2/widgets-next")
+                    .query_param("page", "2");
+                then.status(200)
+                    .header("X-Total-Count", "3")
+                    .json_body(json!({"widgets": [{"id": 3}]}));
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?
+            .with_pagination(Pagination { all: true, limit: None });
+        let result = client.list("widgets", None).await?;
+        assert_eq!(result, json!({"widgets": [{"id": 1}, {"id": 2}, {"id": 3}]}));
+        first.assert_async().await;
+        second.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn client_list_stops_at_limit() -> Result<()> {
+        let server = MockServer::start_async().await;
+        let next = server.url("/v2/widgets-next?page=2");
+        let first = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/widgets");
+                then.status(200)
+                    .header("Link", format!("<{}>; rel=\"next\"", next))
+                    .json_body(json!({"widgets": [{"id": 1}, {"id": 2}]}));
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?
+            .with_pagination(Pagination { all: false, limit: Some(1) });
+        let result = client.list("widgets", None).await?;
+        assert_eq!(result, json!({"widgets": [{"id": 1}]}));
+        first.assert_hits_async(1).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn client_list_pages_hands_over_each_page() -> Result<()> {
+        let server = MockServer::start_async().await;
+        let next = server.url("/v2/widgets-next?page=2");
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/widgets").query_param("per_page", "3");
+                then.status(200)
+                    .header("Link", format!("<{}>; rel=\"next\"", next))
+                    .json_body(json!({"widgets": [{"id": 1}, {"id": 2}]}));
+            })
+            .await;
+        let second = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/widgets-next");
+                then.status(200).json_body(json!({"widgets": [{"id": 3}, {"id": 4}]}));
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?
+            .with_pagination(Pagination { all: true, limit: Some(3) });
+        let mut pages = Vec::new();
+        client
+            .list_pages("widgets", None, |page| {
+                pages.push(page);
+                Ok(())
+            })
+            .await?;
+        assert_eq!(
+            pages,
+            vec![json!({"widgets": [{"id": 1}, {"id": 2}]}), json!({"widgets": [{"id": 3}]})]
+        );
+        second.assert_async().await;
+
+        // The merged list is the same records in one collection
+        let all = client.list("widgets", None).await?;
+        assert_eq!(all, json!({"widgets": [{"id": 1}, {"id": 2}, {"id": 3}]}));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn client_records_then_replays_without_network() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(POST).path("/v2/widgets");
+                then.status(201).json_body(json!({"widget": {"id": 7}}));
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?
+            .with_recording(dir.path());
+        let recorded = client.post("widgets", Some(json!({"widget": {"name": "a"}}))).await?;
+        mock.assert_hits_async(1).await;
+
+        let mut replay = FreeAgentClient::replay(false, dir.path())?;
+        replay.base_url = server.url("/v2");
+        let replayed = replay.post("widgets", Some(json!({"widget": {"name": "a"}}))).await?;
+        assert_eq!(replayed, recorded);
+        mock.assert_hits_async(1).await;
+
+        let missing = replay.post("widgets", Some(json!({"widget": {"name": "b"}}))).await;
+        assert!(missing.unwrap_err().to_string().contains("No recorded response"));
+        Ok(())
+    }
+
     #[tokio::test]
     async fn client_handles_no_content_as_success() -> Result<()> {
         let server = MockServer::start_async().await;
@@ -357,6 +1031,25 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn client_returns_structured_errors() -> Result<()> {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/missing");
+                then.status(404)
+                    .json_body(json!({"errors": {"error": {"message": "Resource not found"}}}));
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?;
+        let err = client.get("missing", None).await.unwrap_err();
+        let api_err = err.downcast_ref::<ApiError>().expect("should be an ApiError");
+        assert_eq!(api_err.kind(), "not_found");
+        assert_eq!(api_err.exit_code(), 5);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn client_retries_on_rate_limit_with_retry_after() -> Result<()> {
         let server = MockServer::start_async().await;
@@ -378,6 +1071,7 @@ mod tests {
             initial_backoff_secs: 1,
             max_backoff_secs: 60,
             exponential_backoff: true,
+            ..RetryConfig::default()
         };
 
         let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?
@@ -412,6 +1106,7 @@ mod tests {
             initial_backoff_secs: 1,
             max_backoff_secs: 60,
             exponential_backoff: true,
+            ..RetryConfig::default()
         };
 
         let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?
@@ -426,6 +1121,267 @@ mod tests {
         Ok(())
     }
 
+    fn fast_retry_config() -> RetryConfig {
+        RetryConfig {
+            max_retries: 2,
+            initial_backoff_secs: 0,
+            ..RetryConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn client_retries_server_errors_for_get() -> Result<()> {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/flaky");
+                then.status(503).body("Service Unavailable");
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?
+            .with_retry_config(fast_retry_config());
+        let err = client.get("flaky", None).await.unwrap_err();
+        assert_eq!(err.downcast_ref::<ApiError>().map(ApiError::kind), Some("server"));
+        mock.assert_hits_async(3).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn client_does_not_retry_post_on_server_error_by_default() -> Result<()> {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(POST).path("/v2/flaky");
+                then.status(502).body("Bad Gateway");
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?
+            .with_retry_config(fast_retry_config());
+        assert!(client.post::<()>("flaky", None).await.is_err());
+        mock.assert_hits_async(1).await;
+
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?
+            .with_retry_config(RetryConfig {
+                retry_post: true,
+                ..fast_retry_config()
+            });
+        assert!(client.post::<()>("flaky", None).await.is_err());
+        mock.assert_hits_async(4).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn client_retries_connection_failures() -> Result<()> {
+        // Nothing listens on this port once the listener is dropped
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}/v2", listener.local_addr()?);
+        drop(listener);
+
+        let client = FreeAgentClient::new_for_test(url, test_tokens())?
+            .with_retry_config(fast_retry_config());
+        let err = client.get("widgets", None).await.unwrap_err();
+        assert_eq!(err.downcast_ref::<ApiError>().map(ApiError::kind), Some("network"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn client_serves_reference_data_from_cache() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/currencies");
+                then.status(200).json_body(json!({"currencies": [{"code": "GBP"}]}));
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?
+            .with_cache(ResponseCache::new(dir.path()));
+        let first = client.get("currencies", None).await?;
+        let second = client.get("currencies", None).await?;
+        assert_eq!(first, second);
+        mock.assert_hits_async(1).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn client_revalidates_stale_cache_entries() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache = ResponseCache::new(dir.path());
+        let server = MockServer::start_async().await;
+        let base_url = server.url("/v2");
+
+        let mut stale = CacheEntry::new(json!({"company": {"name": "Acme"}}), Some("\"v1\"".to_string()), None);
+        stale.stored_at -= chrono::Duration::days(1);
+        cache.store(&CacheKey::new(&base_url, "company", None), &stale)?;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/v2/company")
+                    .header("If-None-Match", "\"v1\"");
+                then.status(304);
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(base_url.clone(), test_tokens())?.with_cache(cache.clone());
+        let result = client.get("company", None).await?;
+        assert_eq!(result["company"]["name"], "Acme");
+        mock.assert_hits_async(1).await;
+
+        let refreshed = cache.load(&CacheKey::new(&base_url, "company", None)).expect("entry kept");
+        assert!(refreshed.is_fresh(Duration::from_secs(60)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn client_invalidates_cache_on_write() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache = ResponseCache::new(dir.path());
+        let server = MockServer::start_async().await;
+        let base_url = server.url("/v2");
+        let key = CacheKey::new(&base_url, "users/me", None);
+        cache.store(&key, &CacheEntry::new(json!({"user": {}}), None, None))?;
+
+        server
+            .mock_async(|when, then| {
+                when.method(httpmock::Method::PUT).path("/v2/users/me");
+                then.status(200).json_body(json!({"user": {"first_name": "Ada"}}));
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(base_url, test_tokens())?.with_cache(cache.clone());
+        client.put("users/me", Some(json!({"user": {"first_name": "Ada"}}))).await?;
+        assert!(cache.load(&key).is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn client_dry_run_skips_writes_but_sends_reads() -> Result<()> {
+        let server = MockServer::start_async().await;
+        let write = server
+            .mock_async(|when, then| {
+                when.method(POST).path("/v2/widgets");
+                then.status(201).json_body(json!({"widget": {}}));
+            })
+            .await;
+        let read = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/widgets");
+                then.status(200).json_body(json!({"widgets": []}));
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?.with_dry_run(true);
+        let result = client.post("widgets", Some(json!({"widget": {"name": "a"}}))).await?;
+        assert_eq!(
+            result,
+            json!({
+                "dry_run": true,
+                "method": "POST",
+                "url": server.url("/v2/widgets"),
+                "body": {"widget": {"name": "a"}},
+            })
+        );
+        let deleted = client.delete("widgets/1").await?;
+        assert_eq!(deleted["method"], "DELETE");
+        assert_eq!(deleted["body"], Value::Null);
+
+        client.get("widgets", None).await?;
+        write.assert_hits_async(0).await;
+        read.assert_hits_async(1).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn client_downloads_files_to_disk() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/files/receipt.pdf");
+                then.status(200).body("%PDF-1.4 content");
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/files/expired.pdf");
+                then.status(403).body("Request has expired");
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?;
+        let path = dir.path().join("nested/receipt.pdf");
+        let bytes = client.download(&server.url("/files/receipt.pdf"), &path).await?;
+        assert_eq!(bytes, 16);
+        assert_eq!(std::fs::read(&path)?, b"%PDF-1.4 content");
+
+        let expired = dir.path().join("expired.pdf");
+        let err = client.download(&server.url("/files/expired.pdf"), &expired).await.unwrap_err();
+        assert_eq!(err.downcast_ref::<ApiError>().map(ApiError::kind), Some("forbidden"));
+        assert!(!expired.exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn client_list_all_follows_pages_without_all_flag() -> Result<()> {
+        let server = MockServer::start_async().await;
+        let next = server.url("/v2/widgets-next?page=2");
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/widgets");
+                then.status(200)
+                    .header("Link", format!("<{}>; rel=\"next\"", next))
+                    .json_body(json!({"widgets": [{"id": 1}]}));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/widgets-next");
+                then.status(200).json_body(json!({"widgets": [{"id": 2}]}));
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?;
+        let result = client.list_all("widgets", None).await?;
+        assert_eq!(result, json!({"widgets": [{"id": 1}, {"id": 2}]}));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn client_paces_requests_with_shared_rate_limiter() -> Result<()> {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/widgets");
+                then.status(200).json_body(json!({"widgets": []}));
+            })
+            .await;
+
+        // Burst of 1, then one request every 100ms
+        let limiter = Arc::new(RateLimiter::new(&[crate::api::rate_limit::Quota {
+            limit: 4,
+            per: Duration::from_millis(400),
+        }]));
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?
+            .with_rate_limiter(limiter);
+
+        let start = std::time::Instant::now();
+        let (a, b, c) = tokio::join!(
+            client.get("widgets", None),
+            client.get("widgets", None),
+            client.get("widgets", None),
+        );
+        a?;
+        b?;
+        c?;
+        assert!(start.elapsed() >= Duration::from_millis(190));
+        mock.assert_hits_async(3).await;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn client_uses_exponential_backoff_without_retry_after() -> Result<()> {
         let server = MockServer::start_async().await;
@@ -444,6 +1400,7 @@ mod tests {
             initial_backoff_secs: 1,
             max_backoff_secs: 60,
             exponential_backoff: true,
+            ..RetryConfig::default()
         };
 
         let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?
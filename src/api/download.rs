@@ -0,0 +1,128 @@
+//! Saving documents returned by the API to disk.
+//!
+//! PDF endpoints (`invoices/:id/pdf` and friends) return the document as
+//! base64 inside the JSON body, while attachments expose a short-lived
+//! `content_src` URL that is streamed straight to a file.
+
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Decode the `pdf.content` field of a PDF endpoint response
+pub fn decode_pdf(response: &Value) -> Result<Vec<u8>> {
+    let content = response
+        .pointer("/pdf/content")
+        .and_then(Value::as_str)
+        .context("Response did not include pdf.content")?;
+    // Long base64 payloads may be wrapped across lines
+    let content: String = content.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    BASE64
+        .decode(content)
+        .context("Failed to decode PDF content")
+}
+
+/// Decode a PDF endpoint response and write it to `path`, returning its size
+pub fn save_pdf(response: &Value, path: &Path) -> Result<usize> {
+    let bytes = decode_pdf(response)?;
+    write_file(path, &bytes)?;
+    Ok(bytes.len())
+}
+
+/// Replace characters that are unsafe in file names
+pub fn sanitize_file_name(name: &str) -> String {
+    let cleaned: String = name
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim_start_matches('.');
+    if cleaned.is_empty() {
+        "download".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+/// Resolve `--output`: a directory (existing, or written with a trailing
+/// separator) gets `default_name` appended, anything else is used as-is
+pub fn output_path(output: Option<&Path>, default_name: &str) -> PathBuf {
+    let default_name = sanitize_file_name(default_name);
+    match output {
+        None => PathBuf::from(default_name),
+        Some(path) => {
+            let raw = path.as_os_str().to_string_lossy();
+            if path.is_dir() || raw.ends_with('/') || raw.ends_with(std::path::MAIN_SEPARATOR) {
+                path.join(default_name)
+            } else {
+                path.to_path_buf()
+            }
+        }
+    }
+}
+
+/// Create the parent directory of `path` if needed
+pub fn ensure_parent(path: &Path) -> Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display())),
+        _ => Ok(()),
+    }
+}
+
+/// Write `bytes` to `path`, creating parent directories
+pub fn write_file(path: &Path, bytes: &[u8]) -> Result<()> {
+    ensure_parent(path)?;
+    fs::write(path, bytes).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn decodes_pdf_content() -> Result<()> {
+        let response = json!({"pdf": {"content": "JVBE\nRi0xLjQ="}});
+        assert_eq!(decode_pdf(&response)?, b"%PDF-1.4");
+        assert!(decode_pdf(&json!({"invoice": {}})).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn sanitizes_file_names() {
+        assert_eq!(sanitize_file_name("INV/2024:01"), "INV_2024_01");
+        assert_eq!(sanitize_file_name("../secret"), "_secret");
+        assert_eq!(sanitize_file_name("  "), "download");
+    }
+
+    #[test]
+    fn resolves_output_paths() -> Result<()> {
+        let dir = tempdir()?;
+        assert_eq!(output_path(None, "a.pdf"), PathBuf::from("a.pdf"));
+        assert_eq!(output_path(Some(dir.path()), "a.pdf"), dir.path().join("a.pdf"));
+        assert_eq!(
+            output_path(Some(Path::new("archive/")), "a.pdf"),
+            Path::new("archive/").join("a.pdf")
+        );
+        let file = dir.path().join("custom.pdf");
+        assert_eq!(output_path(Some(&file), "a.pdf"), file);
+        Ok(())
+    }
+
+    #[test]
+    fn write_file_creates_parent_directories() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("2024/01/a.pdf");
+        write_file(&path, b"%PDF")?;
+        assert_eq!(fs::read(path)?, b"%PDF");
+        Ok(())
+    }
+}
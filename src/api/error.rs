@@ -0,0 +1,300 @@
+//! Structured API errors and their process exit codes.
+
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Exit code for errors that are not API failures
+pub const EXIT_GENERAL: i32 = 1;
+
+/// A single validation problem reported by the API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    /// Field the message refers to, when the API names one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "{}: {}", field, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Errors returned by the FreeAgent API client
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    /// 401 - the access token was rejected
+    #[error("API Error ({}): {message}", status_text(401))]
+    Unauthorized { message: String },
+
+    /// 403 - the user lacks permission for this resource
+    #[error("API Error ({}): {message}", status_text(403))]
+    Forbidden { message: String },
+
+    /// 404 - the resource does not exist
+    #[error("API Error ({}): {message}", status_text(404))]
+    NotFound { message: String },
+
+    /// 400/422 - the request was rejected, usually with per-field messages
+    #[error("API Error ({}): {}", status_text(*status), join_errors(errors))]
+    Validation { status: u16, errors: Vec<FieldError> },
+
+    /// 429 - still rate limited after exhausting retries
+    #[error(
+        "Rate limit exceeded. Maximum retry attempts ({attempts}) reached. Retry after {retry_after_secs} seconds. API Response: {message}"
+    )]
+    RateLimited {
+        attempts: u32,
+        retry_after_secs: u64,
+        message: String,
+    },
+
+    /// 5xx - FreeAgent failed to handle the request
+    #[error("API Error ({}): {message}", status_text(*status))]
+    Server { status: u16, message: String },
+
+    /// Any other unsuccessful status
+    #[error("API Error ({}): {message}", status_text(*status))]
+    Http { status: u16, message: String },
+
+    /// The request could not be sent or the response could not be read
+    #[error("Request failed: {0}")]
+    Network(#[from] reqwest::Error),
+}
+
+impl ApiError {
+    /// Build an error from an unsuccessful response
+    pub fn from_response(status: StatusCode, body: &str) -> Self {
+        let errors = serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|json| json.get("errors").map(parse_field_errors))
+            .unwrap_or_default();
+
+        let message = if errors.is_empty() {
+            body.trim().to_string()
+        } else {
+            join_errors(&errors)
+        };
+
+        match status.as_u16() {
+            401 => Self::Unauthorized { message },
+            403 => Self::Forbidden { message },
+            404 => Self::NotFound { message },
+            400 | 422 => {
+                let errors = if errors.is_empty() {
+                    vec![FieldError {
+                        field: None,
+                        message,
+                    }]
+                } else {
+                    errors
+                };
+                Self::Validation {
+                    status: status.as_u16(),
+                    errors,
+                }
+            }
+            code if status.is_server_error() => Self::Server {
+                status: code,
+                message,
+            },
+            code => Self::Http {
+                status: code,
+                message,
+            },
+        }
+    }
+
+    /// Stable machine-readable name of the error kind
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Unauthorized { .. } => "unauthorized",
+            Self::Forbidden { .. } => "forbidden",
+            Self::NotFound { .. } => "not_found",
+            Self::Validation { .. } => "validation",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::Server { .. } => "server",
+            Self::Http { .. } => "http",
+            Self::Network(_) => "network",
+        }
+    }
+
+    /// Process exit code for this error kind
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Unauthorized { .. } => 3,
+            Self::Forbidden { .. } => 4,
+            Self::NotFound { .. } => 5,
+            Self::Validation { .. } => 6,
+            Self::RateLimited { .. } => 7,
+            Self::Server { .. } => 8,
+            Self::Network(_) => 9,
+            Self::Http { .. } => 10,
+        }
+    }
+
+    /// HTTP status of the failed response, if one was received
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::Unauthorized { .. } => Some(401),
+            Self::Forbidden { .. } => Some(403),
+            Self::NotFound { .. } => Some(404),
+            Self::Validation { status, .. } | Self::Server { status, .. } | Self::Http { status, .. } => {
+                Some(*status)
+            }
+            Self::RateLimited { .. } => Some(429),
+            Self::Network(err) => err.status().map(|s| s.as_u16()),
+        }
+    }
+
+    /// JSON description of the error for `--error-format json`
+    pub fn to_json(&self) -> Value {
+        let mut error = json!({
+            "kind": self.kind(),
+            "exit_code": self.exit_code(),
+            "status": self.status(),
+            "message": self.to_string(),
+        });
+        match self {
+            Self::Validation { errors, .. } => {
+                error["errors"] = json!(errors);
+            }
+            Self::RateLimited {
+                retry_after_secs, ..
+            } => {
+                error["retry_after_secs"] = json!(retry_after_secs);
+            }
+            _ => {}
+        }
+        json!({ "error": error })
+    }
+}
+
+fn status_text(code: u16) -> String {
+    StatusCode::from_u16(code)
+        .map(|s| s.to_string())
+        .unwrap_or_else(|_| code.to_string())
+}
+
+fn join_errors(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Parse FreeAgent's `errors` value into field/message pairs.
+///
+/// Handles `[{"message": ..}]`, `{"error": {"message": ..}}`,
+/// `{"error": [..]}` and Rails-style `{"field": ["message", ..]}` shapes.
+pub fn parse_field_errors(errors: &Value) -> Vec<FieldError> {
+    match errors {
+        Value::Array(items) => items.iter().flat_map(parse_field_errors).collect(),
+        Value::String(message) => vec![FieldError {
+            field: None,
+            message: message.clone(),
+        }],
+        Value::Object(obj) => {
+            if let Some(message) = obj.get("message").and_then(Value::as_str) {
+                let field = ["field", "attribute", "param"]
+                    .iter()
+                    .find_map(|key| obj.get(*key).and_then(Value::as_str))
+                    .map(|s| s.to_string());
+                return vec![FieldError {
+                    field,
+                    message: message.to_string(),
+                }];
+            }
+            if let Some(inner) = obj.get("error") {
+                return parse_field_errors(inner);
+            }
+            obj.iter()
+                .flat_map(|(field, messages)| {
+                    parse_field_errors(messages)
+                        .into_iter()
+                        .map(move |mut err| {
+                            err.field.get_or_insert_with(|| field.clone());
+                            err
+                        })
+                })
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_error_message() {
+        let errors = parse_field_errors(&json!({"error": {"message": "Resource not found"}}));
+        assert_eq!(
+            errors,
+            vec![FieldError {
+                field: None,
+                message: "Resource not found".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_error_arrays_and_field_maps() {
+        let errors = parse_field_errors(&json!([
+            {"message": "Dated on can't be blank", "field": "dated_on"},
+            {"message": "Contact is invalid"}
+        ]));
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].field.as_deref(), Some("dated_on"));
+
+        let errors = parse_field_errors(&json!({"reference": ["is taken", "is too long"]}));
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[1].to_string(), "reference: is too long");
+    }
+
+    #[test]
+    fn maps_status_codes_to_variants_and_exit_codes() {
+        let body = r#"{"errors": {"error": {"message": "nope"}}}"#;
+        let cases = [
+            (401, "unauthorized", 3),
+            (403, "forbidden", 4),
+            (404, "not_found", 5),
+            (422, "validation", 6),
+            (503, "server", 8),
+            (409, "http", 10),
+        ];
+        for (status, kind, code) in cases {
+            let err = ApiError::from_response(StatusCode::from_u16(status).unwrap(), body);
+            assert_eq!(err.kind(), kind);
+            assert_eq!(err.exit_code(), code);
+            assert_eq!(err.status(), Some(status));
+            assert!(err.to_string().contains("API Error"));
+            assert!(err.to_string().contains("nope"));
+        }
+    }
+
+    #[test]
+    fn validation_json_includes_field_errors() {
+        let err = ApiError::from_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            r#"{"errors": [{"message": "can't be blank", "field": "contact"}]}"#,
+        );
+        let json = err.to_json();
+        assert_eq!(json["error"]["kind"], "validation");
+        assert_eq!(json["error"]["status"], 422);
+        assert_eq!(json["error"]["errors"][0]["field"], "contact");
+    }
+
+    #[test]
+    fn non_json_body_becomes_message() {
+        let err = ApiError::from_response(StatusCode::BAD_GATEWAY, "Bad gateway\n");
+        assert_eq!(err.to_string(), "API Error (502 Bad Gateway): Bad gateway");
+    }
+}
@@ -0,0 +1,281 @@
+//! HTTP request/response logging for `--verbose` and `--trace`.
+//!
+//! Verbose logging shows the request line, status, timing and rate-limit
+//! headers. Trace logging adds headers and bodies. Bearer tokens and any
+//! `access_token`, `refresh_token` or `client_secret` values are redacted
+//! before anything is written.
+
+use anyhow::{Context, Result};
+use reqwest::header::{HeaderMap, AUTHORIZATION};
+use serde_json::Value;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// JSON keys whose values are never logged
+const SECRET_KEYS: &[&str] = &["access_token", "refresh_token", "client_secret"];
+
+/// Response headers shown at verbose level
+const RATE_LIMIT_HEADERS: &[&str] = &[
+    "retry-after",
+    "x-ratelimit-limit",
+    "x-ratelimit-remaining",
+    "x-ratelimit-reset",
+];
+
+/// How much HTTP traffic to log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    /// Request line, status, timing and rate-limit headers
+    Verbose,
+    /// Everything, including headers and bodies
+    Trace,
+}
+
+/// Writes redacted HTTP traffic to stderr or a file
+pub struct HttpLogger {
+    level: LogLevel,
+    sink: Mutex<Box<dyn Write + Send>>,
+}
+
+impl std::fmt::Debug for HttpLogger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpLogger").field("level", &self.level).finish()
+    }
+}
+
+impl HttpLogger {
+    /// Log to stderr
+    pub fn stderr(level: LogLevel) -> Self {
+        Self::with_writer(level, Box::new(std::io::stderr()))
+    }
+
+    /// Append to the file at `path`
+    pub fn file(level: LogLevel, path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open log file {}", path.display()))?;
+        Ok(Self::with_writer(level, Box::new(file)))
+    }
+
+    /// Log to any writer
+    pub fn with_writer(level: LogLevel, writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            level,
+            sink: Mutex::new(writer),
+        }
+    }
+
+    pub fn level(&self) -> LogLevel {
+        self.level
+    }
+
+    fn write_lines(&self, lines: &[String]) {
+        let mut sink = self.sink.lock().unwrap_or_else(|e| e.into_inner());
+        for line in lines {
+            // Logging must never fail the request itself
+            let _ = writeln!(sink, "{}", line);
+        }
+        let _ = sink.flush();
+    }
+
+    /// Log an outgoing request
+    pub fn request(&self, request: &reqwest::Request, attempt: u32) {
+        let mut lines = vec![format!(
+            "> {} {}{}",
+            request.method(),
+            request.url(),
+            if attempt > 0 {
+                format!(" (retry {})", attempt)
+            } else {
+                String::new()
+            }
+        )];
+
+        if self.level >= LogLevel::Trace {
+            lines.extend(header_lines('>', request.headers(), |_| true));
+            if let Some(body) = request.body().and_then(|b| b.as_bytes()) {
+                lines.push(format!("> {}", redact_body(&String::from_utf8_lossy(body))));
+            }
+        }
+
+        self.write_lines(&lines);
+    }
+
+    /// Log a replayed request, which has no wire representation
+    pub fn replayed(&self, method: &str, url: &str) {
+        self.write_lines(&[format!("> {} {} (replayed)", method, url)]);
+    }
+
+    /// Log a response's status, timing and headers
+    pub fn response(&self, status: reqwest::StatusCode, headers: &HeaderMap, elapsed: Duration) {
+        let mut lines = vec![format!("< {} in {}ms", status, elapsed.as_millis())];
+        if self.level >= LogLevel::Trace {
+            lines.extend(header_lines('<', headers, |_| true));
+        } else {
+            lines.extend(header_lines('<', headers, |name| {
+                RATE_LIMIT_HEADERS.contains(&name)
+            }));
+        }
+        self.write_lines(&lines);
+    }
+
+    /// Log a response body (trace only)
+    pub fn response_body(&self, body: &str) {
+        if self.level >= LogLevel::Trace && !body.is_empty() {
+            self.write_lines(&[format!("< {}", redact_body(body))]);
+        }
+    }
+
+    /// Log a transport failure
+    pub fn error(&self, error: &reqwest::Error, elapsed: Duration) {
+        self.write_lines(&[format!("< failed after {}ms: {}", elapsed.as_millis(), error)]);
+    }
+}
+
+fn header_lines<'a>(
+    prefix: char,
+    headers: &'a HeaderMap,
+    include: impl Fn(&str) -> bool + 'a,
+) -> impl Iterator<Item = String> + 'a {
+    headers
+        .iter()
+        .filter(move |(name, _)| include(name.as_str()))
+        .map(move |(name, value)| {
+            let value = if *name == AUTHORIZATION {
+                redact_authorization(value.to_str().unwrap_or_default())
+            } else {
+                value.to_str().unwrap_or("<binary>").to_string()
+            };
+            format!("{} {}: {}", prefix, name, value)
+        })
+}
+
+/// Keep the auth scheme but hide the credential
+pub fn redact_authorization(value: &str) -> String {
+    match value.split_once(' ') {
+        Some((scheme, _)) => format!("{} {}", scheme, REDACTED),
+        None => REDACTED.to_string(),
+    }
+}
+
+/// Redact secret values in a JSON body; non-JSON bodies are returned unchanged
+pub fn redact_body(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            value.to_string()
+        }
+        Err(_) => body.to_string(),
+    }
+}
+
+/// Replace secret values anywhere in a JSON document
+pub fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                if SECRET_KEYS.contains(&key.as_str()) {
+                    *item = Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(item);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    /// Writer that shares its buffer with the test
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Buffer {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    fn request() -> reqwest::Request {
+        reqwest::Client::new()
+            .post("https://api.freeagent.com/v2/invoices?view=open")
+            .header(AUTHORIZATION, "Bearer secret-access")
+            .json(&json!({"invoice": {"reference": "INV-1"}, "refresh_token": "secret-refresh"}))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn redacts_nested_secrets() {
+        let mut value = json!({"tokens": [{"access_token": "a", "refresh_token": "r"}], "client_secret": "s", "name": "x"});
+        redact_value(&mut value);
+        assert_eq!(
+            value,
+            json!({"tokens": [{"access_token": REDACTED, "refresh_token": REDACTED}], "client_secret": REDACTED, "name": "x"})
+        );
+        assert_eq!(redact_body("not json"), "not json");
+        assert_eq!(redact_authorization("Bearer abc"), "Bearer [REDACTED]");
+    }
+
+    #[test]
+    fn verbose_logs_request_line_and_rate_limit_headers_only() {
+        let buffer = Buffer::default();
+        let logger = HttpLogger::with_writer(LogLevel::Verbose, Box::new(buffer.clone()));
+        logger.request(&request(), 0);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("119"));
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+        logger.response(reqwest::StatusCode::OK, &headers, Duration::from_millis(42));
+        logger.response_body("{\"ok\":true}");
+
+        let log = buffer.contents();
+        assert!(log.contains("> POST https://api.freeagent.com/v2/invoices?view=open"));
+        assert!(log.contains("< 200 OK in 42ms"));
+        assert!(log.contains("x-ratelimit-remaining: 119"));
+        assert!(!log.contains("content-type"));
+        assert!(!log.contains("INV-1"));
+        assert!(!log.contains("ok"));
+    }
+
+    #[test]
+    fn trace_logs_bodies_without_secrets() {
+        let buffer = Buffer::default();
+        let logger = HttpLogger::with_writer(LogLevel::Trace, Box::new(buffer.clone()));
+        logger.request(&request(), 1);
+        logger.response_body("{\"access_token\":\"new-access\",\"expires_in\":3600}");
+
+        let log = buffer.contents();
+        assert!(log.contains("(retry 1)"));
+        assert!(log.contains("authorization: Bearer [REDACTED]"));
+        assert!(log.contains("INV-1"));
+        assert!(log.contains("expires_in"));
+        assert!(!log.contains("secret-access"));
+        assert!(!log.contains("secret-refresh"));
+        assert!(!log.contains("new-access"));
+    }
+}
@@ -1,6 +1,22 @@
 //! FreeAgent API module.
 
+pub mod cache;
+pub mod cassette;
 pub mod client;
+pub mod download;
+pub mod error;
+pub mod logging;
+pub mod pagination;
+pub mod rate_limit;
+pub mod resolve;
+pub mod resources;
 pub mod retry;
+pub mod upload;
 
+pub use cache::ResponseCache;
 pub use client::{FreeAgentClient, QueryBuilder};
+pub use error::{ApiError, FieldError};
+pub use logging::{HttpLogger, LogLevel};
+pub use pagination::Pagination;
+pub use rate_limit::RateLimiter;
+pub use resolve::ResourceKind;
@@ -0,0 +1,173 @@
+//! Pagination support for list endpoints.
+//!
+//! FreeAgent paginates list responses and advertises further pages through the
+//! `Link` header (`rel="next"`) and the total record count through `X-Total-Count`.
+
+use reqwest::header::HeaderMap;
+use serde_json::Value;
+
+/// Largest page size accepted by the FreeAgent API
+pub const MAX_PER_PAGE: i32 = 100;
+
+/// Pagination behavior for list requests
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Pagination {
+    /// Follow `rel="next"` links until every page has been fetched
+    pub all: bool,
+    /// Maximum number of records to return across all pages
+    pub limit: Option<usize>,
+}
+
+impl Pagination {
+    /// Whether list requests should follow further pages
+    pub fn is_enabled(&self) -> bool {
+        self.all || self.limit.is_some()
+    }
+
+    /// Page size to request, no larger than a `limit` needs
+    pub fn per_page(&self) -> i32 {
+        match self.limit {
+            Some(limit) => limit.clamp(1, MAX_PER_PAGE as usize) as i32,
+            None => MAX_PER_PAGE,
+        }
+    }
+}
+
+/// Extract the `rel="next"` URL from a `Link` header
+pub fn next_link(headers: &HeaderMap) -> Option<String> {
+    let link = headers.get("Link")?.to_str().ok()?;
+
+    link.split(',').find_map(|part| {
+        let mut sections = part.split(';');
+        let target = sections.next()?.trim();
+        let is_next = sections.any(|param| {
+            let param = param.trim();
+            param == "rel=\"next\"" || param == "rel=next"
+        });
+        if !is_next {
+            return None;
+        }
+        target
+            .strip_prefix('<')
+            .and_then(|t| t.strip_suffix('>'))
+            .map(|t| t.to_string())
+    })
+}
+
+/// Extract the `X-Total-Count` header
+pub fn total_count(headers: &HeaderMap) -> Option<usize> {
+    headers
+        .get("X-Total-Count")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok())
+}
+
+/// Find the key of the resource collection in a list response
+pub fn collection_key(page: &Value) -> Option<String> {
+    page.as_object()?
+        .iter()
+        .find(|(_, value)| value.is_array())
+        .map(|(key, _)| key.clone())
+}
+
+/// Number of records in the collection of a list response
+pub fn record_count(page: &Value) -> usize {
+    collection_key(page)
+        .and_then(|key| page.get(&key).and_then(Value::as_array).map(Vec::len))
+        .unwrap_or(0)
+}
+
+/// Append the collection of `page` onto the collection of `merged`
+pub fn merge_page(merged: &mut Value, page: Value) {
+    let Some(key) = collection_key(&page) else {
+        return;
+    };
+
+    let Value::Object(mut page_obj) = page else {
+        return;
+    };
+    let Some(Value::Array(items)) = page_obj.remove(&key) else {
+        return;
+    };
+
+    if let Some(obj) = merged.as_object_mut() {
+        match obj.get_mut(&key) {
+            Some(Value::Array(existing)) => existing.extend(items),
+            _ => {
+                obj.insert(key, Value::Array(items));
+            }
+        }
+    }
+}
+
+/// Truncate the collection of a list response to at most `limit` records
+pub fn truncate(merged: &mut Value, limit: usize) {
+    if let Some(key) = collection_key(merged) {
+        if let Some(Value::Array(items)) = merged.get_mut(&key) {
+            items.truncate(limit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use serde_json::json;
+
+    #[test]
+    fn next_link_parses_rel_next() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Link",
+            HeaderValue::from_static(
+                "<https://api.freeagent.com/v2/invoices?page=1>; rel=\"prev\", <https://api.freeagent.com/v2/invoices?page=3>; rel=\"next\", <https://api.freeagent.com/v2/invoices?page=9>; rel=\"last\"",
+            ),
+        );
+        assert_eq!(
+            next_link(&headers).as_deref(),
+            Some("https://api.freeagent.com/v2/invoices?page=3")
+        );
+    }
+
+    #[test]
+    fn next_link_missing_on_last_page() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Link",
+            HeaderValue::from_static("<https://api.freeagent.com/v2/invoices?page=1>; rel=\"first\""),
+        );
+        assert!(next_link(&headers).is_none());
+        assert!(next_link(&HeaderMap::new()).is_none());
+    }
+
+    #[test]
+    fn total_count_parses_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Total-Count", HeaderValue::from_static("42"));
+        assert_eq!(total_count(&headers), Some(42));
+    }
+
+    #[test]
+    fn merge_page_extends_collection() {
+        let mut merged = json!({"invoices": [{"id": 1}]});
+        merge_page(&mut merged, json!({"invoices": [{"id": 2}, {"id": 3}]}));
+        assert_eq!(record_count(&merged), 3);
+        truncate(&mut merged, 2);
+        assert_eq!(merged, json!({"invoices": [{"id": 1}, {"id": 2}]}));
+    }
+
+    #[test]
+    fn pagination_enabled_by_all_or_limit() {
+        assert!(!Pagination::default().is_enabled());
+        assert!(Pagination { all: true, limit: None }.is_enabled());
+        assert!(Pagination { all: false, limit: Some(5) }.is_enabled());
+    }
+
+    #[test]
+    fn per_page_shrinks_to_the_limit() {
+        assert_eq!(Pagination { all: true, limit: None }.per_page(), MAX_PER_PAGE);
+        assert_eq!(Pagination { all: false, limit: Some(5) }.per_page(), 5);
+        assert_eq!(Pagination { all: false, limit: Some(250) }.per_page(), MAX_PER_PAGE);
+    }
+}
@@ -0,0 +1,221 @@
+//! Client-side rate limiting.
+//!
+//! FreeAgent enforces per-minute and per-hour request quotas. Rather than only
+//! reacting to 429 responses, the client paces outgoing requests with one token
+//! bucket per quota. Each bucket refills at the quota's own rate, so sustained
+//! throughput matches what the API allows, and holds only a quarter of the
+//! quota as burst capacity, which keeps the margin for other clients sharing
+//! the quota in how far ahead a burst may run rather than in lost throughput.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::api::retry::RetryConfig;
+
+/// A request quota over a time window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    /// Requests allowed per window
+    pub limit: u32,
+    /// Window length
+    pub per: Duration,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(quota: Quota, now: Instant) -> Self {
+        let limit = f64::from(quota.limit.max(1));
+        let capacity = (limit / 4.0).max(1.0);
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: limit / quota.per.as_secs_f64(),
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// Time until one token is available
+    fn wait_time(&self) -> Duration {
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / self.refill_per_sec)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    buckets: Vec<Bucket>,
+    blocked_until: Option<Instant>,
+}
+
+/// Token-bucket limiter shared by every request made through a client
+#[derive(Debug)]
+pub struct RateLimiter {
+    state: Mutex<State>,
+}
+
+impl RateLimiter {
+    /// Create a limiter enforcing all of the given quotas
+    pub fn new(quotas: &[Quota]) -> Self {
+        let now = Instant::now();
+        Self {
+            state: Mutex::new(State {
+                buckets: quotas.iter().map(|q| Bucket::new(*q, now)).collect(),
+                blocked_until: None,
+            }),
+        }
+    }
+
+    /// Create a limiter from the configured quotas, or `None` when all are disabled
+    pub fn from_config(config: &RetryConfig) -> Option<Self> {
+        let quotas: Vec<Quota> = [
+            (config.requests_per_minute, Duration::from_secs(60)),
+            (config.requests_per_hour, Duration::from_secs(3600)),
+        ]
+        .into_iter()
+        .filter_map(|(limit, per)| limit.filter(|l| *l > 0).map(|limit| Quota { limit, per }))
+        .collect();
+
+        if quotas.is_empty() {
+            None
+        } else {
+            Some(Self::new(&quotas))
+        }
+    }
+
+    /// Wait until a request may be sent, then consume one token from every bucket
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().expect("rate limiter lock poisoned");
+                let now = Instant::now();
+
+                match state.blocked_until {
+                    Some(until) if until > now => until - now,
+                    _ => {
+                        state.blocked_until = None;
+                        for bucket in &mut state.buckets {
+                            bucket.refill(now);
+                        }
+                        let wait = state
+                            .buckets
+                            .iter()
+                            .map(Bucket::wait_time)
+                            .max()
+                            .unwrap_or(Duration::ZERO);
+                        if wait.is_zero() {
+                            for bucket in &mut state.buckets {
+                                bucket.tokens -= 1.0;
+                            }
+                            return;
+                        }
+                        wait
+                    }
+                }
+            };
+
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Hold back every request for `duration`, e.g. after the API answered 429
+    pub fn pause(&self, duration: Duration) {
+        let mut state = self.state.lock().expect("rate limiter lock poisoned");
+        let until = Instant::now() + duration;
+        if state.blocked_until.is_none_or(|current| current < until) {
+            state.blocked_until = Some(until);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn burst_is_a_quarter_of_the_quota() {
+        let limiter = RateLimiter::new(&[Quota {
+            limit: 8,
+            per: Duration::from_secs(60),
+        }]);
+        let start = Instant::now();
+        for _ in 0..2 {
+            limiter.acquire().await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+
+    #[test]
+    fn refills_at_the_full_quota_rate() {
+        let bucket = Bucket::new(
+            Quota {
+                limit: 120,
+                per: Duration::from_secs(60),
+            },
+            Instant::now(),
+        );
+        assert_eq!(bucket.capacity, 30.0);
+        assert_eq!(bucket.refill_per_sec, 2.0);
+    }
+
+    #[tokio::test]
+    async fn concurrent_requests_are_paced() {
+        // Burst of 1, then 4 tokens per 400ms (one every 100ms)
+        let limiter = Arc::new(RateLimiter::new(&[Quota {
+            limit: 4,
+            per: Duration::from_millis(400),
+        }]));
+
+        let start = Instant::now();
+        let tasks: Vec<_> = (0..4)
+            .map(|_| {
+                let limiter = Arc::clone(&limiter);
+                tokio::spawn(async move { limiter.acquire().await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.expect("task should finish");
+        }
+
+        assert!(start.elapsed() >= Duration::from_millis(290));
+    }
+
+    #[tokio::test]
+    async fn pause_blocks_until_elapsed() {
+        let limiter = RateLimiter::new(&[Quota {
+            limit: 100,
+            per: Duration::from_secs(60),
+        }]);
+        limiter.pause(Duration::from_millis(100));
+        let start = Instant::now();
+        limiter.acquire().await;
+        assert!(start.elapsed() >= Duration::from_millis(90));
+    }
+
+    #[test]
+    fn from_config_skips_disabled_quotas() {
+        let config = RetryConfig {
+            requests_per_minute: None,
+            requests_per_hour: Some(0),
+            ..RetryConfig::default()
+        };
+        assert!(RateLimiter::from_config(&config).is_none());
+        assert!(RateLimiter::from_config(&RetryConfig::default()).is_some());
+    }
+}
@@ -0,0 +1,376 @@
+//! Expand user-supplied references to the resource URLs the API expects.
+//!
+//! Anywhere a command takes a contact, project, bank account, user, category
+//! or task it accepts a full URL (used as-is), a bare ID (expanded against
+//! the current environment) or a name, which is looked up and must match
+//! exactly one record. All-digit input that is not an existing ID is looked
+//! up as a name, since names like "2024" are common for projects.
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::api::pagination::Pagination;
+use crate::api::{ApiError, FreeAgentClient};
+
+/// Resources that can be referenced by ID, URL or name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    Contact,
+    Project,
+    BankAccount,
+    User,
+    Category,
+    Task,
+}
+
+impl ResourceKind {
+    /// API path of the collection
+    pub fn path(self) -> &'static str {
+        match self {
+            Self::Contact => "contacts",
+            Self::Project => "projects",
+            Self::BankAccount => "bank_accounts",
+            Self::User => "users",
+            Self::Category => "categories",
+            Self::Task => "tasks",
+        }
+    }
+
+    /// Plural noun used in error messages
+    fn noun(self) -> &'static str {
+        match self {
+            Self::Contact => "contacts",
+            Self::Project => "projects",
+            Self::BankAccount => "bank accounts",
+            Self::User => "users",
+            Self::Category => "categories",
+            Self::Task => "tasks",
+        }
+    }
+
+    /// Query parameters for the name lookup, widened to include inactive records
+    fn lookup_params(self) -> HashMap<String, String> {
+        match self {
+            Self::Contact | Self::Project | Self::Task => {
+                HashMap::from([("view".to_string(), "all".to_string())])
+            }
+            _ => HashMap::new(),
+        }
+    }
+
+    /// Names a record can be matched by
+    fn labels(self, record: &Value) -> Vec<String> {
+        let field = |key: &str| record.get(key).and_then(Value::as_str).map(str::to_string);
+        let full_name = || {
+            let name = [field("first_name"), field("last_name")]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" ");
+            (!name.is_empty()).then_some(name)
+        };
+
+        let labels = match self {
+            Self::Contact => vec![field("organisation_name"), full_name(), field("email")],
+            Self::User => vec![full_name(), field("email")],
+            Self::Category => vec![field("description"), field("nominal_code")],
+            Self::Project | Self::Task | Self::BankAccount => vec![field("name")],
+        };
+        labels.into_iter().flatten().filter(|l| !l.is_empty()).collect()
+    }
+}
+
+/// How well a lookup matched a record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Match {
+    /// Every word of the query appears in a label
+    Partial,
+    /// A label equals the query, ignoring case
+    Exact,
+}
+
+fn match_record(query: &str, labels: &[String]) -> Option<Match> {
+    let query = query.trim().to_lowercase();
+    let words: Vec<&str> = query.split_whitespace().collect();
+    labels
+        .iter()
+        .filter_map(|label| {
+            let label = label.to_lowercase();
+            if label == query {
+                Some(Match::Exact)
+            } else if words.iter().all(|word| label.contains(word)) {
+                Some(Match::Partial)
+            } else {
+                None
+            }
+        })
+        .max()
+}
+
+/// Records in a list response, wherever they are nested
+///
+/// Most endpoints return a single array; `categories` splits its records
+/// across one array per category group.
+fn records(response: &Value) -> Vec<&Value> {
+    response
+        .as_object()
+        .map(|object| {
+            object
+                .values()
+                .filter_map(Value::as_array)
+                .flatten()
+                .filter(|record| record.is_object())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Pick the single best match for `query`, failing on none or several
+fn select(kind: ResourceKind, query: &str, response: &Value) -> Result<String> {
+    let matches: Vec<(Match, &Value)> = records(response)
+        .into_iter()
+        .filter_map(|record| match_record(query, &kind.labels(record)).map(|m| (m, record)))
+        .collect();
+    let best = matches.iter().map(|(m, _)| *m).max();
+    let candidates: Vec<&Value> = matches
+        .iter()
+        .filter(|(m, _)| Some(*m) == best)
+        .map(|(_, record)| *record)
+        .collect();
+
+    match candidates.as_slice() {
+        [] => bail!("No {} match '{}'", kind.noun(), query),
+        [record] => record
+            .get("url")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("Matched {} record has no url", kind.noun())),
+        _ => {
+            let listing: Vec<String> = candidates
+                .iter()
+                .map(|record| {
+                    let label = kind.labels(record).into_iter().next().unwrap_or_default();
+                    let url = record.get("url").and_then(Value::as_str).unwrap_or("?");
+                    format!("  {} ({})", label, url)
+                })
+                .collect();
+            bail!(
+                "'{}' matches {} {}; use an ID or URL to pick one:\n{}",
+                query,
+                candidates.len(),
+                kind.noun(),
+                listing.join("\n")
+            )
+        }
+    }
+}
+
+fn is_url(input: &str) -> bool {
+    input.starts_with("http://") || input.starts_with("https://")
+}
+
+fn is_not_found(err: &anyhow::Error) -> bool {
+    matches!(err.downcast_ref::<ApiError>(), Some(ApiError::NotFound { .. }))
+}
+
+impl FreeAgentClient {
+    /// Expand an ID, URL or name to the canonical URL of a resource
+    pub async fn resolve(&self, kind: ResourceKind, input: &str) -> Result<String> {
+        self.resolve_with(kind, input, kind.lookup_params()).await
+    }
+
+    /// Expand a task reference, looking names up only among the tasks of
+    /// `project` (a resolved project URL) when one is given
+    pub async fn resolve_task(&self, input: &str, project: Option<&str>) -> Result<String> {
+        let mut params = ResourceKind::Task.lookup_params();
+        if let Some(project) = project {
+            params.insert("project".to_string(), project.to_string());
+        }
+        self.resolve_with(ResourceKind::Task, input, params).await
+    }
+
+    async fn resolve_with(
+        &self,
+        kind: ResourceKind,
+        input: &str,
+        params: HashMap<String, String>,
+    ) -> Result<String> {
+        let input = input.trim();
+        if input.is_empty() {
+            bail!("Expected an ID, URL or name for {}", kind.noun());
+        }
+        if is_url(input) {
+            return Ok(input.to_string());
+        }
+        // Category IDs are their nominal codes
+        if input.chars().all(|c| c.is_ascii_digit()) {
+            let path = format!("{}/{}", kind.path(), input);
+            match self.get(&path, None).await {
+                Ok(_) => return self.build_url(&path),
+                Err(err) if is_not_found(&err) => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        let response = match kind {
+            // Categories are not paginated and are served from the cache
+            ResourceKind::Category => self.get(kind.path(), None).await?,
+            _ => {
+                let pagination = Pagination { all: true, limit: None };
+                let params = (!params.is_empty()).then_some(params);
+                self.list_with(pagination, kind.path(), params).await?
+            }
+        };
+        select(kind, input, &response)
+    }
+
+    /// [`resolve`](Self::resolve) an optional argument
+    pub async fn resolve_opt(&self, kind: ResourceKind, input: Option<&str>) -> Result<Option<String>> {
+        match input {
+            Some(input) => self.resolve(kind, input).await.map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::StoredTokens;
+    use httpmock::Method::GET;
+    use httpmock::MockServer;
+    use serde_json::json;
+
+    fn client(server: &MockServer) -> Result<FreeAgentClient> {
+        let tokens = StoredTokens::new("test-access".to_string(), "test-refresh".to_string(), 3600, false);
+        FreeAgentClient::new_for_test(server.url("/v2"), tokens)
+    }
+
+    fn contacts() -> Value {
+        json!({"contacts": [
+            {"url": "https://api/v2/contacts/1", "organisation_name": "Acme Ltd"},
+            {"url": "https://api/v2/contacts/2", "organisation_name": "Acme Holdings"},
+            {"url": "https://api/v2/contacts/3", "first_name": "Jane", "last_name": "Smith"},
+        ]})
+    }
+
+    #[test]
+    fn prefers_exact_matches_over_partial_ones() -> Result<()> {
+        let response = contacts();
+        assert_eq!(select(ResourceKind::Contact, "acme ltd", &response)?, "https://api/v2/contacts/1");
+        assert_eq!(select(ResourceKind::Contact, "smith jane", &response)?, "https://api/v2/contacts/3");
+        Ok(())
+    }
+
+    #[test]
+    fn fails_loudly_on_ambiguous_or_missing_names() {
+        let response = contacts();
+        let err = select(ResourceKind::Contact, "acme", &response).unwrap_err().to_string();
+        assert!(err.contains("matches 2 contacts"));
+        assert!(err.contains("Acme Holdings (https://api/v2/contacts/2)"));
+
+        let err = select(ResourceKind::Contact, "globex", &response).unwrap_err().to_string();
+        assert_eq!(err, "No contacts match 'globex'");
+    }
+
+    #[test]
+    fn matches_categories_across_groups() -> Result<()> {
+        let response = json!({
+            "admin_expenses_categories": [{"url": "https://api/v2/categories/285", "description": "Accommodation and Meals", "nominal_code": "285"}],
+            "income_categories": [{"url": "https://api/v2/categories/001", "description": "Sales", "nominal_code": "001"}],
+        });
+        assert_eq!(select(ResourceKind::Category, "sales", &response)?, "https://api/v2/categories/001");
+        assert_eq!(select(ResourceKind::Category, "meals", &response)?, "https://api/v2/categories/285");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn passes_urls_through_and_expands_ids() -> Result<()> {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/bank_accounts/42");
+                then.status(200).json_body(json!({"bank_account": {"url": "https://api/v2/bank_accounts/42"}}));
+            })
+            .await;
+        let client = client(&server)?;
+        assert_eq!(
+            client.resolve(ResourceKind::Contact, "https://api.freeagent.com/v2/contacts/9").await?,
+            "https://api.freeagent.com/v2/contacts/9"
+        );
+        assert_eq!(
+            client.resolve(ResourceKind::BankAccount, "42").await?,
+            server.url("/v2/bank_accounts/42")
+        );
+        assert_eq!(client.resolve_opt(ResourceKind::User, None).await?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn looks_up_names_including_inactive_records() -> Result<()> {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/contacts").query_param("view", "all");
+                then.status(200).json_body(contacts());
+            })
+            .await;
+
+        let client = client(&server)?;
+        let url = client.resolve(ResourceKind::Contact, "Jane").await?;
+        assert_eq!(url, "https://api/v2/contacts/3");
+        mock.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn looks_up_digit_names_when_no_record_has_that_id() -> Result<()> {
+        let server = MockServer::start_async().await;
+        let by_id = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/projects/2024");
+                then.status(404).json_body(json!({"errors": {"error": {"message": "Resource not found"}}}));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/projects").query_param("view", "all");
+                then.status(200).json_body(json!({"projects": [
+                    {"url": "https://api/v2/projects/5", "name": "2024"},
+                    {"url": "https://api/v2/projects/6", "name": "2025"},
+                ]}));
+            })
+            .await;
+
+        let client = client(&server)?;
+        let url = client.resolve(ResourceKind::Project, "2024").await?;
+        assert_eq!(url, "https://api/v2/projects/5");
+        by_id.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn scopes_task_names_to_the_project() -> Result<()> {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/v2/tasks")
+                    .query_param("view", "all")
+                    .query_param("project", "https://api/v2/projects/5");
+                then.status(200).json_body(json!({"tasks": [
+                    {"url": "https://api/v2/tasks/8", "name": "Design"},
+                ]}));
+            })
+            .await;
+
+        let client = client(&server)?;
+        let url = client
+            .resolve_task("design", Some("https://api/v2/projects/5"))
+            .await?;
+        assert_eq!(url, "https://api/v2/tasks/8");
+        mock.assert_async().await;
+        Ok(())
+    }
+}
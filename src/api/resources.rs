@@ -0,0 +1,265 @@
+//! Typed client methods for the core resources in [`crate::models`].
+
+use anyhow::{anyhow, Context, Result};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::api::FreeAgentClient;
+use crate::models::{
+    BankTransaction, BankTransactionExplanation, BankTransactionExplanationFilter,
+    BankTransactionFilter, Bill, BillFilter, Category, Contact, ContactFilter, Invoice,
+    InvoiceFilter, ListFilter, Project, ProjectFilter, Resource, Timeslip, TimeslipFilter, User,
+    UserFilter,
+};
+
+/// Deserialize the value stored under `key` in an API response
+fn extract<T: DeserializeOwned>(mut response: Value, key: &str) -> Result<T> {
+    let value = response
+        .get_mut(key)
+        .map(Value::take)
+        .ok_or_else(|| anyhow!("Response is missing the `{}` field", key))?;
+    serde_json::from_value(value).with_context(|| format!("Failed to decode `{}`", key))
+}
+
+impl FreeAgentClient {
+    /// List records of a resource
+    pub async fn list_resources<R: Resource>(&self, filter: &impl ListFilter) -> Result<Vec<R>> {
+        let response = self.list(R::PATH, filter.to_params()).await?;
+        extract(response, R::PLURAL)
+    }
+
+    /// Get a single record by ID
+    pub async fn get_resource<R: Resource>(&self, id: &str) -> Result<R> {
+        let response = self.get(&format!("{}/{}", R::PATH, id), None).await?;
+        extract(response, R::SINGULAR)
+    }
+
+    /// Create a record, returning it as stored by the API
+    pub async fn create_resource<R: Resource>(&self, record: &R) -> Result<R> {
+        let body = json!({ R::SINGULAR: record });
+        let response = self.post(R::PATH, Some(body)).await?;
+        extract(response, R::SINGULAR)
+    }
+
+    /// Update a record by ID, returning it as stored by the API
+    pub async fn update_resource<R: Resource>(&self, id: &str, record: &R) -> Result<R> {
+        let body = json!({ R::SINGULAR: record });
+        let response = self.put(&format!("{}/{}", R::PATH, id), Some(body)).await?;
+        extract(response, R::SINGULAR)
+    }
+
+    /// Delete a record by ID
+    pub async fn delete_resource<R: Resource>(&self, id: &str) -> Result<()> {
+        self.delete(&format!("{}/{}", R::PATH, id)).await?;
+        Ok(())
+    }
+
+    /// List invoices
+    pub async fn list_invoices(&self, filter: &InvoiceFilter) -> Result<Vec<Invoice>> {
+        self.list_resources(filter).await
+    }
+
+    /// Get an invoice by ID
+    pub async fn get_invoice(&self, id: &str) -> Result<Invoice> {
+        self.get_resource(id).await
+    }
+
+    /// List bills
+    pub async fn list_bills(&self, filter: &BillFilter) -> Result<Vec<Bill>> {
+        self.list_resources(filter).await
+    }
+
+    /// Get a bill by ID
+    pub async fn get_bill(&self, id: &str) -> Result<Bill> {
+        self.get_resource(id).await
+    }
+
+    /// List contacts
+    pub async fn list_contacts(&self, filter: &ContactFilter) -> Result<Vec<Contact>> {
+        self.list_resources(filter).await
+    }
+
+    /// Get a contact by ID
+    pub async fn get_contact(&self, id: &str) -> Result<Contact> {
+        self.get_resource(id).await
+    }
+
+    /// List projects
+    pub async fn list_projects(&self, filter: &ProjectFilter) -> Result<Vec<Project>> {
+        self.list_resources(filter).await
+    }
+
+    /// Get a project by ID
+    pub async fn get_project(&self, id: &str) -> Result<Project> {
+        self.get_resource(id).await
+    }
+
+    /// List timeslips
+    pub async fn list_timeslips(&self, filter: &TimeslipFilter) -> Result<Vec<Timeslip>> {
+        self.list_resources(filter).await
+    }
+
+    /// Get a timeslip by ID
+    pub async fn get_timeslip(&self, id: &str) -> Result<Timeslip> {
+        self.get_resource(id).await
+    }
+
+    /// List bank transactions
+    pub async fn list_bank_transactions(
+        &self,
+        filter: &BankTransactionFilter,
+    ) -> Result<Vec<BankTransaction>> {
+        self.list_resources(filter).await
+    }
+
+    /// Get a bank transaction by ID
+    pub async fn get_bank_transaction(&self, id: &str) -> Result<BankTransaction> {
+        self.get_resource(id).await
+    }
+
+    /// List bank transaction explanations
+    pub async fn list_bank_transaction_explanations(
+        &self,
+        filter: &BankTransactionExplanationFilter,
+    ) -> Result<Vec<BankTransactionExplanation>> {
+        self.list_resources(filter).await
+    }
+
+    /// Get a bank transaction explanation by ID
+    pub async fn get_bank_transaction_explanation(
+        &self,
+        id: &str,
+    ) -> Result<BankTransactionExplanation> {
+        self.get_resource(id).await
+    }
+
+    /// List categories across all category groups
+    pub async fn list_categories(&self) -> Result<Vec<Category>> {
+        let response = self.get(Category::PATH, None).await?;
+        let mut categories = Vec::new();
+        if let Value::Object(groups) = response {
+            for (group, records) in groups {
+                if records.is_array() {
+                    let mut decoded: Vec<Category> = serde_json::from_value(records)
+                        .with_context(|| format!("Failed to decode `{}`", group))?;
+                    categories.append(&mut decoded);
+                }
+            }
+        }
+        Ok(categories)
+    }
+
+    /// Get a category by nominal code
+    pub async fn get_category(&self, nominal_code: &str) -> Result<Category> {
+        self.get_resource(nominal_code).await
+    }
+
+    /// List users
+    pub async fn list_users(&self, filter: &UserFilter) -> Result<Vec<User>> {
+        self.list_resources(filter).await
+    }
+
+    /// Get a user by ID
+    pub async fn get_user(&self, id: &str) -> Result<User> {
+        self.get_resource(id).await
+    }
+
+    /// Get the authenticated user
+    pub async fn get_current_user(&self) -> Result<User> {
+        self.get_resource("me").await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::StoredTokens;
+    use httpmock::Method::{GET, POST};
+    use httpmock::MockServer;
+
+    fn test_tokens() -> StoredTokens {
+        StoredTokens::new(
+            "test-access".to_string(),
+            "test-refresh".to_string(),
+            3600,
+            false,
+        )
+    }
+
+    #[tokio::test]
+    async fn list_invoices_decodes_typed_records() -> Result<()> {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/invoices").query_param("view", "overdue");
+                then.status(200).json_body(json!({
+                    "invoices": [{
+                        "url": "https://api.freeagent.com/v2/invoices/1",
+                        "reference": "INV-001",
+                        "due_on": "2024-01-31",
+                        "total_value": "1200.00"
+                    }]
+                }));
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?;
+        let filter = InvoiceFilter {
+            view: Some("overdue".to_string()),
+            ..Default::default()
+        };
+        let invoices = client.list_invoices(&filter).await?;
+        assert_eq!(invoices.len(), 1);
+        assert_eq!(invoices[0].reference.as_deref(), Some("INV-001"));
+        assert_eq!(invoices[0].total_value.as_deref(), Some("1200.00"));
+        mock.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn list_categories_flattens_groups() -> Result<()> {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v2/categories");
+                then.status(200).json_body(json!({
+                    "admin_expenses_categories": [{"nominal_code": "285"}],
+                    "income_categories": [{"nominal_code": "001"}, {"nominal_code": "002"}]
+                }));
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?;
+        let categories = client.list_categories().await?;
+        assert_eq!(categories.len(), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn create_resource_wraps_body_in_singular_key() -> Result<()> {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/v2/contacts")
+                    .json_body(json!({"contact": {"organisation_name": "Acme"}}));
+                then.status(201).json_body(json!({
+                    "contact": {
+                        "url": "https://api.freeagent.com/v2/contacts/9",
+                        "organisation_name": "Acme"
+                    }
+                }));
+            })
+            .await;
+
+        let client = FreeAgentClient::new_for_test(server.url("/v2"), test_tokens())?;
+        let contact = Contact {
+            organisation_name: Some("Acme".to_string()),
+            ..Default::default()
+        };
+        let created = client.create_resource(&contact).await?;
+        assert_eq!(created.id(), Some("9"));
+        mock.assert_async().await;
+        Ok(())
+    }
+}
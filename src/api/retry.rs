@@ -1,9 +1,19 @@
 //! Retry logic and rate limit handling for API requests.
 
 use anyhow::Result;
-use reqwest::Response;
+use rand::Rng;
+use reqwest::{Method, Response, StatusCode};
 use std::time::Duration;
 
+/// Statuses retried by default: transient gateway and availability failures
+pub const DEFAULT_RETRY_STATUSES: [u16; 3] = [502, 503, 504];
+
+/// FreeAgent's documented per-minute request quota
+pub const DEFAULT_REQUESTS_PER_MINUTE: u32 = 120;
+
+/// FreeAgent's documented per-hour request quota
+pub const DEFAULT_REQUESTS_PER_HOUR: u32 = 3600;
+
 /// Configuration for retry behavior
 #[derive(Debug, Clone)]
 pub struct RetryConfig {
@@ -15,6 +25,18 @@ pub struct RetryConfig {
     pub max_backoff_secs: u64,
     /// Whether to use exponential backoff
     pub exponential_backoff: bool,
+    /// Randomise each backoff between half and the full duration
+    pub jitter: bool,
+    /// Response statuses (other than 429) that are retried
+    pub retry_statuses: Vec<u16>,
+    /// Whether connection failures and timeouts are retried
+    pub retry_network_errors: bool,
+    /// Whether POST requests are retried after they may have reached the server
+    pub retry_post: bool,
+    /// Client-side request quota per minute (`None` disables it)
+    pub requests_per_minute: Option<u32>,
+    /// Client-side request quota per hour (`None` disables it)
+    pub requests_per_hour: Option<u32>,
 }
 
 impl Default for RetryConfig {
@@ -24,10 +46,52 @@ impl Default for RetryConfig {
             initial_backoff_secs: 1,
             max_backoff_secs: 60,
             exponential_backoff: true,
+            jitter: true,
+            retry_statuses: DEFAULT_RETRY_STATUSES.to_vec(),
+            retry_network_errors: true,
+            retry_post: false,
+            requests_per_minute: Some(DEFAULT_REQUESTS_PER_MINUTE),
+            requests_per_hour: Some(DEFAULT_REQUESTS_PER_HOUR),
         }
     }
 }
 
+fn env_flag(name: &str, default: bool) -> bool {
+    std::env::var(name)
+        .ok()
+        .map(|v| matches!(v.as_str(), "1" | "true" | "TRUE" | "yes" | "YES"))
+        .unwrap_or(default)
+}
+
+/// Read a request quota; `0` or `off` disables it
+fn env_quota(name: &str, default: u32) -> Option<u32> {
+    match std::env::var(name) {
+        Ok(v) if v.eq_ignore_ascii_case("off") => None,
+        Ok(v) => match v.trim().parse::<u32>() {
+            Ok(0) => None,
+            Ok(limit) => Some(limit),
+            Err(_) => Some(default),
+        },
+        Err(_) => Some(default),
+    }
+}
+
+/// Parse a comma-separated status list; `5xx` expands to the whole class
+fn parse_statuses(value: &str) -> Vec<u16> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .flat_map(|entry| match entry.to_ascii_lowercase().as_str() {
+            class if class.len() == 3 && class.ends_with("xx") => class[..1]
+                .parse::<u16>()
+                .map(|c| (c * 100..c * 100 + 100).collect())
+                .unwrap_or_default(),
+            code => code.parse::<u16>().map(|c| vec![c]).unwrap_or_default(),
+        })
+        .collect()
+}
+
 impl RetryConfig {
     /// Create a new retry configuration from environment variables
     pub fn from_env() -> Self {
@@ -46,28 +110,64 @@ impl RetryConfig {
             .and_then(|v| v.parse().ok())
             .unwrap_or(60);
 
-        let exponential_backoff = std::env::var("FREEAGENT_EXPONENTIAL_BACKOFF")
-            .ok()
-            .map(|v| matches!(v.as_str(), "1" | "true" | "TRUE" | "yes" | "YES"))
-            .unwrap_or(true);
+        let exponential_backoff = env_flag("FREEAGENT_EXPONENTIAL_BACKOFF", true);
+
+        let retry_statuses = std::env::var("FREEAGENT_RETRY_STATUSES")
+            .map(|v| parse_statuses(&v))
+            .unwrap_or_else(|_| DEFAULT_RETRY_STATUSES.to_vec());
 
         Self {
             max_retries,
             initial_backoff_secs,
             max_backoff_secs,
             exponential_backoff,
+            jitter: env_flag("FREEAGENT_RETRY_JITTER", true),
+            retry_statuses,
+            retry_network_errors: env_flag("FREEAGENT_RETRY_NETWORK_ERRORS", true),
+            retry_post: env_flag("FREEAGENT_RETRY_POST", false),
+            requests_per_minute: env_quota("FREEAGENT_REQUESTS_PER_MINUTE", DEFAULT_REQUESTS_PER_MINUTE),
+            requests_per_hour: env_quota("FREEAGENT_REQUESTS_PER_HOUR", DEFAULT_REQUESTS_PER_HOUR),
         }
     }
 
     /// Calculate the backoff duration for a given attempt
     pub fn backoff_duration(&self, attempt: u32) -> Duration {
-        if !self.exponential_backoff {
-            return Duration::from_secs(self.initial_backoff_secs);
+        let backoff_secs = if self.exponential_backoff {
+            self.initial_backoff_secs
+                .saturating_mul(2u64.saturating_pow(attempt))
+                .min(self.max_backoff_secs)
+        } else {
+            self.initial_backoff_secs
+        };
+        let backoff = Duration::from_secs(backoff_secs);
+
+        if !self.jitter || backoff.is_zero() {
+            return backoff;
         }
 
-        let backoff_secs = self.initial_backoff_secs * 2u64.pow(attempt);
-        let backoff_secs = backoff_secs.min(self.max_backoff_secs);
-        Duration::from_secs(backoff_secs)
+        let half = backoff / 2;
+        half + rand::thread_rng().gen_range(Duration::ZERO..=half)
+    }
+
+    /// Whether a request with this method may be sent again after reaching the server
+    pub fn allows_retry(&self, method: &Method) -> bool {
+        (*method != Method::POST && *method != Method::PATCH) || self.retry_post
+    }
+
+    /// Whether a response status should be retried for this method
+    pub fn should_retry_status(&self, method: &Method, status: StatusCode) -> bool {
+        self.retry_statuses.contains(&status.as_u16()) && self.allows_retry(method)
+    }
+
+    /// Whether a transport error should be retried for this method.
+    ///
+    /// Connection failures are always safe to retry because the request never
+    /// reached the server; other failures follow the method rules.
+    pub fn should_retry_error(&self, method: &Method, error: &reqwest::Error) -> bool {
+        if !self.retry_network_errors {
+            return false;
+        }
+        error.is_connect() || ((error.is_timeout() || error.is_request() || error.is_body()) && self.allows_retry(method))
     }
 }
 
@@ -117,11 +217,20 @@ mod tests {
         assert_eq!(config.initial_backoff_secs, 1);
         assert_eq!(config.max_backoff_secs, 60);
         assert!(config.exponential_backoff);
+        assert!(config.jitter);
+        assert_eq!(config.retry_statuses, vec![502, 503, 504]);
+        assert!(config.retry_network_errors);
+        assert!(!config.retry_post);
+        assert_eq!(config.requests_per_minute, Some(120));
+        assert_eq!(config.requests_per_hour, Some(3600));
     }
 
     #[test]
     fn retry_config_exponential_backoff() {
-        let config = RetryConfig::default();
+        let config = RetryConfig {
+            jitter: false,
+            ..RetryConfig::default()
+        };
         
         // First attempt: 1 * 2^0 = 1 second
         assert_eq!(config.backoff_duration(0).as_secs(), 1);
@@ -143,6 +252,8 @@ mod tests {
             initial_backoff_secs: 1,
             max_backoff_secs: 10,
             exponential_backoff: true,
+            jitter: false,
+            ..RetryConfig::default()
         };
         
         // Should cap at max_backoff_secs
@@ -156,6 +267,8 @@ mod tests {
             initial_backoff_secs: 5,
             max_backoff_secs: 60,
             exponential_backoff: false,
+            jitter: false,
+            ..RetryConfig::default()
         };
         
         // All attempts should use initial_backoff_secs
@@ -164,23 +277,73 @@ mod tests {
         assert_eq!(config.backoff_duration(2).as_secs(), 5);
     }
 
+    #[test]
+    fn retry_config_jitter_stays_within_bounds() {
+        let config = RetryConfig {
+            initial_backoff_secs: 4,
+            ..RetryConfig::default()
+        };
+        for _ in 0..50 {
+            let backoff = config.backoff_duration(1);
+            assert!(backoff >= Duration::from_secs(4));
+            assert!(backoff <= Duration::from_secs(8));
+        }
+    }
+
+    #[test]
+    fn retry_config_only_retries_post_when_allowed() {
+        let config = RetryConfig::default();
+        assert!(config.should_retry_status(&Method::GET, StatusCode::BAD_GATEWAY));
+        assert!(config.should_retry_status(&Method::PUT, StatusCode::SERVICE_UNAVAILABLE));
+        assert!(config.should_retry_status(&Method::DELETE, StatusCode::GATEWAY_TIMEOUT));
+        assert!(!config.should_retry_status(&Method::POST, StatusCode::BAD_GATEWAY));
+        assert!(!config.should_retry_status(&Method::GET, StatusCode::INTERNAL_SERVER_ERROR));
+
+        let config = RetryConfig {
+            retry_post: true,
+            ..RetryConfig::default()
+        };
+        assert!(config.should_retry_status(&Method::POST, StatusCode::BAD_GATEWAY));
+    }
+
+    #[test]
+    fn parse_statuses_expands_classes() {
+        assert_eq!(parse_statuses("502, 504"), vec![502, 504]);
+        let class = parse_statuses("5xx");
+        assert_eq!(class.len(), 100);
+        assert!(class.contains(&500) && class.contains(&599));
+        assert!(parse_statuses("bogus").is_empty());
+    }
+
     #[test]
     fn retry_config_from_env() {
         std::env::set_var("FREEAGENT_MAX_RETRIES", "5");
         std::env::set_var("FREEAGENT_INITIAL_BACKOFF_SECS", "2");
         std::env::set_var("FREEAGENT_MAX_BACKOFF_SECS", "120");
         std::env::set_var("FREEAGENT_EXPONENTIAL_BACKOFF", "false");
+        std::env::set_var("FREEAGENT_RETRY_STATUSES", "500,503");
+        std::env::set_var("FREEAGENT_RETRY_POST", "true");
+        std::env::set_var("FREEAGENT_REQUESTS_PER_MINUTE", "60");
+        std::env::set_var("FREEAGENT_REQUESTS_PER_HOUR", "0");
 
         let config = RetryConfig::from_env();
         assert_eq!(config.max_retries, 5);
         assert_eq!(config.initial_backoff_secs, 2);
         assert_eq!(config.max_backoff_secs, 120);
         assert!(!config.exponential_backoff);
+        assert_eq!(config.retry_statuses, vec![500, 503]);
+        assert!(config.retry_post);
+        assert_eq!(config.requests_per_minute, Some(60));
+        assert_eq!(config.requests_per_hour, None);
 
         // Clean up
         std::env::remove_var("FREEAGENT_MAX_RETRIES");
         std::env::remove_var("FREEAGENT_INITIAL_BACKOFF_SECS");
         std::env::remove_var("FREEAGENT_MAX_BACKOFF_SECS");
         std::env::remove_var("FREEAGENT_EXPONENTIAL_BACKOFF");
+        std::env::remove_var("FREEAGENT_RETRY_STATUSES");
+        std::env::remove_var("FREEAGENT_RETRY_POST");
+        std::env::remove_var("FREEAGENT_REQUESTS_PER_MINUTE");
+        std::env::remove_var("FREEAGENT_REQUESTS_PER_HOUR");
     }
 }
@@ -0,0 +1,146 @@
+//! File attachments for expenses, bills, credit notes and explanations.
+//!
+//! FreeAgent does not take multipart uploads; files are embedded in the JSON
+//! body as an `attachment` object holding the base64-encoded content.
+
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Serialize;
+use std::path::Path;
+
+/// Largest file FreeAgent accepts as an attachment
+pub const MAX_ATTACHMENT_BYTES: usize = 5 * 1024 * 1024;
+
+/// An attachment in the shape FreeAgent expects
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Attachment {
+    /// Base64-encoded file content
+    pub data: String,
+    pub file_name: String,
+    pub content_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl Attachment {
+    /// Read and encode a local file
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Failed to read attachment {}", path.display()))?;
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .with_context(|| format!("Invalid attachment file name: {}", path.display()))?;
+        Self::from_bytes(file_name, &bytes)
+    }
+
+    /// Encode in-memory file content
+    pub fn from_bytes(file_name: &str, bytes: &[u8]) -> Result<Self> {
+        if bytes.len() > MAX_ATTACHMENT_BYTES {
+            bail!(
+                "Attachment {} is {} bytes; FreeAgent accepts at most {} bytes",
+                file_name,
+                bytes.len(),
+                MAX_ATTACHMENT_BYTES
+            );
+        }
+        let content_type = content_type(file_name, bytes).with_context(|| {
+            format!(
+                "Unsupported attachment type for {}; use a PDF, PNG, JPEG or GIF file",
+                file_name
+            )
+        })?;
+
+        Ok(Self {
+            data: BASE64.encode(bytes),
+            file_name: file_name.to_string(),
+            content_type: content_type.to_string(),
+            description: None,
+        })
+    }
+
+    /// Set the attachment description
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+/// Detect a supported content type from the file's magic bytes, falling back
+/// to its extension
+pub fn content_type(file_name: &str, bytes: &[u8]) -> Option<&'static str> {
+    let sniffed = if bytes.starts_with(b"%PDF") {
+        Some("application/x-pdf")
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else {
+        None
+    };
+
+    sniffed.or_else(|| {
+        let extension = Path::new(file_name).extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "pdf" => Some("application/x-pdf"),
+            "png" => Some("image/png"),
+            "jpg" | "jpeg" => Some("image/jpeg"),
+            "gif" => Some("image/gif"),
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn detects_content_type_from_bytes_then_extension() {
+        assert_eq!(content_type("scan", b"%PDF-1.7"), Some("application/x-pdf"));
+        assert_eq!(content_type("receipt.pdf", b"\x89PNG\r\n\x1a\n"), Some("image/png"));
+        assert_eq!(content_type("photo.JPG", b""), Some("image/jpeg"));
+        assert_eq!(content_type("notes.txt", b"hello"), None);
+    }
+
+    #[test]
+    fn encodes_attachment_json() -> Result<()> {
+        let attachment = Attachment::from_bytes("receipt.pdf", b"%PDF")?.with_description("Taxi");
+        assert_eq!(
+            serde_json::to_value(&attachment)?,
+            json!({
+                "data": "JVBERg==",
+                "file_name": "receipt.pdf",
+                "content_type": "application/x-pdf",
+                "description": "Taxi",
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_unsupported_and_oversized_files() {
+        let err = Attachment::from_bytes("notes.txt", b"hello").unwrap_err();
+        assert!(err.to_string().contains("Unsupported attachment type"));
+
+        let big = vec![0u8; MAX_ATTACHMENT_BYTES + 1];
+        let err = Attachment::from_bytes("big.pdf", &big).unwrap_err();
+        assert!(err.to_string().contains("at most"));
+    }
+
+    #[test]
+    fn reads_files_from_disk() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("receipt.png");
+        std::fs::write(&path, b"\x89PNG\r\n\x1a\nrest")?;
+        let attachment = Attachment::from_path(&path)?;
+        assert_eq!(attachment.file_name, "receipt.png");
+        assert_eq!(attachment.content_type, "image/png");
+        Ok(())
+    }
+}
@@ -0,0 +1,752 @@
+//! Pluggable storage backends for OAuth tokens.
+//!
+//! `FREEAGENT_TOKEN_STORE` picks the backend:
+//!
+//! - `keyring`: the OS secret store (macOS Keychain, Windows Credential
+//!   Manager, or the Secret Service on Linux)
+//! - `encrypted`: `tokens.enc`, encrypted with a passphrase (Argon2id and
+//!   XChaCha20-Poly1305), for headless machines without a secret store
+//! - `file`: plaintext `tokens.json` protected only by file permissions
+//! - `auto` (default): the keyring when one is reachable, otherwise the
+//!   encrypted file. On a headless machine with no passphrase to hand, an
+//!   existing plaintext `tokens.json` keeps being used, with a warning.
+//!
+//! The passphrase for the encrypted file is read from
+//! `FREEAGENT_TOKEN_PASSPHRASE`, or prompted for on a terminal.
+//!
+//! Setting `FREEAGENT_REFRESH_TOKEN` bypasses all of these with
+//! [`EnvTokens`], for CI jobs that cannot run `freeagent login`.
+
+use anyhow::{anyhow, bail, Context, Result};
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+use super::token::StoredTokens;
+
+/// Environment variable selecting the token backend
+pub const TOKEN_STORE_ENV: &str = "FREEAGENT_TOKEN_STORE";
+
+/// Environment variable holding the passphrase for the encrypted file
+pub const PASSPHRASE_ENV: &str = "FREEAGENT_TOKEN_PASSPHRASE";
+
+/// Keyring service name; each profile is stored as a separate account
+pub const KEYRING_SERVICE: &str = "freeagent-cli";
+
+/// Environment variable holding a refresh token for non-interactive use
+pub const REFRESH_TOKEN_ENV: &str = "FREEAGENT_REFRESH_TOKEN";
+
+/// Environment variable holding an access token to use before refreshing
+pub const ACCESS_TOKEN_ENV: &str = "FREEAGENT_ACCESS_TOKEN";
+
+/// Environment variable naming where rotated refresh tokens are written
+pub const TOKEN_SINK_ENV: &str = "FREEAGENT_TOKEN_SINK";
+
+/// Where tokens are kept
+pub trait TokenBackend: Send + Sync {
+    /// Short name shown by `status`
+    fn name(&self) -> &'static str;
+
+    /// Load stored tokens, if any
+    fn load(&self) -> Result<Option<StoredTokens>>;
+
+    /// Store tokens, replacing any existing ones
+    fn save(&self, tokens: &StoredTokens) -> Result<()>;
+
+    /// Delete stored tokens; succeeds if there were none
+    fn delete(&self) -> Result<()>;
+
+    /// Whether tokens are stored, without decrypting them
+    fn exists(&self) -> Result<bool>;
+}
+
+/// Backend selected by `FREEAGENT_TOKEN_STORE`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendKind {
+    Auto,
+    Keyring,
+    Encrypted,
+    File,
+}
+
+impl BackendKind {
+    /// Read the backend choice from the environment
+    pub fn from_env() -> Result<Self> {
+        match std::env::var(TOKEN_STORE_ENV) {
+            Ok(value) => Self::parse(&value),
+            Err(_) => Ok(Self::Auto),
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "auto" => Ok(Self::Auto),
+            "keyring" => Ok(Self::Keyring),
+            "encrypted" => Ok(Self::Encrypted),
+            "file" | "plaintext" => Ok(Self::File),
+            other => bail!(
+                "Unknown {} '{}': expected auto, keyring, encrypted or file",
+                TOKEN_STORE_ENV,
+                other
+            ),
+        }
+    }
+}
+
+/// Build the backend for a profile whose files live in `dir`
+pub fn for_profile(kind: BackendKind, profile: &str, dir: &Path) -> Box<dyn TokenBackend> {
+    let encrypted = || Box::new(EncryptedFile::new(dir.join("tokens.enc")));
+    match kind {
+        BackendKind::Keyring => Box::new(KeyringStore::new(profile)),
+        BackendKind::Encrypted => encrypted(),
+        BackendKind::File => Box::new(PlaintextFile::new(dir.join("tokens.json"))),
+        BackendKind::Auto => {
+            let keyring = KeyringStore::new(profile);
+            if keyring.is_available() {
+                Box::new(keyring)
+            } else {
+                auto_without_keyring(dir, passphrase_available())
+            }
+        }
+    }
+}
+
+/// The `auto` backend when no keyring is reachable
+///
+/// The encrypted file, unless there is no passphrase to open it and the
+/// tokens are still in a plaintext `tokens.json`: failing every command
+/// would be worse than carrying on with the file that already works.
+fn auto_without_keyring(dir: &Path, passphrase_available: bool) -> Box<dyn TokenBackend> {
+    let encrypted = dir.join("tokens.enc");
+    let plaintext = dir.join("tokens.json");
+    if !passphrase_available && !encrypted.exists() && plaintext.exists() {
+        eprintln!(
+            "Warning: tokens are still in plaintext {}; set {} to move them into an encrypted store",
+            plaintext.display(),
+            PASSPHRASE_ENV
+        );
+        return Box::new(PlaintextFile::new(plaintext));
+    }
+    Box::new(EncryptedFile::new(encrypted))
+}
+
+/// Create `dir` (owner-only on Unix) if it does not exist
+pub(crate) fn ensure_private_dir(dir: &Path) -> Result<()> {
+    if !dir.exists() {
+        fs::create_dir_all(dir).context("Failed to create config directory")?;
+
+        #[cfg(unix)]
+        {
+            let mut perms = fs::metadata(dir)?.permissions();
+            perms.set_mode(0o700);
+            fs::set_permissions(dir, perms)?;
+        }
+    }
+    Ok(())
+}
+
+/// Atomically write `contents` to `path`, readable only by the owner on Unix
+///
+/// The data goes to a temporary file in the same directory which is then
+/// renamed over `path`, so readers never see a truncated file.
+pub(crate) fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    ensure_private_dir(parent)?;
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid file path {}", path.display()))?;
+    let temp_path = parent.join(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let result = (|| -> std::io::Result<()> {
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options.open(&temp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result.with_context(|| format!("Failed to write {}", path.display()))
+}
+
+pub(crate) fn remove_if_exists(path: &Path) -> Result<()> {
+    if path.exists() {
+        fs::remove_file(path).with_context(|| format!("Failed to delete {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Plaintext JSON file, the original storage format
+pub struct PlaintextFile {
+    path: PathBuf,
+}
+
+impl PlaintextFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl TokenBackend for PlaintextFile {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn load(&self) -> Result<Option<StoredTokens>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let json = fs::read_to_string(&self.path).context("Failed to read token file")?;
+        let tokens = serde_json::from_str(&json).context("Failed to parse token file")?;
+        Ok(Some(tokens))
+    }
+
+    fn save(&self, tokens: &StoredTokens) -> Result<()> {
+        let json = serde_json::to_string_pretty(tokens).context("Failed to serialize tokens")?;
+        write_private(&self.path, json.as_bytes())
+    }
+
+    fn delete(&self) -> Result<()> {
+        remove_if_exists(&self.path)
+    }
+
+    fn exists(&self) -> Result<bool> {
+        Ok(self.path.exists())
+    }
+}
+
+/// On-disk layout of `tokens.enc`
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope {
+    version: u8,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+const ENVELOPE_VERSION: u8 = 1;
+
+/// Passphrase-encrypted file
+///
+/// The key is derived with Argon2id's default parameters and a random salt;
+/// a fresh salt and nonce are used for every save.
+pub struct EncryptedFile {
+    path: PathBuf,
+    passphrase: OnceLock<String>,
+}
+
+impl EncryptedFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            passphrase: OnceLock::new(),
+        }
+    }
+
+    /// Use a fixed passphrase instead of the environment or a prompt
+    pub fn with_passphrase(self, passphrase: impl Into<String>) -> Self {
+        let _ = self.passphrase.set(passphrase.into());
+        self
+    }
+
+    /// The passphrase, asked for at most once per process
+    fn passphrase(&self, creating: bool) -> Result<&str> {
+        if let Some(passphrase) = self.passphrase.get() {
+            return Ok(passphrase);
+        }
+        let passphrase = read_passphrase(creating)?;
+        Ok(self.passphrase.get_or_init(|| passphrase))
+    }
+}
+
+/// Passphrase typed at the prompt, reused by later stores in the same process
+static PROMPTED_PASSPHRASE: OnceLock<String> = OnceLock::new();
+
+/// Whether [`read_passphrase`] can get a passphrase without failing
+fn passphrase_available() -> bool {
+    std::env::var(PASSPHRASE_ENV).is_ok_and(|value| !value.is_empty())
+        || PROMPTED_PASSPHRASE.get().is_some()
+        || std::io::stdin().is_terminal()
+}
+
+fn read_passphrase(creating: bool) -> Result<String> {
+    if let Ok(value) = std::env::var(PASSPHRASE_ENV) {
+        if !value.is_empty() {
+            return Ok(value);
+        }
+    }
+    if let Some(passphrase) = PROMPTED_PASSPHRASE.get() {
+        return Ok(passphrase.clone());
+    }
+    if !std::io::stdin().is_terminal() {
+        bail!(
+            "Encrypted tokens need a passphrase. Set {} (or {}=file to store tokens unencrypted).",
+            PASSPHRASE_ENV,
+            TOKEN_STORE_ENV
+        );
+    }
+
+    let passphrase = rpassword::prompt_password("Token passphrase: ")
+        .context("Failed to read passphrase")?;
+    if passphrase.is_empty() {
+        bail!("Passphrase cannot be empty");
+    }
+    if creating {
+        let confirm = rpassword::prompt_password("Confirm passphrase: ")
+            .context("Failed to read passphrase")?;
+        if confirm != passphrase {
+            bail!("Passphrases do not match");
+        }
+    }
+    Ok(PROMPTED_PASSPHRASE.get_or_init(|| passphrase).clone())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| anyhow!("Failed to derive encryption key: {}", err))?;
+    Ok(key)
+}
+
+fn decode_field(value: &str, field: &str) -> Result<Vec<u8>> {
+    BASE64
+        .decode(value)
+        .with_context(|| format!("Invalid {} in encrypted tokens", field))
+}
+
+/// Encrypt tokens into the JSON envelope used by `tokens.enc`
+///
+/// A fresh salt and nonce are used every time.
+pub fn seal(tokens: &StoredTokens, passphrase: &str) -> Result<String> {
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let key = derive_key(passphrase, &salt)?;
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+    let plaintext = serde_json::to_vec(tokens).context("Failed to serialize tokens")?;
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce), plaintext.as_ref())
+        .map_err(|_| anyhow!("Failed to encrypt tokens"))?;
+
+    let envelope = Envelope {
+        version: ENVELOPE_VERSION,
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    };
+    serde_json::to_string_pretty(&envelope).context("Failed to serialize tokens")
+}
+
+/// Decrypt tokens from an envelope written by [`seal`]
+pub fn unseal(json: &str, passphrase: &str) -> Result<StoredTokens> {
+    let envelope: Envelope =
+        serde_json::from_str(json).context("Failed to parse encrypted tokens")?;
+    if envelope.version != ENVELOPE_VERSION {
+        bail!("Unsupported encrypted tokens version {}", envelope.version);
+    }
+
+    let salt = decode_field(&envelope.salt, "salt")?;
+    let nonce = decode_field(&envelope.nonce, "nonce")?;
+    let ciphertext = decode_field(&envelope.ciphertext, "ciphertext")?;
+    if nonce.len() != 24 {
+        bail!("Invalid nonce in encrypted tokens");
+    }
+
+    let key = derive_key(passphrase, &salt)?;
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+    let plaintext = cipher
+        .decrypt(XNonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| anyhow!("Failed to decrypt tokens; is the passphrase correct?"))?;
+    serde_json::from_slice(&plaintext).context("Failed to parse decrypted tokens")
+}
+
+/// Serialize tokens for `auth export`, sealed with the token passphrase if
+/// `encrypt` is set
+pub fn export_bundle(tokens: &StoredTokens, encrypt: bool) -> Result<String> {
+    if encrypt {
+        seal(tokens, &read_passphrase(true)?)
+    } else {
+        serde_json::to_string_pretty(tokens).context("Failed to serialize tokens")
+    }
+}
+
+/// Parse a bundle written by [`export_bundle`], decrypting it when sealed
+pub fn import_bundle(json: &str) -> Result<StoredTokens> {
+    let value: serde_json::Value =
+        serde_json::from_str(json).context("Token bundle is not valid JSON")?;
+    if value.get("ciphertext").is_some() {
+        unseal(json, &read_passphrase(false)?)
+    } else {
+        serde_json::from_value(value).context("Token bundle is missing token fields")
+    }
+}
+
+impl TokenBackend for EncryptedFile {
+    fn name(&self) -> &'static str {
+        "encrypted"
+    }
+
+    fn load(&self) -> Result<Option<StoredTokens>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let json = fs::read_to_string(&self.path).context("Failed to read encrypted token file")?;
+        unseal(&json, self.passphrase(false)?).map(Some)
+    }
+
+    fn save(&self, tokens: &StoredTokens) -> Result<()> {
+        let json = seal(tokens, self.passphrase(!self.path.exists())?)?;
+        write_private(&self.path, json.as_bytes())
+    }
+
+    fn delete(&self) -> Result<()> {
+        remove_if_exists(&self.path)
+    }
+
+    fn exists(&self) -> Result<bool> {
+        Ok(self.path.exists())
+    }
+}
+
+/// The OS secret store, one entry per profile
+pub struct KeyringStore {
+    profile: String,
+}
+
+impl KeyringStore {
+    pub fn new(profile: &str) -> Self {
+        Self {
+            profile: profile.to_string(),
+        }
+    }
+
+    fn entry(&self) -> Result<keyring::Entry> {
+        keyring::Entry::new(KEYRING_SERVICE, &self.profile).context("Failed to open keyring entry")
+    }
+
+    /// Whether a secret store is reachable on this machine
+    pub fn is_available(&self) -> bool {
+        matches!(
+            self.entry().map(|entry| entry.get_password()),
+            Ok(Ok(_)) | Ok(Err(keyring::Error::NoEntry))
+        )
+    }
+
+    fn get(&self) -> Result<Option<String>> {
+        match self.entry()?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(err) => Err(anyhow!(err).context("Failed to read tokens from the keyring")),
+        }
+    }
+}
+
+impl TokenBackend for KeyringStore {
+    fn name(&self) -> &'static str {
+        "keyring"
+    }
+
+    fn load(&self) -> Result<Option<StoredTokens>> {
+        match self.get()? {
+            Some(secret) => {
+                let tokens = serde_json::from_str(&secret)
+                    .context("Failed to parse tokens stored in the keyring")?;
+                Ok(Some(tokens))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn save(&self, tokens: &StoredTokens) -> Result<()> {
+        let secret = serde_json::to_string(tokens).context("Failed to serialize tokens")?;
+        self.entry()?
+            .set_password(&secret)
+            .context("Failed to save tokens to the keyring")
+    }
+
+    fn delete(&self) -> Result<()> {
+        match self.entry()?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(err) => Err(anyhow!(err).context("Failed to delete tokens from the keyring")),
+        }
+    }
+
+    fn exists(&self) -> Result<bool> {
+        Ok(self.get()?.is_some())
+    }
+}
+
+/// Where [`EnvTokens`] writes the refresh token after each refresh
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenSink {
+    /// Print it to stderr as `FREEAGENT_REFRESH_TOKEN=<token>`, keeping it out
+    /// of command output on stdout
+    Stderr,
+    /// Overwrite a file (owner-only on Unix) with it
+    File(PathBuf),
+}
+
+impl TokenSink {
+    /// Read the sink from `FREEAGENT_TOKEN_SINK`: `-` or `stderr`, or a path
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var(TOKEN_SINK_ENV).ok()?;
+        match value.trim() {
+            "" => None,
+            "-" | "stderr" => Some(Self::Stderr),
+            path => Some(Self::File(PathBuf::from(path))),
+        }
+    }
+
+    fn write(&self, refresh_token: &str) -> Result<()> {
+        match self {
+            Self::Stderr => {
+                eprintln!("{}={}", REFRESH_TOKEN_ENV, refresh_token);
+                Ok(())
+            }
+            Self::File(path) => write_private(path, format!("{}\n", refresh_token).as_bytes()),
+        }
+    }
+}
+
+/// Tokens supplied through the environment instead of stored on disk.
+///
+/// Without `FREEAGENT_ACCESS_TOKEN` the tokens start out expired, so the
+/// first request refreshes them. Refreshed tokens only live in memory; the
+/// refresh token is handed to the [`TokenSink`] so a pipeline can pass it to
+/// its next run.
+pub struct EnvTokens {
+    tokens: Mutex<StoredTokens>,
+    sink: Option<TokenSink>,
+}
+
+impl EnvTokens {
+    pub fn new(refresh_token: String, access_token: Option<String>, sandbox: bool) -> Self {
+        let tokens = match access_token {
+            // Assume a supplied access token was issued recently
+            Some(access_token) => StoredTokens::new(access_token, refresh_token, 3600, sandbox),
+            None => StoredTokens::new(String::new(), refresh_token, 0, sandbox),
+        };
+        Self {
+            tokens: Mutex::new(tokens),
+            sink: None,
+        }
+    }
+
+    /// Read tokens and sink from the environment, if a refresh token is set
+    pub fn from_env(sandbox: bool) -> Option<Self> {
+        let non_empty = |var| {
+            std::env::var(var)
+                .ok()
+                .map(|value: String| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let refresh_token = non_empty(REFRESH_TOKEN_ENV)?;
+        Some(
+            Self::new(refresh_token, non_empty(ACCESS_TOKEN_ENV), sandbox)
+                .with_sink(TokenSink::from_env()),
+        )
+    }
+
+    /// Write refreshed refresh tokens to `sink`
+    pub fn with_sink(mut self, sink: Option<TokenSink>) -> Self {
+        self.sink = sink;
+        self
+    }
+}
+
+impl TokenBackend for EnvTokens {
+    fn name(&self) -> &'static str {
+        "environment"
+    }
+
+    fn load(&self) -> Result<Option<StoredTokens>> {
+        let tokens = self.tokens.lock().map_err(|_| anyhow!("Token lock poisoned"))?;
+        Ok(Some(tokens.clone()))
+    }
+
+    fn save(&self, tokens: &StoredTokens) -> Result<()> {
+        let mut current = self.tokens.lock().map_err(|_| anyhow!("Token lock poisoned"))?;
+        let rotated = current.refresh_token != tokens.refresh_token;
+        *current = tokens.clone();
+
+        match &self.sink {
+            Some(sink) => sink.write(&tokens.refresh_token),
+            None => {
+                if rotated {
+                    eprintln!(
+                        "Warning: the refresh token was rotated; set {} to keep the new one",
+                        TOKEN_SINK_ENV
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn delete(&self) -> Result<()> {
+        bail!(
+            "Tokens come from {}; unset it instead of logging out",
+            REFRESH_TOKEN_ENV
+        )
+    }
+
+    fn exists(&self) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn tokens() -> StoredTokens {
+        StoredTokens::new("access".to_string(), "refresh".to_string(), 3600, false)
+    }
+
+    #[test]
+    fn parses_backend_names() -> Result<()> {
+        assert_eq!(BackendKind::parse("")?, BackendKind::Auto);
+        assert_eq!(BackendKind::parse("Keyring")?, BackendKind::Keyring);
+        assert_eq!(BackendKind::parse("encrypted")?, BackendKind::Encrypted);
+        assert_eq!(BackendKind::parse("file")?, BackendKind::File);
+        assert!(BackendKind::parse("vault").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn auto_keeps_plaintext_tokens_without_a_passphrase() -> Result<()> {
+        let dir = tempdir()?;
+        assert_eq!(auto_without_keyring(dir.path(), false).name(), "encrypted");
+
+        PlaintextFile::new(dir.path().join("tokens.json")).save(&tokens())?;
+        let backend = auto_without_keyring(dir.path(), false);
+        assert_eq!(backend.name(), "file");
+        assert_eq!(backend.load()?.expect("tokens").refresh_token, "refresh");
+
+        // With a passphrase the plaintext file is migrated as usual
+        assert_eq!(auto_without_keyring(dir.path(), true).name(), "encrypted");
+        Ok(())
+    }
+
+    #[test]
+    fn encrypted_file_roundtrip_hides_tokens() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("tokens.enc");
+        let store = EncryptedFile::new(&path).with_passphrase("correct horse");
+        store.save(&tokens())?;
+
+        let raw = fs::read_to_string(&path)?;
+        assert!(!raw.contains("access"));
+        assert!(!raw.contains("refresh"));
+        #[cfg(unix)]
+        assert_eq!(fs::metadata(&path)?.permissions().mode() & 0o777, 0o600);
+
+        let loaded = store.load()?.expect("tokens should load");
+        assert_eq!(loaded.refresh_token, "refresh");
+        assert!(store.exists()?);
+
+        let wrong = EncryptedFile::new(&path).with_passphrase("battery staple");
+        let err = wrong.load().unwrap_err();
+        assert!(err.to_string().contains("passphrase"));
+
+        store.delete()?;
+        assert!(store.load()?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn export_bundles_roundtrip() -> Result<()> {
+        let plain = export_bundle(&tokens(), false)?;
+        assert_eq!(import_bundle(&plain)?.refresh_token, "refresh");
+
+        let sealed = seal(&tokens(), "correct horse")?;
+        assert!(!sealed.contains("refresh"));
+        assert_eq!(unseal(&sealed, "correct horse")?.access_token, "access");
+        assert!(unseal(&sealed, "battery staple").is_err());
+        assert!(import_bundle("{\"access_token\": \"only\"}").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn plaintext_file_roundtrip() -> Result<()> {
+        let dir = tempdir()?;
+        let store = PlaintextFile::new(dir.path().join("nested/tokens.json"));
+        assert!(!store.exists()?);
+        store.save(&tokens())?;
+        assert_eq!(store.load()?.expect("tokens").access_token, "access");
+        store.delete()?;
+        store.delete()?;
+        assert!(!store.exists()?);
+        Ok(())
+    }
+
+    #[test]
+    fn write_private_replaces_files_atomically() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("tokens.json");
+        fs::write(&path, "old contents that are longer")?;
+
+        write_private(&path, b"new")?;
+        assert_eq!(fs::read_to_string(&path)?, "new");
+        #[cfg(unix)]
+        assert_eq!(fs::metadata(&path)?.permissions().mode() & 0o777, 0o600);
+        let entries = fs::read_dir(dir.path())?.count();
+        assert_eq!(entries, 1, "temporary file left behind");
+        Ok(())
+    }
+
+    #[test]
+    fn env_tokens_start_expired_and_write_rotated_tokens_to_sink() -> Result<()> {
+        let dir = tempdir()?;
+        let sink = dir.path().join("refresh-token");
+        let store = EnvTokens::new("first".to_string(), None, true)
+            .with_sink(Some(TokenSink::File(sink.clone())));
+
+        let mut loaded = store.load()?.expect("env tokens always load");
+        assert!(loaded.is_expired());
+        assert!(loaded.sandbox);
+        assert!(!sink.exists());
+
+        loaded.update("access".to_string(), Some("second".to_string()), 3600);
+        store.save(&loaded)?;
+        assert_eq!(fs::read_to_string(&sink)?, "second\n");
+        assert_eq!(store.load()?.expect("tokens").refresh_token, "second");
+        assert!(store.delete().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn env_token_access_token_is_used_until_expiry() -> Result<()> {
+        let store = EnvTokens::new("refresh".to_string(), Some("access".to_string()), false);
+        let loaded = store.load()?.expect("env tokens always load");
+        assert_eq!(loaded.access_token, "access");
+        assert!(!loaded.is_expired());
+        Ok(())
+    }
+}
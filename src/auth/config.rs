@@ -81,31 +81,60 @@ pub fn redirect_uri(port: u16) -> String {
     format!("http://localhost:{}/callback", port)
 }
 
-/// Get API URL based on sandbox mode
-pub fn api_url(sandbox: bool) -> &'static str {
-    if sandbox {
-        SANDBOX_API_URL
-    } else {
-        PRODUCTION_API_URL
-    }
+/// Callback port used by `login --no-browser`.
+///
+/// Nothing listens on it: the browser's failed redirect is copied back into
+/// the terminal instead, so a fixed port keeps the redirect URI predictable.
+pub const NO_BROWSER_CALLBACK_PORT: u16 = 8484;
+
+/// Environment variable overriding the API base URL
+pub const API_URL_ENV: &str = "FREEAGENT_API_URL";
+
+/// Environment variable overriding the OAuth authorization URL
+pub const AUTH_URL_ENV: &str = "FREEAGENT_AUTH_URL";
+
+/// Environment variable overriding the OAuth token URL
+pub const TOKEN_URL_ENV: &str = "FREEAGENT_TOKEN_URL";
+
+/// Read a URL override from the environment, ignoring blank values
+fn url_override(var: &str) -> Option<String> {
+    std::env::var(var)
+        .ok()
+        .map(|value| value.trim().trim_end_matches('/').to_string())
+        .filter(|value| !value.is_empty())
 }
 
-/// Get auth URL based on sandbox mode
-pub fn auth_url(sandbox: bool) -> &'static str {
-    if sandbox {
-        SANDBOX_AUTH_URL
-    } else {
-        PRODUCTION_AUTH_URL
-    }
+/// Get API URL based on sandbox mode, honouring `FREEAGENT_API_URL`
+pub fn api_url(sandbox: bool) -> String {
+    url_override(API_URL_ENV).unwrap_or_else(|| {
+        if sandbox {
+            SANDBOX_API_URL.to_string()
+        } else {
+            PRODUCTION_API_URL.to_string()
+        }
+    })
 }
 
-/// Get token URL based on sandbox mode
-pub fn token_url(sandbox: bool) -> &'static str {
-    if sandbox {
-        SANDBOX_TOKEN_URL
-    } else {
-        PRODUCTION_TOKEN_URL
-    }
+/// Get auth URL based on sandbox mode, honouring `FREEAGENT_AUTH_URL`
+pub fn auth_url(sandbox: bool) -> String {
+    url_override(AUTH_URL_ENV).unwrap_or_else(|| {
+        if sandbox {
+            SANDBOX_AUTH_URL.to_string()
+        } else {
+            PRODUCTION_AUTH_URL.to_string()
+        }
+    })
+}
+
+/// Get token URL based on sandbox mode, honouring `FREEAGENT_TOKEN_URL`
+pub fn token_url(sandbox: bool) -> String {
+    url_override(TOKEN_URL_ENV).unwrap_or_else(|| {
+        if sandbox {
+            SANDBOX_TOKEN_URL.to_string()
+        } else {
+            PRODUCTION_TOKEN_URL.to_string()
+        }
+    })
 }
 
 #[cfg(test)]
@@ -194,6 +223,7 @@ mod tests {
     }
 
     #[test]
+    #[serial]
     fn urls_switch_between_prod_and_sandbox() {
         assert_eq!(api_url(false), PRODUCTION_API_URL);
         assert_eq!(api_url(true), SANDBOX_API_URL);
@@ -202,4 +232,16 @@ mod tests {
         assert_eq!(token_url(false), PRODUCTION_TOKEN_URL);
         assert_eq!(token_url(true), SANDBOX_TOKEN_URL);
     }
+
+    #[test]
+    #[serial]
+    fn urls_use_env_overrides() {
+        std::env::set_var(API_URL_ENV, "http://localhost:9999/v2/");
+        std::env::set_var(TOKEN_URL_ENV, "  ");
+        assert_eq!(api_url(false), "http://localhost:9999/v2");
+        assert_eq!(api_url(true), "http://localhost:9999/v2");
+        assert_eq!(token_url(false), PRODUCTION_TOKEN_URL);
+        std::env::remove_var(API_URL_ENV);
+        std::env::remove_var(TOKEN_URL_ENV);
+    }
 }
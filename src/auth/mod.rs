@@ -1,7 +1,9 @@
 //! Authentication module for FreeAgent OAuth2.
 
+pub mod backend;
 pub mod config;
 pub mod oauth;
+pub mod profile;
 pub mod token;
 
 pub use config::*;
@@ -6,7 +6,7 @@ use anyhow::{anyhow, Context, Result};
 
 use oauth2::{
     basic::BasicClient, AuthType, AuthUrl, AuthorizationCode, ClientId, ClientSecret,
-    CsrfToken, ErrorResponse, PkceCodeChallenge, RedirectUrl, RefreshToken,
+    CsrfToken, ErrorResponse, PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, RefreshToken,
     RequestTokenError, TokenResponse, TokenUrl,
 };
 
@@ -16,21 +16,39 @@ use std::net::TcpListener;
 use tiny_http::{Response, Server};
 use url::Url;
 
+use crate::api::ResponseCache;
+use super::backend::EnvTokens;
 use super::config;
+use super::profile::DEFAULT_PROFILE;
 use super::token::{StoredTokens, TokenStorage};
 
 /// OAuth2 authentication manager
 pub struct OAuthManager {
     sandbox: bool,
+    profile: String,
     storage: TokenStorage,
 }
 
 impl OAuthManager {
-    /// Create a new OAuth manager
+    /// Create a new OAuth manager for the default profile
     pub fn new(sandbox: bool) -> Result<Self> {
+        Self::for_profile(DEFAULT_PROFILE, sandbox)
+    }
+
+    /// Create a new OAuth manager for a named profile
+    ///
+    /// Tokens come from `FREEAGENT_REFRESH_TOKEN` instead of the profile's
+    /// store when it is set.
+    pub fn for_profile(profile: &str, sandbox: bool) -> Result<Self> {
+        let mut storage = TokenStorage::for_profile(profile)?;
+        if let Some(env_tokens) = EnvTokens::from_env(sandbox) {
+            storage = storage.with_backend(Box::new(env_tokens));
+        }
+
         Ok(Self {
             sandbox,
-            storage: TokenStorage::new()?,
+            profile: profile.to_string(),
+            storage,
         })
     }
 
@@ -41,10 +59,8 @@ impl OAuthManager {
         let mut client = BasicClient::new(
             ClientId::new(client_id),
             Some(ClientSecret::new(client_secret)),
-            AuthUrl::new(config::auth_url(self.sandbox).to_string())
-                .context("Invalid auth URL")?,
-            Some(TokenUrl::new(config::token_url(self.sandbox).to_string())
-                .context("Invalid token URL")?),
+            AuthUrl::new(config::auth_url(self.sandbox)).context("Invalid auth URL")?,
+            Some(TokenUrl::new(config::token_url(self.sandbox)).context("Invalid token URL")?),
         )
         .set_auth_type(AuthType::RequestBody);
 
@@ -93,22 +109,7 @@ impl OAuthManager {
         let (server, callback_port) = self.start_callback_server()?;
         let redirect_uri = config::redirect_uri(callback_port);
         let client = self.create_client(Some(&redirect_uri))?;
-
-        let use_pkce = std::env::var("FREEAGENT_OAUTH_PKCE")
-            .map(|value| matches!(value.as_str(), "1" | "true" | "TRUE" | "yes" | "YES"))
-            .unwrap_or(false);
-
-        let (auth_url, csrf_token, pkce_verifier) = if use_pkce {
-            let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
-            let (auth_url, csrf_token) = client
-                .authorize_url(CsrfToken::new_random)
-                .set_pkce_challenge(pkce_challenge)
-                .url();
-            (auth_url, csrf_token, Some(pkce_verifier))
-        } else {
-            let (auth_url, csrf_token) = client.authorize_url(CsrfToken::new_random).url();
-            (auth_url, csrf_token, None)
-        };
+        let (auth_url, csrf_token, pkce_verifier) = authorize_url(&client, pkce_requested());
 
         println!("Opening browser for authentication...");
         println!();
@@ -124,9 +125,46 @@ impl OAuthManager {
         // Start local server to receive callback
         let auth_code = self.wait_for_callback(server, callback_port, &csrf_token)?;
 
+        self.exchange_code(&client, auth_code, pkce_verifier).await
+    }
+
+    /// Start the OAuth login flow without a browser or callback server.
+    ///
+    /// The authorization URL is printed, and the redirect URL the browser
+    /// ends up on is read back from stdin. PKCE is always used, since the
+    /// code travels by hand rather than straight to a local server.
+    pub async fn login_without_browser(&self) -> Result<StoredTokens> {
+        let redirect_uri = config::redirect_uri(config::NO_BROWSER_CALLBACK_PORT);
+        let client = self.create_client(Some(&redirect_uri))?;
+        let (auth_url, csrf_token, pkce_verifier) = authorize_url(&client, true);
+
+        println!("Visit this URL in a browser on any machine to authorize the CLI:");
+        println!("{}", auth_url);
+        println!();
+        println!(
+            "After approving, the browser is redirected to {} (the page will not load).",
+            redirect_uri
+        );
+        println!("Paste the full URL from the address bar:");
+
+        let mut input = String::new();
+        std::io::stdin()
+            .read_line(&mut input)
+            .context("Failed to read the redirect URL from stdin")?;
+        let auth_code = parse_callback(&input, &csrf_token)?;
+
+        self.exchange_code(&client, auth_code, pkce_verifier).await
+    }
+
+    /// Exchange an authorization code for tokens and save them
+    async fn exchange_code(
+        &self,
+        client: &BasicClient,
+        auth_code: String,
+        pkce_verifier: Option<PkceCodeVerifier>,
+    ) -> Result<StoredTokens> {
         println!("Authorization code received. Exchanging for tokens...");
 
-        // Exchange code for tokens
         let token_request = client.exchange_code(AuthorizationCode::new(auth_code));
         let token_request = if let Some(pkce_verifier) = pkce_verifier {
             token_request.set_pkce_verifier(pkce_verifier)
@@ -153,9 +191,10 @@ impl OAuthManager {
 
         // Save tokens
         self.storage.save(&tokens)?;
+        self.clear_cache();
 
         println!("Successfully authenticated!");
-        println!("Tokens saved to: {:?}", self.storage.token_file_path());
+        println!("Tokens saved to the {} store", self.storage.backend_name());
 
         Ok(tokens)
     }
@@ -177,24 +216,10 @@ impl OAuthManager {
             .recv()
             .map_err(|e| anyhow!("Failed to receive callback: {}", e))?;
 
-        let url = format!("http://localhost{}", request.url());
-        let parsed_url = Url::parse(&url).context("Failed to parse callback URL")?;
-
-        let mut code = None;
-        let mut state = None;
-        let mut error = None;
-
-        for (key, value) in parsed_url.query_pairs() {
-            match key.as_ref() {
-                "code" => code = Some(value.to_string()),
-                "state" => state = Some(value.to_string()),
-                "error" => error = Some(value.to_string()),
-                _ => {}
-            }
-        }
+        let result = parse_callback(request.url(), expected_state);
 
         // Send response to browser
-        let response_html = if error.is_some() {
+        let response_html = if result.is_err() {
             r#"<!DOCTYPE html>
 <html>
 <head><title>Authentication Failed</title></head>
@@ -218,23 +243,26 @@ impl OAuthManager {
             .with_header(tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/html"[..]).unwrap());
         let _ = request.respond(response);
 
-        // Check for errors
-        if let Some(err) = error {
-            return Err(anyhow!("OAuth error: {}", err));
-        }
-
-        // Verify state
-        let received_state = state.ok_or_else(|| anyhow!("No state parameter in callback"))?;
-        if &received_state != expected_state.secret() {
-            return Err(anyhow!("State mismatch - possible CSRF attack"));
-        }
-
-        // Return authorization code
-        code.ok_or_else(|| anyhow!("No authorization code in callback"))
+        result
     }
 
     /// Refresh the access token
+    ///
+    /// Runs under the profile's token lock. Another process may have
+    /// refreshed first and rotated the refresh token, so the stored tokens
+    /// are reloaded once the lock is held and reused if still valid.
     pub async fn refresh(&self, tokens: &mut StoredTokens) -> Result<()> {
+        let _lock = self.storage.lock()?;
+        if let Some(stored) = self.storage.load()? {
+            if stored.sandbox == tokens.sandbox {
+                let still_valid = !stored.is_expired();
+                *tokens = stored;
+                if still_valid {
+                    return Ok(());
+                }
+            }
+        }
+
         let client = self.create_client(None)?;
 
         let token_result = client
@@ -261,20 +289,20 @@ impl OAuthManager {
         let mut tokens = self
             .storage
             .load()?
-            .ok_or_else(|| anyhow!("Not logged in. Run 'freeagent login' first."))?;
+            .ok_or_else(|| anyhow!("Not logged in. Run '{}' first.", self.login_command(self.sandbox)))?;
 
         // Check if we need to use sandbox mode consistently
         if tokens.sandbox != self.sandbox {
             return Err(anyhow!(
-                "Token was created for {} mode, but {} mode was requested. Run 'freeagent login{}' to authenticate.",
+                "Token was created for {} mode, but {} mode was requested. Run '{}' to authenticate, or use a separate profile for each environment.",
                 if tokens.sandbox { "sandbox" } else { "production" },
                 if self.sandbox { "sandbox" } else { "production" },
-                if self.sandbox { " --sandbox" } else { "" }
+                self.login_command(self.sandbox)
             ));
         }
 
         if tokens.is_expired() {
-            println!("Access token expired, refreshing...");
+            eprintln!("Access token expired, refreshing...");
             self.refresh(&mut tokens).await?;
         }
 
@@ -284,14 +312,115 @@ impl OAuthManager {
     /// Logout - delete stored tokens
     pub fn logout(&self) -> Result<()> {
         self.storage.delete()?;
+        self.clear_cache();
         println!("Successfully logged out.");
         Ok(())
     }
 
+    /// Drop the profile's cached responses, which may belong to another
+    /// user or company once the tokens change
+    pub fn clear_cache(&self) {
+        let result = ResponseCache::profile_dir(&self.profile)
+            .and_then(|dir| ResponseCache::new(dir).clear());
+        if let Err(err) = result {
+            eprintln!("Warning: could not clear the response cache: {:#}", err);
+        }
+    }
+
     /// Get token storage reference
     pub fn storage(&self) -> &TokenStorage {
         &self.storage
     }
+
+    /// Name of the profile whose tokens are managed
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    /// The command that logs in to this profile
+    pub fn login_command(&self, sandbox: bool) -> String {
+        let mut command = "freeagent".to_string();
+        if self.profile != DEFAULT_PROFILE {
+            command.push_str(&format!(" --profile {}", self.profile));
+        }
+        command.push_str(" login");
+        if sandbox {
+            command.push_str(" --sandbox");
+        }
+        command
+    }
+}
+
+/// Whether `FREEAGENT_OAUTH_PKCE` asks for PKCE in the browser flow
+fn pkce_requested() -> bool {
+    std::env::var("FREEAGENT_OAUTH_PKCE")
+        .map(|value| matches!(value.as_str(), "1" | "true" | "TRUE" | "yes" | "YES"))
+        .unwrap_or(false)
+}
+
+/// Build the authorization URL, adding a PKCE challenge when `use_pkce` is set
+fn authorize_url(client: &BasicClient, use_pkce: bool) -> (Url, CsrfToken, Option<PkceCodeVerifier>) {
+    if use_pkce {
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+        let (auth_url, csrf_token) = client
+            .authorize_url(CsrfToken::new_random)
+            .set_pkce_challenge(pkce_challenge)
+            .url();
+        (auth_url, csrf_token, Some(pkce_verifier))
+    } else {
+        let (auth_url, csrf_token) = client.authorize_url(CsrfToken::new_random).url();
+        (auth_url, csrf_token, None)
+    }
+}
+
+/// Extract the authorization code from a callback.
+///
+/// `input` is a redirect URL, a path with a query string, or the query
+/// string itself. It must include the expected `state`, so a bare code is
+/// rejected: its origin can't be checked.
+fn parse_callback(input: &str, expected_state: &CsrfToken) -> Result<String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(anyhow!("No authorization code entered"));
+    }
+
+    let query = match input.split_once('?') {
+        Some((_, query)) => query,
+        None if input.contains('=') => input,
+        None => {
+            return Err(anyhow!(
+                "Expected the full URL the browser was redirected to: a bare code cannot be used because the URL's state parameter is needed to confirm the login was started here"
+            ));
+        }
+    };
+    let query = query.split('#').next().unwrap_or_default();
+
+    let mut code = None;
+    let mut state = None;
+    let mut error = None;
+
+    for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+        match key.as_ref() {
+            "code" => code = Some(value.to_string()),
+            "state" => state = Some(value.to_string()),
+            "error" => error = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    // Check for errors
+    if let Some(err) = error {
+        return Err(anyhow!("OAuth error: {}", err));
+    }
+
+    // Verify state
+    let received_state = state.ok_or_else(|| anyhow!("No state parameter in callback"))?;
+    if &received_state != expected_state.secret() {
+        return Err(anyhow!("State mismatch - possible CSRF attack"));
+    }
+
+    // Return authorization code
+    code.ok_or_else(|| anyhow!("No authorization code in callback"))
 }
 
 fn format_token_error<E, T>(context: &str, error: RequestTokenError<E, T>) -> anyhow::Error
@@ -336,3 +465,53 @@ fn format_response_body(body: &[u8]) -> String {
         truncated
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> CsrfToken {
+        CsrfToken::new("expected-state".to_string())
+    }
+
+    #[test]
+    fn parse_callback_accepts_redirect_url_with_matching_state() {
+        let code = parse_callback(
+            "http://localhost:8484/callback?code=abc%2F123&state=expected-state\n",
+            &state(),
+        )
+        .unwrap();
+        assert_eq!(code, "abc/123");
+
+        let code = parse_callback("/callback?state=expected-state&code=xyz", &state()).unwrap();
+        assert_eq!(code, "xyz");
+    }
+
+    #[test]
+    fn parse_callback_rejects_state_mismatch_and_missing_state() {
+        let err = parse_callback("http://localhost/callback?code=abc&state=other", &state())
+            .unwrap_err();
+        assert!(err.to_string().contains("State mismatch"));
+
+        let err = parse_callback("code=abc", &state()).unwrap_err();
+        assert!(err.to_string().contains("No state parameter"));
+    }
+
+    #[test]
+    fn parse_callback_reports_oauth_errors() {
+        let err = parse_callback(
+            "http://localhost/callback?error=access_denied&state=expected-state",
+            &state(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("access_denied"));
+    }
+
+    #[test]
+    fn parse_callback_rejects_bare_code() {
+        let err = parse_callback("  abc123 \n", &state()).unwrap_err();
+        assert!(err.to_string().contains("full URL"));
+        assert!(parse_callback("", &state()).is_err());
+        assert!(parse_callback("http://localhost/callback", &state()).is_err());
+    }
+}
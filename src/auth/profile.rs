@@ -0,0 +1,184 @@
+//! Named profiles for working with several companies or environments.
+//!
+//! Each profile has its own tokens, sandbox flag and response cache. The
+//! `default` profile keeps the original layout (`tokens.json` directly in the
+//! config directory) so existing logins carry over; other profiles live under
+//! `profiles/<name>/`. `config.json` records the active profile and what is
+//! known about each one.
+
+use anyhow::{bail, Context, Result};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Profile used when none is selected
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Environment variable selecting the profile
+pub const PROFILE_ENV: &str = "FREEAGENT_PROFILE";
+
+/// The CLI's config directory
+pub fn config_dir() -> Result<PathBuf> {
+    let project_dirs = ProjectDirs::from("com", "freeagent", "freeagent-cli")
+        .context("Failed to determine config directory")?;
+    Ok(project_dirs.config_dir().to_path_buf())
+}
+
+/// Check that a profile name is safe to use as a directory name
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        bail!(
+            "Invalid profile name '{}': use up to 64 letters, digits, '-' or '_'",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Directory holding a profile's tokens and cache
+pub fn profile_dir(name: &str) -> Result<PathBuf> {
+    validate_name(name)?;
+    let config_dir = config_dir()?;
+    if name == DEFAULT_PROFILE {
+        Ok(config_dir)
+    } else {
+        Ok(config_dir.join("profiles").join(name))
+    }
+}
+
+/// What is known about a profile
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileSettings {
+    /// Whether the profile talks to the sandbox API
+    #[serde(default)]
+    pub sandbox: bool,
+    /// Company name, recorded at login
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub company: Option<String>,
+}
+
+/// Contents of `config.json`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Config {
+    /// Profile used when neither `--profile` nor `FREEAGENT_PROFILE` is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ProfileSettings>,
+}
+
+impl Config {
+    /// Path to `config.json`
+    pub fn path() -> Result<PathBuf> {
+        Ok(config_dir()?.join("config.json"))
+    }
+
+    /// Load the config, or an empty one if none has been written yet
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Write the config back to disk
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize config")?;
+        fs::write(&path, json).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// The profile selected in the config
+    pub fn active(&self) -> &str {
+        self.active_profile.as_deref().unwrap_or(DEFAULT_PROFILE)
+    }
+
+    /// Settings for a profile, defaulting when it has none recorded
+    pub fn settings(&self, name: &str) -> ProfileSettings {
+        self.profiles.get(name).cloned().unwrap_or_default()
+    }
+}
+
+/// Pick the profile to use: `--profile`/`FREEAGENT_PROFILE` if given,
+/// otherwise the active profile from the config
+pub fn resolve(explicit: Option<&str>) -> Result<String> {
+    let name = match explicit.map(str::trim).filter(|name| !name.is_empty()) {
+        Some(name) => name.to_string(),
+        None => Config::load()?.active().to_string(),
+    };
+    validate_name(&name)?;
+    Ok(name)
+}
+
+/// Token files that mark a directory as holding a profile's login
+const TOKEN_FILES: &[&str] = &["tokens.json", "tokens.enc"];
+
+fn has_token_file(dir: &Path) -> bool {
+    TOKEN_FILES.iter().any(|file| dir.join(file).exists())
+}
+
+/// Profiles with token files or recorded settings, sorted by name
+///
+/// Profiles stored only in the keyring are found through their settings,
+/// which are recorded at login.
+pub fn known_profiles(config: &Config) -> Result<Vec<String>> {
+    let mut names: Vec<String> = config.profiles.keys().cloned().collect();
+    if has_token_file(&profile_dir(DEFAULT_PROFILE)?) {
+        names.push(DEFAULT_PROFILE.to_string());
+    }
+    let profiles_dir = config_dir()?.join("profiles");
+    if let Ok(entries) = fs::read_dir(&profiles_dir) {
+        for entry in entries.flatten() {
+            if let Some(name) = entry.file_name().to_str() {
+                if validate_name(name).is_ok() && has_token_file(&entry.path()) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_profile_names() {
+        assert!(validate_name("acme").is_ok());
+        assert!(validate_name("client_2-sandbox").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("../etc").is_err());
+        assert!(validate_name("a b").is_err());
+    }
+
+    #[test]
+    fn config_defaults_to_the_default_profile() -> Result<()> {
+        let config: Config = serde_json::from_str("{}")?;
+        assert_eq!(config.active(), DEFAULT_PROFILE);
+        assert_eq!(config.settings("acme"), ProfileSettings::default());
+
+        let config: Config = serde_json::from_str(
+            r#"{"active_profile": "acme", "profiles": {"acme": {"sandbox": true, "company": "Acme Ltd"}}}"#,
+        )?;
+        assert_eq!(config.active(), "acme");
+        assert!(config.settings("acme").sandbox);
+        assert_eq!(config.settings("acme").company.as_deref(), Some("Acme Ltd"));
+        Ok(())
+    }
+}
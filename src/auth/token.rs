@@ -1,17 +1,21 @@
 //! Token storage and management.
 //!
-//! Tokens are stored in a JSON file in the user's config directory.
-//! The file permissions are set to be readable only by the owner.
+//! Tokens are kept in the OS keyring, a passphrase-encrypted file or a
+//! plaintext file in the profile's directory (see [`super::backend`] and
+//! [`super::profile`]).
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Duration, Utc};
-use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::fs::{File, OpenOptions};
 use std::path::PathBuf;
+use std::sync::OnceLock;
 
 #[cfg(unix)]
-use std::os::unix::fs::PermissionsExt;
+use std::os::unix::fs::OpenOptionsExt;
+
+use super::backend::{self, BackendKind, PlaintextFile, TokenBackend};
+use super::profile::{self, DEFAULT_PROFILE};
 
 /// Stored OAuth tokens with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,88 +67,137 @@ impl StoredTokens {
     }
 }
 
+/// Exclusive lock on a profile's tokens, held across processes until dropped
+pub struct TokenLock {
+    _file: Option<File>,
+}
+
 /// Token storage manager
+///
+/// Delegates to the [`TokenBackend`] chosen by `FREEAGENT_TOKEN_STORE`,
+/// migrating a plaintext `tokens.json` into it on first use.
 pub struct TokenStorage {
+    profile: String,
     config_dir: PathBuf,
     token_file: PathBuf,
+    kind: BackendKind,
+    backend: OnceLock<Box<dyn TokenBackend>>,
 }
 
 impl TokenStorage {
-    /// Create a new token storage instance
+    /// Create a token storage instance for the default profile
     pub fn new() -> Result<Self> {
-        let project_dirs = ProjectDirs::from("com", "freeagent", "freeagent-cli")
-            .context("Failed to determine config directory")?;
-        
-        let config_dir = project_dirs.config_dir().to_path_buf();
+        Self::for_profile(DEFAULT_PROFILE)
+    }
+
+    /// Create a token storage instance for a named profile
+    pub fn for_profile(profile: &str) -> Result<Self> {
+        let config_dir = profile::profile_dir(profile)?;
         let token_file = config_dir.join("tokens.json");
         
         Ok(Self {
+            profile: profile.to_string(),
             config_dir,
             token_file,
+            kind: BackendKind::from_env()?,
+            backend: OnceLock::new(),
         })
     }
 
-    /// Ensure the config directory exists with proper permissions
-    fn ensure_config_dir(&self) -> Result<()> {
-        if !self.config_dir.exists() {
-            fs::create_dir_all(&self.config_dir)
-                .context("Failed to create config directory")?;
-            
-            #[cfg(unix)]
-            {
-                let mut perms = fs::metadata(&self.config_dir)?.permissions();
-                perms.set_mode(0o700);
-                fs::set_permissions(&self.config_dir, perms)?;
-            }
-        }
-        Ok(())
+    /// Use a specific backend instead of the one chosen by the environment
+    pub fn with_backend(self, backend: Box<dyn TokenBackend>) -> Self {
+        let _ = self.backend.set(backend);
+        self
+    }
+
+    /// The backend in use, selected on first access
+    fn backend(&self) -> &dyn TokenBackend {
+        self.backend
+            .get_or_init(|| backend::for_profile(self.kind, &self.profile, &self.config_dir))
+            .as_ref()
+    }
+
+    /// Name of the backend in use (`keyring`, `encrypted` or `file`)
+    pub fn backend_name(&self) -> &'static str {
+        self.backend().name()
     }
 
     /// Save tokens to storage
     pub fn save(&self, tokens: &StoredTokens) -> Result<()> {
-        self.ensure_config_dir()?;
-        
-        let json = serde_json::to_string_pretty(tokens)
-            .context("Failed to serialize tokens")?;
-        
-        fs::write(&self.token_file, &json)
-            .context("Failed to write token file")?;
-        
-        #[cfg(unix)]
-        {
-            let mut perms = fs::metadata(&self.token_file)?.permissions();
-            perms.set_mode(0o600);
-            fs::set_permissions(&self.token_file, perms)?;
-        }
-        
-        Ok(())
+        self.backend().save(tokens)
     }
 
     /// Load tokens from storage
     pub fn load(&self) -> Result<Option<StoredTokens>> {
-        if !self.token_file.exists() {
+        if let Some(tokens) = self.backend().load()? {
+            return Ok(Some(tokens));
+        }
+        self.migrate_plaintext()
+    }
+
+    /// Move tokens from a plaintext `tokens.json` into a secure backend
+    fn migrate_plaintext(&self) -> Result<Option<StoredTokens>> {
+        if self.backend().name() == "file" {
             return Ok(None);
         }
-        
-        let json = fs::read_to_string(&self.token_file)
-            .context("Failed to read token file")?;
-        
-        let tokens: StoredTokens = serde_json::from_str(&json)
-            .context("Failed to parse token file")?;
-        
+        let plaintext = PlaintextFile::new(&self.token_file);
+        let Some(tokens) = plaintext.load()? else {
+            return Ok(None);
+        };
+
+        self.backend().save(&tokens)?;
+        plaintext.delete()?;
+        eprintln!(
+            "Moved tokens from {} to the {} store",
+            self.token_file.display(),
+            self.backend().name()
+        );
         Ok(Some(tokens))
     }
 
-    /// Delete stored tokens (logout)
-    pub fn delete(&self) -> Result<()> {
-        if self.token_file.exists() {
-            fs::remove_file(&self.token_file)
-                .context("Failed to delete token file")?;
+    /// Take the profile's advisory token lock, waiting for other processes
+    ///
+    /// Hold it from loading tokens until refreshed ones are saved, so two
+    /// processes never spend the same refresh token. Tokens supplied through
+    /// the environment are private to this process and need no lock.
+    pub fn lock(&self) -> Result<TokenLock> {
+        if self.backend().name() == "environment" {
+            return Ok(TokenLock { _file: None });
         }
-        Ok(())
+
+        backend::ensure_private_dir(&self.config_dir)?;
+        let path = self.lock_file_path();
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create(true).truncate(false);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let file = options
+            .open(&path)
+            .with_context(|| format!("Failed to open lock file {}", path.display()))?;
+        file.lock()
+            .with_context(|| format!("Failed to lock {}", path.display()))?;
+        Ok(TokenLock { _file: Some(file) })
+    }
+
+    fn lock_file_path(&self) -> PathBuf {
+        self.config_dir.join("tokens.lock")
+    }
+
+    /// Whether tokens are stored, without decrypting them
+    pub fn has_tokens(&self) -> Result<bool> {
+        Ok(self.token_file.exists() || self.backend().exists()?)
     }
 
-    /// Get the path to the token file (for display purposes)
+    /// Delete stored tokens (logout), including any plaintext copy
+    ///
+    /// `tokens.lock` stays: another process may hold or be waiting on it,
+    /// and a fresh file would let a third lock that instead.
+    pub fn delete(&self) -> Result<()> {
+        self.backend().delete()?;
+        PlaintextFile::new(&self.token_file).delete()
+    }
+
+    /// Get the path to the plaintext token file (for display purposes)
     pub fn token_file_path(&self) -> &PathBuf {
         &self.token_file
     }
@@ -159,15 +212,21 @@ impl Default for TokenStorage {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::auth::backend::TOKEN_STORE_ENV;
     use serial_test::serial;
+    use std::fs;
     use tempfile::tempdir;
 
+    #[cfg(unix)]
+    use std::os::unix::fs::PermissionsExt;
+
     struct EnvGuard {
         home: Option<String>,
         xdg_config_home: Option<String>,
         appdata: Option<String>,
         local_appdata: Option<String>,
         userprofile: Option<String>,
+        token_store: Option<String>,
     }
 
     impl EnvGuard {
@@ -178,6 +237,7 @@ mod tests {
                 appdata: std::env::var("APPDATA").ok(),
                 local_appdata: std::env::var("LOCALAPPDATA").ok(),
                 userprofile: std::env::var("USERPROFILE").ok(),
+                token_store: std::env::var(TOKEN_STORE_ENV).ok(),
             };
 
             std::env::set_var("HOME", temp_path);
@@ -185,6 +245,7 @@ mod tests {
             std::env::set_var("APPDATA", temp_path);
             std::env::set_var("LOCALAPPDATA", temp_path);
             std::env::set_var("USERPROFILE", temp_path);
+            std::env::set_var(TOKEN_STORE_ENV, "file");
 
             guard
         }
@@ -221,6 +282,12 @@ mod tests {
             } else {
                 std::env::remove_var("USERPROFILE");
             }
+
+            if let Some(value) = &self.token_store {
+                std::env::set_var(TOKEN_STORE_ENV, value);
+            } else {
+                std::env::remove_var(TOKEN_STORE_ENV);
+            }
         }
     }
 
@@ -294,4 +361,63 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn delete_keeps_the_lock_file() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let _guard = EnvGuard::new(temp_dir.path().to_str().expect("temp path"));
+
+        let storage = TokenStorage::new()?;
+        storage.save(&StoredTokens::new("access".to_string(), "refresh".to_string(), 3600, false))?;
+        let lock = storage.lock()?;
+        storage.delete()?;
+        assert!(storage.load()?.is_none());
+        assert!(storage.lock_file_path().exists());
+        drop(lock);
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn named_profiles_store_tokens_separately() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let _guard = EnvGuard::new(temp_dir.path().to_str().expect("temp path"));
+
+        let default = TokenStorage::new()?;
+        let acme = TokenStorage::for_profile("acme")?;
+        assert!(acme.token_file_path().ends_with("profiles/acme/tokens.json"));
+        assert!(TokenStorage::for_profile("../escape").is_err());
+
+        acme.save(&StoredTokens::new("acme".to_string(), "refresh".to_string(), 3600, true))?;
+        assert!(default.load()?.is_none());
+        assert_eq!(acme.load()?.expect("acme tokens").access_token, "acme");
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn plaintext_tokens_migrate_into_secure_backend() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let _guard = EnvGuard::new(temp_dir.path().to_str().expect("temp path"));
+
+        let plaintext = TokenStorage::new()?;
+        plaintext.save(&StoredTokens::new("access".to_string(), "refresh".to_string(), 3600, false))?;
+        assert!(plaintext.token_file_path().exists());
+
+        let encrypted_path = temp_dir.path().join("tokens.enc");
+        let storage = TokenStorage::new()?.with_backend(Box::new(
+            backend::EncryptedFile::new(&encrypted_path).with_passphrase("secret"),
+        ));
+        assert!(storage.has_tokens()?);
+        let migrated = storage.load()?.expect("tokens should migrate");
+        assert_eq!(migrated.access_token, "access");
+        assert!(!storage.token_file_path().exists());
+        assert!(encrypted_path.exists());
+        assert_eq!(storage.load()?.expect("tokens").refresh_token, "refresh");
+
+        storage.delete()?;
+        assert!(!storage.has_tokens()?);
+        Ok(())
+    }
 }
@@ -3,8 +3,10 @@
 use anyhow::Result;
 use clap::Subcommand;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Subcommand)]
 pub enum AccountingCommands {
@@ -81,14 +83,14 @@ pub enum AccountingCommands {
 }
 
 impl AccountingCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::BalanceSheet { date } => {
                 let params = QueryBuilder::new()
                     .add("date", date.clone())
                     .build();
                 let result = client.get("accounting/balance_sheet", params).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::ProfitAndLoss { from_date, to_date } => {
                 let params = QueryBuilder::new()
@@ -96,14 +98,14 @@ impl AccountingCommands {
                     .add("to_date", to_date.clone())
                     .build();
                 let result = client.get("accounting/profit_and_loss_summary", params).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::TrialBalance { date } => {
                 let params = QueryBuilder::new()
                     .add("date", date.clone())
                     .build();
                 let result = client.get("accounting/trial_balance", params).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Cashflow { from_date, to_date } => {
                 let params = QueryBuilder::new()
@@ -111,7 +113,7 @@ impl AccountingCommands {
                     .add("to_date", to_date.clone())
                     .build();
                 let result = client.get("accounting/cashflow", params).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::GeneralLedger { from_date, to_date } => {
                 let params = QueryBuilder::new()
@@ -119,7 +121,7 @@ impl AccountingCommands {
                     .add("to_date", to_date.clone())
                     .build();
                 let result = client.get("accounting/general_ledger", params).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::AccountTransactions { category, from_date, to_date } => {
                 let params = QueryBuilder::new()
@@ -127,16 +129,14 @@ impl AccountingCommands {
                     .add("from_date", from_date.clone())
                     .add("to_date", to_date.clone())
                     .build();
-                let result = client.get("accounting/account_transactions", params).await?;
-                print_output(&result, format);
+                print_list(client, "accounting/account_transactions", params, options).await?;
             }
             Self::Transactions => {
-                let result = client.get("accounting/transactions", None).await?;
-                print_output(&result, format);
+                print_list(client, "accounting/transactions", None, options).await?;
             }
             Self::Transaction { id } => {
                 let result = client.get(&format!("accounting/transactions/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
@@ -1,10 +1,15 @@
 //! Attachment commands.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Subcommand;
+use serde_json::json;
+use std::path::PathBuf;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::download;
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Subcommand)]
 pub enum AttachmentCommands {
@@ -25,6 +30,16 @@ pub enum AttachmentCommands {
         id: String,
     },
     
+    /// Download an attachment's file
+    Download {
+        /// Attachment ID
+        id: String,
+        
+        /// File or directory to write to (defaults to the attachment's file name)
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    
     /// Delete an attachment
     Delete {
         /// Attachment ID
@@ -37,19 +52,33 @@ pub enum AttachmentCommands {
 }
 
 impl AttachmentCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { page, per_page } => {
                 let params = QueryBuilder::new()
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("attachments", params).await?;
-                print_output(&result, format);
+                print_list(client, "attachments", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("attachments/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
+            }
+            Self::Download { id, output } => {
+                let result = client.get(&format!("attachments/{}", id), None).await?;
+                let attachment = &result["attachment"];
+                let content_src = attachment["content_src"]
+                    .as_str()
+                    .context("Attachment has no content_src to download")?;
+                let file_name = attachment["file_name"]
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("attachment-{}", id));
+
+                let path = download::output_path(output.as_deref(), &file_name);
+                let bytes = client.download(content_src, &path).await?;
+                print_output(&json!({ "path": path, "bytes": bytes }), options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -57,7 +86,7 @@ impl AttachmentCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("attachments/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
@@ -1,39 +1,95 @@
 //! Authentication commands.
 
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use clap::Subcommand;
+use serde_json::{json, Value};
+use std::io::Read;
+use std::path::PathBuf;
 
-use crate::auth::OAuthManager;
-use crate::output::{print_info, print_success};
+use freeagent::api::FreeAgentClient;
+use freeagent::auth::backend;
+use freeagent::auth::profile::{Config, DEFAULT_PROFILE};
+use freeagent::auth::OAuthManager;
+use freeagent::output::{print_info, print_output, print_success, OutputOptions};
 
 #[derive(Debug, Subcommand)]
 pub enum AuthCommands {
     /// Login to FreeAgent using OAuth2
-    Login,
-    
+    Login {
+        /// Print the authorization URL and paste the redirect URL back instead of using a local callback
+        ///
+        /// Paste the full URL from the address bar, not just the code: its
+        /// state parameter is checked so a login started elsewhere cannot be
+        /// slipped in.
+        #[arg(long)]
+        no_browser: bool,
+    },
+
     /// Logout and delete stored tokens
     Logout,
-    
+
     /// Show current authentication status
     Status,
+
+    /// Print the stored tokens so they can be imported on another machine
+    Export {
+        /// Encrypt the bundle with the token passphrase
+        #[arg(long)]
+        encrypt: bool,
+    },
+
+    /// Store tokens printed by `auth export`
+    Import {
+        /// File containing the exported bundle, or - for stdin
+        #[arg(default_value = "-")]
+        file: PathBuf,
+    },
+
+    /// Make sure the access token is valid, refreshing it if needed
+    Token {
+        /// Print the access token, e.g. for use with curl
+        #[arg(long)]
+        print: bool,
+    },
 }
 
 impl AuthCommands {
-    pub async fn execute(&self, sandbox: bool) -> Result<()> {
-        let oauth = OAuthManager::new(sandbox)?;
-        
+    /// `options` is set only when `--format` was given explicitly; `status`
+    /// prints plain text otherwise
+    pub async fn execute(&self, profile: &str, sandbox: bool, options: Option<&OutputOptions>) -> Result<()> {
+        let oauth = OAuthManager::for_profile(profile, sandbox)?;
+
         match self {
-            Self::Login => {
-                oauth.login().await?;
+            Self::Login { no_browser } => {
+                if *no_browser {
+                    oauth.login_without_browser().await?;
+                } else {
+                    oauth.login().await?;
+                }
+                remember_profile(profile, sandbox).await?;
+                if profile != DEFAULT_PROFILE {
+                    print_info(&format!("Saved as profile '{}'", profile));
+                }
             }
             Self::Logout => {
                 oauth.logout()?;
             }
             Self::Status => {
+                if let Some(options) = options {
+                    print_output(&status_json(&oauth, profile, sandbox).await?, options)?;
+                    return Ok(());
+                }
+
+                let settings = Config::load()?.settings(profile);
                 match oauth.storage().load()? {
                     Some(tokens) => {
                         print_success("Logged in");
+                        print_info(&format!("Profile: {}", profile));
+                        if let Some(company) = &settings.company {
+                            print_info(&format!("Company: {}", company));
+                        }
                         print_info(&format!("Environment: {}", if tokens.sandbox { "Sandbox" } else { "Production" }));
+                        print_info(&format!("Token store: {}", oauth.storage().backend_name()));
                         print_info(&format!("Token expires: {}", tokens.expires_at));
                         print_info(&format!("Last refreshed: {}", tokens.last_refreshed));
                         if tokens.is_expired() {
@@ -41,12 +97,128 @@ impl AuthCommands {
                         }
                     }
                     None => {
-                        print_info("Not logged in. Run 'freeagent login' to authenticate.");
+                        print_info(&format!(
+                            "Not logged in. Run '{}' to authenticate.",
+                            oauth.login_command(sandbox)
+                        ));
                     }
                 }
             }
+            Self::Export { encrypt } => {
+                let tokens = oauth.storage().load()?.ok_or_else(|| {
+                    anyhow!("Not logged in. Run '{}' first.", oauth.login_command(sandbox))
+                })?;
+                println!("{}", backend::export_bundle(&tokens, *encrypt)?);
+            }
+            Self::Import { file } => {
+                let bundle = if file.as_os_str() == "-" {
+                    let mut bundle = String::new();
+                    std::io::stdin()
+                        .read_to_string(&mut bundle)
+                        .context("Failed to read token bundle from stdin")?;
+                    bundle
+                } else {
+                    std::fs::read_to_string(file)
+                        .with_context(|| format!("Failed to read {}", file.display()))?
+                };
+                let tokens = backend::import_bundle(&bundle)?;
+
+                {
+                    let _lock = oauth.storage().lock()?;
+                    oauth.storage().save(&tokens)?;
+                }
+                oauth.clear_cache();
+                remember_profile(profile, tokens.sandbox).await?;
+                print_success(&format!(
+                    "Imported {} tokens into profile '{}'",
+                    if tokens.sandbox { "sandbox" } else { "production" },
+                    profile
+                ));
+            }
+            Self::Token { print } => {
+                let tokens = oauth.get_valid_tokens().await?;
+                if *print {
+                    println!("{}", tokens.access_token);
+                } else {
+                    print_info(&format!(
+                        "Access token valid until {} (use --print to show it)",
+                        tokens.expires_at
+                    ));
+                }
+            }
         }
-        
+
         Ok(())
     }
 }
+
+/// Record a profile's environment and company in the config file
+async fn remember_profile(profile: &str, sandbox: bool) -> Result<()> {
+    let company = match company_name(profile, sandbox).await {
+        Ok(name) => name,
+        Err(err) => {
+            eprintln!("Warning: could not look up the company name: {:#}", err);
+            None
+        }
+    };
+
+    let mut config = Config::load()?;
+    let settings = config.profiles.entry(profile.to_string()).or_default();
+    settings.sandbox = sandbox;
+    settings.company = company;
+    config.save()
+}
+
+/// Machine-readable status, including who the tokens belong to
+async fn status_json(oauth: &OAuthManager, profile: &str, sandbox: bool) -> Result<Value> {
+    let Some(tokens) = oauth.storage().load()? else {
+        return Ok(json!({
+            "logged_in": false,
+            "profile": profile,
+            "login_command": oauth.login_command(sandbox),
+        }));
+    };
+
+    let (user, company) = match identity(profile, sandbox).await {
+        Ok(identity) => identity,
+        Err(err) => {
+            eprintln!("Warning: could not look up the user and company: {:#}", err);
+            (Value::Null, Value::Null)
+        }
+    };
+    // Reload in case looking up the identity refreshed the tokens
+    let tokens = oauth.storage().load()?.unwrap_or(tokens);
+
+    Ok(json!({
+        "logged_in": true,
+        "profile": profile,
+        "environment": if tokens.sandbox { "sandbox" } else { "production" },
+        "token_store": oauth.storage().backend_name(),
+        "expires_at": tokens.expires_at,
+        "expired": tokens.is_expired(),
+        "last_refreshed": tokens.last_refreshed,
+        "user": user,
+        "company": company,
+    }))
+}
+
+/// Fetch the authenticated user and company
+async fn identity(profile: &str, sandbox: bool) -> Result<(Value, Value)> {
+    let client = FreeAgentClient::for_profile(profile, sandbox).await?;
+    let user = client.get("users/me", None).await?;
+    let company = client.get("company", None).await?;
+    Ok((
+        user.get("user").cloned().unwrap_or(Value::Null),
+        company.get("company").cloned().unwrap_or(Value::Null),
+    ))
+}
+
+/// Fetch the name of the company a freshly logged-in profile belongs to
+async fn company_name(profile: &str, sandbox: bool) -> Result<Option<String>> {
+    let client = FreeAgentClient::for_profile(profile, sandbox).await?;
+    let company = client.get("company", None).await?;
+    Ok(company
+        .pointer("/company/name")
+        .and_then(Value::as_str)
+        .map(str::to_string))
+}
@@ -4,8 +4,10 @@ use anyhow::Result;
 use clap::{Subcommand, ValueEnum};
 use serde_json::json;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Clone, ValueEnum)]
 pub enum BankAccountView {
@@ -27,6 +29,7 @@ impl BankAccountView {
 }
 
 #[derive(Debug, Clone, ValueEnum)]
+#[allow(clippy::enum_variant_names)]
 pub enum BankAccountType {
     StandardBankAccount,
     CreditCardAccount,
@@ -127,7 +130,7 @@ pub enum BankAccountCommands {
 }
 
 impl BankAccountCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { view, page, per_page } => {
                 let params = QueryBuilder::new()
@@ -135,12 +138,11 @@ impl BankAccountCommands {
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("bank_accounts", params).await?;
-                print_output(&result, format);
+                print_list(client, "bank_accounts", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("bank_accounts/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 account_type,
@@ -169,7 +171,7 @@ impl BankAccountCommands {
                 
                 let body = json!({ "bank_account": account });
                 let result = client.post("bank_accounts", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update { id, name, bank_name, is_primary } => {
                 let mut account = serde_json::Map::new();
@@ -186,7 +188,7 @@ impl BankAccountCommands {
                 
                 let body = json!({ "bank_account": account });
                 let result = client.put(&format!("bank_accounts/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -194,7 +196,7 @@ impl BankAccountCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("bank_accounts/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
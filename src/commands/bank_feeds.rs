@@ -3,8 +3,10 @@
 use anyhow::Result;
 use clap::Subcommand;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Subcommand)]
 pub enum BankFeedCommands {
@@ -27,19 +29,18 @@ pub enum BankFeedCommands {
 }
 
 impl BankFeedCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { page, per_page } => {
                 let params = QueryBuilder::new()
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("bank_feeds", params).await?;
-                print_output(&result, format);
+                print_list(client, "bank_feeds", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("bank_feeds/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
 
@@ -3,10 +3,15 @@
 use anyhow::{bail, Result};
 use clap::Subcommand;
 use serde_json::Value;
+use std::path::PathBuf;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::upload::Attachment;
+use freeagent::api::{FreeAgentClient, QueryBuilder, ResourceKind};
+use freeagent::output::{print_output, OutputOptions};
 
+use super::print_list;
+
+#[allow(clippy::too_many_arguments)]
 fn build_explanation_body(
     bank_account: Option<String>,
     bank_transaction: Option<String>,
@@ -209,11 +214,19 @@ fn build_explanation_body(
     }
 }
 
+/// Encode an `--attach` file as attachment JSON, or pass `--attachment-json` through
+fn resolve_attachment(attachment_json: &Option<String>, attach: &Option<PathBuf>) -> Result<Option<String>> {
+    match attach {
+        Some(path) => Ok(Some(serde_json::to_string(&Attachment::from_path(path)?)?)),
+        None => Ok(attachment_json.clone()),
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum BankTransactionExplanationCommands {
     /// List bank transaction explanations
     List {
-        /// Bank account URL (required)
+        /// Bank account ID, URL or name (required)
         #[arg(long)]
         bank_account: String,
 
@@ -246,7 +259,7 @@ pub enum BankTransactionExplanationCommands {
 
     /// Create a bank transaction explanation (JSON payload)
     Create {
-        /// Bank account URL
+        /// Bank account ID, URL or name
         #[arg(long)]
         bank_account: Option<String>,
 
@@ -266,7 +279,7 @@ pub enum BankTransactionExplanationCommands {
         #[arg(long)]
         description: Option<String>,
 
-        /// Category URL
+        /// Category nominal code, URL or name
         #[arg(long)]
         category: Option<String>,
 
@@ -306,7 +319,7 @@ pub enum BankTransactionExplanationCommands {
         #[arg(long)]
         cheque_number: Option<String>,
 
-        /// Project URL
+        /// Project ID, URL or name
         #[arg(long)]
         project: Option<String>,
 
@@ -330,11 +343,11 @@ pub enum BankTransactionExplanationCommands {
         #[arg(long)]
         paid_bill: Option<String>,
 
-        /// Paid user URL
+        /// Paid user ID, URL or name
         #[arg(long)]
         paid_user: Option<String>,
 
-        /// Transfer bank account URL
+        /// Transfer bank account ID, URL or name
         #[arg(long)]
         transfer_bank_account: Option<String>,
 
@@ -354,7 +367,7 @@ pub enum BankTransactionExplanationCommands {
         #[arg(long)]
         property: Option<String>,
 
-        /// Direct contact URL
+        /// Direct contact ID, URL or name
         #[arg(long)]
         direct_contact: Option<String>,
 
@@ -362,6 +375,10 @@ pub enum BankTransactionExplanationCommands {
         #[arg(long)]
         attachment_json: Option<String>,
 
+        /// File to attach (PDF, PNG, JPEG or GIF, up to 5 MB)
+        #[arg(long, value_name = "PATH", conflicts_with = "attachment_json")]
+        attach: Option<PathBuf>,
+
         /// Capital asset as JSON (for depreciation profile)
         #[arg(long)]
         capital_asset_json: Option<String>,
@@ -376,7 +393,7 @@ pub enum BankTransactionExplanationCommands {
         /// Explanation ID
         id: String,
 
-        /// Bank account URL
+        /// Bank account ID, URL or name
         #[arg(long)]
         bank_account: Option<String>,
 
@@ -396,7 +413,7 @@ pub enum BankTransactionExplanationCommands {
         #[arg(long)]
         description: Option<String>,
 
-        /// Category URL
+        /// Category nominal code, URL or name
         #[arg(long)]
         category: Option<String>,
 
@@ -436,7 +453,7 @@ pub enum BankTransactionExplanationCommands {
         #[arg(long)]
         cheque_number: Option<String>,
 
-        /// Project URL
+        /// Project ID, URL or name
         #[arg(long)]
         project: Option<String>,
 
@@ -460,11 +477,11 @@ pub enum BankTransactionExplanationCommands {
         #[arg(long)]
         paid_bill: Option<String>,
 
-        /// Paid user URL
+        /// Paid user ID, URL or name
         #[arg(long)]
         paid_user: Option<String>,
 
-        /// Transfer bank account URL
+        /// Transfer bank account ID, URL or name
         #[arg(long)]
         transfer_bank_account: Option<String>,
 
@@ -484,7 +501,7 @@ pub enum BankTransactionExplanationCommands {
         #[arg(long)]
         property: Option<String>,
 
-        /// Direct contact URL
+        /// Direct contact ID, URL or name
         #[arg(long)]
         direct_contact: Option<String>,
 
@@ -492,6 +509,10 @@ pub enum BankTransactionExplanationCommands {
         #[arg(long)]
         attachment_json: Option<String>,
 
+        /// File to attach (PDF, PNG, JPEG or GIF, up to 5 MB)
+        #[arg(long, value_name = "PATH", conflicts_with = "attachment_json")]
+        attach: Option<PathBuf>,
+
         /// Capital asset as JSON (for depreciation profile)
         #[arg(long)]
         capital_asset_json: Option<String>,
@@ -513,7 +534,7 @@ pub enum BankTransactionExplanationCommands {
 }
 
 impl BankTransactionExplanationCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List {
                 bank_account,
@@ -524,21 +545,20 @@ impl BankTransactionExplanationCommands {
                 per_page,
             } => {
                 let params = QueryBuilder::new()
-                    .add("bank_account", Some(bank_account.clone()))
+                    .add("bank_account", Some(client.resolve(ResourceKind::BankAccount, bank_account).await?))
                     .add("from_date", from_date.clone())
                     .add("to_date", to_date.clone())
                     .add("updated_since", updated_since.clone())
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("bank_transaction_explanations", params).await?;
-                print_output(&result, format);
+                print_list(client, "bank_transaction_explanations", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client
                     .get(&format!("bank_transaction_explanations/{}", id), None)
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 bank_account,
@@ -570,16 +590,17 @@ impl BankTransactionExplanationCommands {
                 property,
                 direct_contact,
                 attachment_json,
+                attach,
                 capital_asset_json,
                 data,
             } => {
                 let body = build_explanation_body(
-                    bank_account.clone(),
+                    client.resolve_opt(ResourceKind::BankAccount, bank_account.as_deref()).await?,
                     bank_transaction.clone(),
                     dated_on.clone(),
                     gross_value.clone(),
                     description.clone(),
-                    category.clone(),
+                    client.resolve_opt(ResourceKind::Category, category.as_deref()).await?,
                     sales_tax_rate.clone(),
                     sales_tax_value.clone(),
                     sales_tax_status.clone(),
@@ -589,26 +610,26 @@ impl BankTransactionExplanationCommands {
                     ec_status.clone(),
                     place_of_supply.clone(),
                     cheque_number.clone(),
-                    project.clone(),
+                    client.resolve_opt(ResourceKind::Project, project.as_deref()).await?,
                     rebill_type.clone(),
                     rebill_factor.clone(),
                     receipt_reference.clone(),
                     paid_invoice.clone(),
                     paid_bill.clone(),
-                    paid_user.clone(),
-                    transfer_bank_account.clone(),
+                    client.resolve_opt(ResourceKind::User, paid_user.as_deref()).await?,
+                    client.resolve_opt(ResourceKind::BankAccount, transfer_bank_account.as_deref()).await?,
                     stock_item.clone(),
-                    stock_altering_quantity.clone(),
+                    *stock_altering_quantity,
                     disposed_asset.clone(),
                     property.clone(),
-                    direct_contact.clone(),
-                    attachment_json.clone(),
+                    client.resolve_opt(ResourceKind::Contact, direct_contact.as_deref()).await?,
+                    resolve_attachment(attachment_json, attach)?,
                     capital_asset_json.clone(),
                     data.clone(),
                     true,
                 )?;
                 let result = client.post("bank_transaction_explanations", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update {
                 id,
@@ -641,16 +662,17 @@ impl BankTransactionExplanationCommands {
                 property,
                 direct_contact,
                 attachment_json,
+                attach,
                 capital_asset_json,
                 data,
             } => {
                 let body = build_explanation_body(
-                    bank_account.clone(),
+                    client.resolve_opt(ResourceKind::BankAccount, bank_account.as_deref()).await?,
                     bank_transaction.clone(),
                     dated_on.clone(),
                     gross_value.clone(),
                     description.clone(),
-                    category.clone(),
+                    client.resolve_opt(ResourceKind::Category, category.as_deref()).await?,
                     sales_tax_rate.clone(),
                     sales_tax_value.clone(),
                     sales_tax_status.clone(),
@@ -660,20 +682,20 @@ impl BankTransactionExplanationCommands {
                     ec_status.clone(),
                     place_of_supply.clone(),
                     cheque_number.clone(),
-                    project.clone(),
+                    client.resolve_opt(ResourceKind::Project, project.as_deref()).await?,
                     rebill_type.clone(),
                     rebill_factor.clone(),
                     receipt_reference.clone(),
                     paid_invoice.clone(),
                     paid_bill.clone(),
-                    paid_user.clone(),
-                    transfer_bank_account.clone(),
+                    client.resolve_opt(ResourceKind::User, paid_user.as_deref()).await?,
+                    client.resolve_opt(ResourceKind::BankAccount, transfer_bank_account.as_deref()).await?,
                     stock_item.clone(),
-                    stock_altering_quantity.clone(),
+                    *stock_altering_quantity,
                     disposed_asset.clone(),
                     property.clone(),
-                    direct_contact.clone(),
-                    attachment_json.clone(),
+                    client.resolve_opt(ResourceKind::Contact, direct_contact.as_deref()).await?,
+                    resolve_attachment(attachment_json, attach)?,
                     capital_asset_json.clone(),
                     data.clone(),
                     false,
@@ -681,7 +703,7 @@ impl BankTransactionExplanationCommands {
                 let result = client
                     .put(&format!("bank_transaction_explanations/{}", id), Some(body))
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -691,7 +713,7 @@ impl BankTransactionExplanationCommands {
                 let result = client
                     .delete(&format!("bank_transaction_explanations/{}", id))
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
 
@@ -743,6 +765,22 @@ mod tests {
         )
     }
 
+    #[test]
+    fn attach_file_becomes_attachment_json() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("receipt.pdf");
+        std::fs::write(&path, b"%PDF-1.4")?;
+
+        let raw = resolve_attachment(&None, &Some(path))?.expect("attachment json");
+        let attachment: Value = serde_json::from_str(&raw)?;
+        assert_eq!(attachment["file_name"], "receipt.pdf");
+        assert_eq!(attachment["content_type"], "application/x-pdf");
+
+        let passthrough = resolve_attachment(&Some("{}".to_string()), &None)?;
+        assert_eq!(passthrough.as_deref(), Some("{}"));
+        Ok(())
+    }
+
     #[test]
     fn explanation_requires_account_or_transaction() {
         let result = build_minimal(None, None);
@@ -4,8 +4,10 @@ use anyhow::Result;
 use clap::{Subcommand, ValueEnum};
 use serde_json::json;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder, ResourceKind};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Clone, ValueEnum)]
 pub enum BankTransactionView {
@@ -32,7 +34,7 @@ impl BankTransactionView {
 pub enum BankTransactionCommands {
     /// List bank transactions
     List {
-        /// Bank account URL (required)
+        /// Bank account ID, URL or name (required)
         #[arg(long)]
         bank_account: String,
         
@@ -65,7 +67,7 @@ pub enum BankTransactionCommands {
     
     /// Create a new bank transaction
     Create {
-        /// Bank account URL (required)
+        /// Bank account ID, URL or name (required)
         #[arg(long)]
         bank_account: String,
         
@@ -112,7 +114,7 @@ pub enum BankTransactionCommands {
 }
 
 impl BankTransactionCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List {
                 bank_account,
@@ -123,23 +125,22 @@ impl BankTransactionCommands {
                 per_page,
             } => {
                 let params = QueryBuilder::new()
-                    .add("bank_account", Some(bank_account.clone()))
+                    .add("bank_account", Some(client.resolve(ResourceKind::BankAccount, bank_account).await?))
                     .add("view", view.as_ref().map(|v| v.as_str()))
                     .add("from_date", from_date.clone())
                     .add("to_date", to_date.clone())
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("bank_transactions", params).await?;
-                print_output(&result, format);
+                print_list(client, "bank_transactions", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("bank_transactions/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create { bank_account, dated_on, amount, description } => {
                 let mut transaction = serde_json::Map::new();
-                transaction.insert("bank_account".to_string(), json!(bank_account));
+                transaction.insert("bank_account".to_string(), json!(client.resolve(ResourceKind::BankAccount, bank_account).await?));
                 transaction.insert("dated_on".to_string(), json!(dated_on));
                 transaction.insert("amount".to_string(), json!(amount));
                 
@@ -149,7 +150,7 @@ impl BankTransactionCommands {
                 
                 let body = json!({ "bank_transaction": transaction });
                 let result = client.post("bank_transactions", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update { id, dated_on, amount, description } => {
                 let mut transaction = serde_json::Map::new();
@@ -166,7 +167,7 @@ impl BankTransactionCommands {
                 
                 let body = json!({ "bank_transaction": transaction });
                 let result = client.put(&format!("bank_transactions/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -174,7 +175,7 @@ impl BankTransactionCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("bank_transactions/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
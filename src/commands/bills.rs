@@ -3,9 +3,13 @@
 use anyhow::Result;
 use clap::{Subcommand, ValueEnum};
 use serde_json::json;
+use std::path::PathBuf;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::upload::Attachment;
+use freeagent::api::{FreeAgentClient, QueryBuilder, ResourceKind};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Clone, ValueEnum)]
 pub enum BillView {
@@ -38,7 +42,7 @@ pub enum BillCommands {
         #[arg(long)]
         view: Option<BillView>,
         
-        /// Filter by contact URL
+        /// Filter by contact ID, URL or name
         #[arg(long)]
         contact: Option<String>,
         
@@ -67,7 +71,7 @@ pub enum BillCommands {
     
     /// Create a new bill
     Create {
-        /// Contact URL (required)
+        /// Contact ID, URL or name (required)
         #[arg(long)]
         contact: String,
         
@@ -91,13 +95,17 @@ pub enum BillCommands {
         #[arg(long)]
         total_value: Option<String>,
         
-        /// Category URL
+        /// Category nominal code, URL or name
         #[arg(long)]
         category: Option<String>,
         
         /// Comments
         #[arg(long)]
         comments: Option<String>,
+        
+        /// File to attach (PDF, PNG, JPEG or GIF, up to 5 MB)
+        #[arg(long, value_name = "PATH")]
+        attach: Option<PathBuf>,
     },
     
     /// Update a bill
@@ -120,6 +128,10 @@ pub enum BillCommands {
         /// Comments
         #[arg(long)]
         comments: Option<String>,
+        
+        /// File to attach (PDF, PNG, JPEG or GIF, up to 5 MB)
+        #[arg(long, value_name = "PATH")]
+        attach: Option<PathBuf>,
     },
     
     /// Delete a bill
@@ -134,7 +146,7 @@ pub enum BillCommands {
 }
 
 impl BillCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List {
                 view,
@@ -146,18 +158,17 @@ impl BillCommands {
             } => {
                 let params = QueryBuilder::new()
                     .add("view", view.as_ref().map(|v| v.as_str()))
-                    .add("contact", contact.clone())
+                    .add("contact", client.resolve_opt(ResourceKind::Contact, contact.as_deref()).await?)
                     .add("from_date", from_date.clone())
                     .add("to_date", to_date.clone())
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("bills", params).await?;
-                print_output(&result, format);
+                print_list(client, "bills", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("bills/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 contact,
@@ -168,9 +179,10 @@ impl BillCommands {
                 total_value,
                 category,
                 comments,
+                attach,
             } => {
                 let mut bill = serde_json::Map::new();
-                bill.insert("contact".to_string(), json!(contact));
+                bill.insert("contact".to_string(), json!(client.resolve(ResourceKind::Contact, contact).await?));
                 
                 if let Some(v) = dated_on {
                     bill.insert("dated_on".to_string(), json!(v));
@@ -188,17 +200,20 @@ impl BillCommands {
                     bill.insert("total_value".to_string(), json!(v));
                 }
                 if let Some(v) = category {
-                    bill.insert("category".to_string(), json!(v));
+                    bill.insert("category".to_string(), json!(client.resolve(ResourceKind::Category, v).await?));
                 }
                 if let Some(v) = comments {
                     bill.insert("comments".to_string(), json!(v));
                 }
+                if let Some(path) = attach {
+                    bill.insert("attachment".to_string(), json!(Attachment::from_path(path)?));
+                }
                 
                 let body = json!({ "bill": bill });
                 let result = client.post("bills", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
-            Self::Update { id, dated_on, due_on, reference, comments } => {
+            Self::Update { id, dated_on, due_on, reference, comments, attach } => {
                 let mut bill = serde_json::Map::new();
                 
                 if let Some(v) = dated_on {
@@ -213,10 +228,13 @@ impl BillCommands {
                 if let Some(v) = comments {
                     bill.insert("comments".to_string(), json!(v));
                 }
+                if let Some(path) = attach {
+                    bill.insert("attachment".to_string(), json!(Attachment::from_path(path)?));
+                }
                 
                 let body = json!({ "bill": bill });
                 let result = client.put(&format!("bills/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -224,7 +242,7 @@ impl BillCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("bills/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
@@ -0,0 +1,27 @@
+//! Response cache commands.
+
+use anyhow::Result;
+use clap::Subcommand;
+
+use freeagent::api::ResponseCache;
+use freeagent::output::print_success;
+
+#[derive(Debug, Subcommand)]
+pub enum CacheCommands {
+    /// Delete all cached API responses for the current profile
+    Clear,
+}
+
+impl CacheCommands {
+    pub fn execute(&self, profile: &str) -> Result<()> {
+        match self {
+            Self::Clear => {
+                let cache = ResponseCache::new(ResponseCache::profile_dir(profile)?);
+                let removed = cache.clear()?;
+                print_success(&format!("Cleared {} cached response(s)", removed));
+            }
+        }
+
+        Ok(())
+    }
+}
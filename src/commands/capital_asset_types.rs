@@ -4,8 +4,10 @@ use anyhow::{bail, Result};
 use clap::Subcommand;
 use serde_json::Value;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 fn build_capital_asset_type_body(
     name: Option<String>,
@@ -92,29 +94,28 @@ pub enum CapitalAssetTypeCommands {
 }
 
 impl CapitalAssetTypeCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { page, per_page } => {
                 let params = QueryBuilder::new()
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("capital_asset_types", params).await?;
-                print_output(&result, format);
+                print_list(client, "capital_asset_types", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("capital_asset_types/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create { name, data } => {
                 let body = build_capital_asset_type_body(name.clone(), data.clone(), true)?;
                 let result = client.post("capital_asset_types", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update { id, name, data } => {
                 let body = build_capital_asset_type_body(name.clone(), data.clone(), false)?;
                 let result = client.put(&format!("capital_asset_types/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -122,7 +123,7 @@ impl CapitalAssetTypeCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("capital_asset_types/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
 
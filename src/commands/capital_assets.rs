@@ -4,8 +4,10 @@ use anyhow::Result;
 use clap::Subcommand;
 use serde_json::json;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder, ResourceKind};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Subcommand)]
 pub enum CapitalAssetCommands {
@@ -44,7 +46,7 @@ pub enum CapitalAssetCommands {
         #[arg(long)]
         purchase_price: Option<String>,
         
-        /// Category URL
+        /// Category nominal code, URL or name
         #[arg(long)]
         category: Option<String>,
     },
@@ -75,19 +77,18 @@ pub enum CapitalAssetCommands {
 }
 
 impl CapitalAssetCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { page, per_page } => {
                 let params = QueryBuilder::new()
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("capital_assets", params).await?;
-                print_output(&result, format);
+                print_list(client, "capital_assets", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("capital_assets/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 description,
@@ -107,12 +108,12 @@ impl CapitalAssetCommands {
                     asset.insert("purchase_price".to_string(), json!(v));
                 }
                 if let Some(v) = category {
-                    asset.insert("category".to_string(), json!(v));
+                    asset.insert("category".to_string(), json!(client.resolve(ResourceKind::Category, v).await?));
                 }
                 
                 let body = json!({ "capital_asset": asset });
                 let result = client.post("capital_assets", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update { id, description, asset_life_years } => {
                 let mut asset = serde_json::Map::new();
@@ -126,7 +127,7 @@ impl CapitalAssetCommands {
                 
                 let body = json!({ "capital_asset": asset });
                 let result = client.put(&format!("capital_assets/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -134,7 +135,7 @@ impl CapitalAssetCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("capital_assets/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
@@ -4,8 +4,10 @@ use anyhow::{bail, Result};
 use clap::Subcommand;
 use serde_json::Value;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 fn validate_category_group(group: &str) -> Result<()> {
     let allowed = ["income", "cost_of_sales", "admin_expenses"];
@@ -103,18 +105,17 @@ pub enum CategoryCommands {
 }
 
 impl CategoryCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { sub_accounts } => {
                 let params = QueryBuilder::new()
                     .add_bool("sub_accounts", if *sub_accounts { Some(true) } else { None })
                     .build();
-                let result = client.get("categories", params).await?;
-                print_output(&result, format);
+                print_list(client, "categories", params, options).await?;
             }
             Self::Get { nominal_code } => {
                 let result = client.get(&format!("categories/{}", nominal_code), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 description,
@@ -163,7 +164,7 @@ impl CategoryCommands {
                     }
                 };
                 let result = client.post("categories", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update {
                 nominal_code,
@@ -214,7 +215,7 @@ impl CategoryCommands {
                     }
                 };
                 let result = client.put(&format!("categories/{}", nominal_code), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { nominal_code, yes } => {
                 if !yes {
@@ -222,7 +223,7 @@ impl CategoryCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("categories/{}", nominal_code)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
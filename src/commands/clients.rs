@@ -3,8 +3,10 @@
 use anyhow::Result;
 use clap::Subcommand;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::OutputOptions;
+
+use super::print_list;
 
 #[derive(Debug, Subcommand)]
 pub enum ClientCommands {
@@ -21,15 +23,14 @@ pub enum ClientCommands {
 }
 
 impl ClientCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { page, per_page } => {
                 let params = QueryBuilder::new()
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("clients", params).await?;
-                print_output(&result, format);
+                print_list(client, "clients", params, options).await?;
             }
         }
 
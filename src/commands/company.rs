@@ -4,8 +4,8 @@ use anyhow::Result;
 use clap::Subcommand;
 use serde_json::json;
 
-use crate::api::FreeAgentClient;
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::FreeAgentClient;
+use freeagent::output::{print_output, OutputOptions};
 
 #[derive(Debug, Subcommand)]
 pub enum CompanyCommands {
@@ -44,11 +44,11 @@ pub enum CompanyCommands {
 }
 
 impl CompanyCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::Get => {
                 let result = client.get("company", None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update {
                 name,
@@ -81,11 +81,11 @@ impl CompanyCommands {
                 
                 let body = json!({ "company": company });
                 let result = client.put("company", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::TaxTimeline => {
                 let result = client.get("company/tax_timeline", None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
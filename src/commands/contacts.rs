@@ -4,8 +4,10 @@ use anyhow::Result;
 use clap::{Subcommand, ValueEnum};
 use serde_json::json;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Clone, ValueEnum)]
 pub enum ContactView {
@@ -190,7 +192,7 @@ pub enum ContactCommands {
 }
 
 impl ContactCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List {
                 view,
@@ -207,12 +209,11 @@ impl ContactCommands {
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("contacts", params).await?;
-                print_output(&result, format);
+                print_list(client, "contacts", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("contacts/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 first_name,
@@ -277,7 +278,7 @@ impl ContactCommands {
                 
                 let body = json!({ "contact": contact });
                 let result = client.post("contacts", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update {
                 id,
@@ -307,7 +308,7 @@ impl ContactCommands {
                 
                 let body = json!({ "contact": contact });
                 let result = client.put(&format!("contacts/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -315,7 +316,7 @@ impl ContactCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("contacts/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
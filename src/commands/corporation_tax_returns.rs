@@ -3,8 +3,10 @@
 use anyhow::Result;
 use clap::Subcommand;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Subcommand)]
 pub enum CorporationTaxReturnCommands {
@@ -51,21 +53,20 @@ pub enum CorporationTaxReturnCommands {
 }
 
 impl CorporationTaxReturnCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { page, per_page } => {
                 let params = QueryBuilder::new()
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("corporation_tax_returns", params).await?;
-                print_output(&result, format);
+                print_list(client, "corporation_tax_returns", params, options).await?;
             }
             Self::Get { period_ends_on } => {
                 let result = client
                     .get(&format!("corporation_tax_returns/{}", period_ends_on), None)
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkAsFiled { period_ends_on } => {
                 let result = client
@@ -77,7 +78,7 @@ impl CorporationTaxReturnCommands {
                         None::<()>,
                     )
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkAsUnfiled { period_ends_on } => {
                 let result = client
@@ -89,7 +90,7 @@ impl CorporationTaxReturnCommands {
                         None::<()>,
                     )
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkAsPaid { period_ends_on } => {
                 let result = client
@@ -98,7 +99,7 @@ impl CorporationTaxReturnCommands {
                         None::<()>,
                     )
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkAsUnpaid { period_ends_on } => {
                 let result = client
@@ -107,7 +108,7 @@ impl CorporationTaxReturnCommands {
                         None::<()>,
                     )
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
 
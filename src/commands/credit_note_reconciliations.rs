@@ -4,8 +4,10 @@ use anyhow::{bail, Result};
 use clap::Subcommand;
 use serde_json::Value;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 fn build_reconciliation_body(
     credit_note: Option<String>,
@@ -33,10 +35,8 @@ fn build_reconciliation_body(
             if !using_structured {
                 bail!("Provide structured fields or --data");
             }
-            if require_core {
-                if credit_note.is_none() || invoice.is_none() || gross_value.is_none() {
-                    bail!("Missing required fields: --credit-note, --invoice, --gross-value");
-                }
+            if require_core && (credit_note.is_none() || invoice.is_none() || gross_value.is_none()) {
+                bail!("Missing required fields: --credit-note, --invoice, --gross-value");
             }
             let mut reconciliation = serde_json::Map::new();
             if let Some(v) = credit_note {
@@ -159,7 +159,7 @@ pub enum CreditNoteReconciliationCommands {
 }
 
 impl CreditNoteReconciliationCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List {
                 from_date,
@@ -175,14 +175,13 @@ impl CreditNoteReconciliationCommands {
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("credit_note_reconciliations", params).await?;
-                print_output(&result, format);
+                print_list(client, "credit_note_reconciliations", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client
                     .get(&format!("credit_note_reconciliations/{}", id), None)
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 credit_note,
@@ -202,7 +201,7 @@ impl CreditNoteReconciliationCommands {
                     true,
                 )?;
                 let result = client.post("credit_note_reconciliations", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update {
                 id,
@@ -225,7 +224,7 @@ impl CreditNoteReconciliationCommands {
                 let result = client
                     .put(&format!("credit_note_reconciliations/{}", id), Some(body))
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -235,7 +234,7 @@ impl CreditNoteReconciliationCommands {
                 let result = client
                     .delete(&format!("credit_note_reconciliations/{}", id))
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
 
@@ -3,19 +3,24 @@
 use anyhow::Result;
 use clap::Subcommand;
 use serde_json::json;
+use std::path::PathBuf;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::download;
+use freeagent::api::upload::Attachment;
+use freeagent::api::{FreeAgentClient, QueryBuilder, ResourceKind};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Subcommand)]
 pub enum CreditNoteCommands {
     /// List all credit notes
     List {
-        /// Filter by contact URL
+        /// Filter by contact ID, URL or name
         #[arg(long)]
         contact: Option<String>,
         
-        /// Filter by project URL
+        /// Filter by project ID, URL or name
         #[arg(long)]
         project: Option<String>,
         
@@ -34,9 +39,19 @@ pub enum CreditNoteCommands {
         id: String,
     },
     
+    /// Get credit note PDF, optionally saving it to disk
+    Pdf {
+        /// Credit note ID
+        id: String,
+        
+        /// Write the decoded PDF to this file or directory instead of printing JSON
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    
     /// Create a new credit note
     Create {
-        /// Contact URL (required)
+        /// Contact ID, URL or name (required)
         #[arg(long)]
         contact: String,
         
@@ -55,6 +70,10 @@ pub enum CreditNoteCommands {
         /// Comments
         #[arg(long)]
         comments: Option<String>,
+        
+        /// File to attach (PDF, PNG, JPEG or GIF, up to 5 MB)
+        #[arg(long, value_name = "PATH")]
+        attach: Option<PathBuf>,
     },
     
     /// Update a credit note
@@ -73,6 +92,10 @@ pub enum CreditNoteCommands {
         /// Comments
         #[arg(long)]
         comments: Option<String>,
+        
+        /// File to attach (PDF, PNG, JPEG or GIF, up to 5 MB)
+        #[arg(long, value_name = "PATH")]
+        attach: Option<PathBuf>,
     },
     
     /// Delete a credit note
@@ -87,25 +110,35 @@ pub enum CreditNoteCommands {
 }
 
 impl CreditNoteCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { contact, project, page, per_page } => {
                 let params = QueryBuilder::new()
-                    .add("contact", contact.clone())
-                    .add("project", project.clone())
+                    .add("contact", client.resolve_opt(ResourceKind::Contact, contact.as_deref()).await?)
+                    .add("project", client.resolve_opt(ResourceKind::Project, project.as_deref()).await?)
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("credit_notes", params).await?;
-                print_output(&result, format);
+                print_list(client, "credit_notes", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("credit_notes/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
+            }
+            Self::Pdf { id, output } => {
+                let result = client.get(&format!("credit_notes/{}/pdf", id), None).await?;
+                match output {
+                    Some(output) => {
+                        let path = download::output_path(Some(output), &format!("credit-note-{}.pdf", id));
+                        let bytes = download::save_pdf(&result, &path)?;
+                        print_output(&json!({ "path": path, "bytes": bytes }), options)?;
+                    }
+                    None => print_output(&result, options)?,
+                }
             }
-            Self::Create { contact, dated_on, reference, currency, comments } => {
+            Self::Create { contact, dated_on, reference, currency, comments, attach } => {
                 let mut credit_note = serde_json::Map::new();
-                credit_note.insert("contact".to_string(), json!(contact));
+                credit_note.insert("contact".to_string(), json!(client.resolve(ResourceKind::Contact, contact).await?));
                 
                 if let Some(v) = dated_on {
                     credit_note.insert("dated_on".to_string(), json!(v));
@@ -119,12 +152,15 @@ impl CreditNoteCommands {
                 if let Some(v) = comments {
                     credit_note.insert("comments".to_string(), json!(v));
                 }
+                if let Some(path) = attach {
+                    credit_note.insert("attachment".to_string(), json!(Attachment::from_path(path)?));
+                }
                 
                 let body = json!({ "credit_note": credit_note });
                 let result = client.post("credit_notes", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
-            Self::Update { id, dated_on, reference, comments } => {
+            Self::Update { id, dated_on, reference, comments, attach } => {
                 let mut credit_note = serde_json::Map::new();
                 
                 if let Some(v) = dated_on {
@@ -136,10 +172,13 @@ impl CreditNoteCommands {
                 if let Some(v) = comments {
                     credit_note.insert("comments".to_string(), json!(v));
                 }
+                if let Some(path) = attach {
+                    credit_note.insert("attachment".to_string(), json!(Attachment::from_path(path)?));
+                }
                 
                 let body = json!({ "credit_note": credit_note });
                 let result = client.put(&format!("credit_notes/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -147,7 +186,7 @@ impl CreditNoteCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("credit_notes/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
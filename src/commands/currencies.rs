@@ -3,8 +3,10 @@
 use anyhow::Result;
 use clap::Subcommand;
 
-use crate::api::FreeAgentClient;
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::FreeAgentClient;
+use freeagent::output::OutputOptions;
+
+use super::print_list;
 
 #[derive(Debug, Subcommand)]
 pub enum CurrencyCommands {
@@ -13,11 +15,10 @@ pub enum CurrencyCommands {
 }
 
 impl CurrencyCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List => {
-                let result = client.get("currencies", None).await?;
-                print_output(&result, format);
+                print_list(client, "currencies", None, options).await?;
             }
         }
 
@@ -3,8 +3,8 @@
 use anyhow::Result;
 use clap::Subcommand;
 
-use crate::api::FreeAgentClient;
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::FreeAgentClient;
+use freeagent::output::{print_output, OutputOptions};
 
 #[derive(Debug, Subcommand)]
 pub enum EcMossCommands {
@@ -13,11 +13,11 @@ pub enum EcMossCommands {
 }
 
 impl EcMossCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::SalesTaxRates => {
                 let result = client.get("ec_moss/sales_tax_rates", None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
 
@@ -4,9 +4,10 @@ use anyhow::{bail, Result};
 use clap::Subcommand;
 use serde_json::Value;
 
-use crate::api::FreeAgentClient;
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, ResourceKind};
+use freeagent::output::{print_output, OutputOptions};
 
+#[allow(clippy::too_many_arguments)]
 fn build_estimate_item_body(
     estimate: Option<String>,
     item_type: Option<String>,
@@ -65,10 +66,10 @@ fn build_estimate_item_body(
                     bail!("--second-sales-tax-status must be one of: TAXABLE, EXEMPT, OUT_OF_SCOPE");
                 }
             }
-            if require_core {
-                if estimate.is_none() || item_type.is_none() || description.is_none() || price.is_none() {
-                    bail!("Missing required fields: --estimate, --item-type, --description, --price");
-                }
+            if require_core
+                && (estimate.is_none() || item_type.is_none() || description.is_none() || price.is_none())
+            {
+                bail!("Missing required fields: --estimate, --item-type, --description, --price");
             }
             let mut item = serde_json::Map::new();
             if let Some(v) = estimate {
@@ -160,7 +161,7 @@ pub enum EstimateItemCommands {
         #[arg(long)]
         second_sales_tax_status: Option<String>,
 
-        /// Category URL
+        /// Category nominal code, URL or name
         #[arg(long)]
         category: Option<String>,
 
@@ -218,7 +219,7 @@ pub enum EstimateItemCommands {
         #[arg(long)]
         second_sales_tax_status: Option<String>,
 
-        /// Category URL
+        /// Category nominal code, URL or name
         #[arg(long)]
         category: Option<String>,
 
@@ -239,7 +240,7 @@ pub enum EstimateItemCommands {
 }
 
 impl EstimateItemCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::Create {
                 estimate,
@@ -268,12 +269,12 @@ impl EstimateItemCommands {
                     second_sales_tax_rate.clone(),
                     second_sales_tax_value.clone(),
                     second_sales_tax_status.clone(),
-                    category.clone(),
+                    client.resolve_opt(ResourceKind::Category, category.as_deref()).await?,
                     data.clone(),
                     true,
                 )?;
                 let result = client.post("estimate_items", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update {
                 id,
@@ -303,12 +304,12 @@ impl EstimateItemCommands {
                     second_sales_tax_rate.clone(),
                     second_sales_tax_value.clone(),
                     second_sales_tax_status.clone(),
-                    category.clone(),
+                    client.resolve_opt(ResourceKind::Category, category.as_deref()).await?,
                     data.clone(),
                     false,
                 )?;
                 let result = client.put(&format!("estimate_items/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -316,7 +317,7 @@ impl EstimateItemCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("estimate_items/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
 
@@ -3,19 +3,23 @@
 use anyhow::Result;
 use clap::Subcommand;
 use serde_json::{json, Value};
+use std::path::PathBuf;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::download;
+use freeagent::api::{FreeAgentClient, QueryBuilder, ResourceKind};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Subcommand)]
 pub enum EstimateCommands {
     /// List all estimates
     List {
-        /// Filter by contact URL
+        /// Filter by contact ID, URL or name
         #[arg(long)]
         contact: Option<String>,
         
-        /// Filter by project URL
+        /// Filter by project ID, URL or name
         #[arg(long)]
         project: Option<String>,
         
@@ -34,9 +38,19 @@ pub enum EstimateCommands {
         id: String,
     },
     
+    /// Get estimate PDF, optionally saving it to disk
+    Pdf {
+        /// Estimate ID
+        id: String,
+        
+        /// Write the decoded PDF to this file or directory instead of printing JSON
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    
     /// Create a new estimate
     Create {
-        /// Contact URL (required)
+        /// Contact ID, URL or name (required)
         #[arg(long)]
         contact: String,
         
@@ -111,25 +125,35 @@ pub enum EstimateCommands {
 }
 
 impl EstimateCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { contact, project, page, per_page } => {
                 let params = QueryBuilder::new()
-                    .add("contact", contact.clone())
-                    .add("project", project.clone())
+                    .add("contact", client.resolve_opt(ResourceKind::Contact, contact.as_deref()).await?)
+                    .add("project", client.resolve_opt(ResourceKind::Project, project.as_deref()).await?)
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("estimates", params).await?;
-                print_output(&result, format);
+                print_list(client, "estimates", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("estimates/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
+            }
+            Self::Pdf { id, output } => {
+                let result = client.get(&format!("estimates/{}/pdf", id), None).await?;
+                match output {
+                    Some(output) => {
+                        let path = download::output_path(Some(output), &format!("estimate-{}.pdf", id));
+                        let bytes = download::save_pdf(&result, &path)?;
+                        print_output(&json!({ "path": path, "bytes": bytes }), options)?;
+                    }
+                    None => print_output(&result, options)?,
+                }
             }
             Self::Create { contact, dated_on, reference, currency, comments } => {
                 let mut estimate = serde_json::Map::new();
-                estimate.insert("contact".to_string(), json!(contact));
+                estimate.insert("contact".to_string(), json!(client.resolve(ResourceKind::Contact, contact).await?));
                 
                 if let Some(v) = dated_on {
                     estimate.insert("dated_on".to_string(), json!(v));
@@ -146,7 +170,7 @@ impl EstimateCommands {
                 
                 let body = json!({ "estimate": estimate });
                 let result = client.post("estimates", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update { id, dated_on, reference, comments } => {
                 let mut estimate = serde_json::Map::new();
@@ -163,7 +187,7 @@ impl EstimateCommands {
                 
                 let body = json!({ "estimate": estimate });
                 let result = client.put(&format!("estimates/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -171,12 +195,12 @@ impl EstimateCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("estimates/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::SendEmail { id, email_to } => {
                 let body = json!({ "email": { "to": email_to } });
                 let result = client.post(&format!("estimates/{}/send_email", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::UpdateDefaultAdditionalText { text, data } => {
                 let using_structured = text.is_some();
@@ -185,7 +209,7 @@ impl EstimateCommands {
                         if using_structured {
                             anyhow::bail!("Use either --text or --data, not both");
                         }
-                        serde_json::from_str(&raw)?
+                        serde_json::from_str(raw)?
                     }
                     None => {
                         if text.is_none() {
@@ -195,11 +219,11 @@ impl EstimateCommands {
                     }
                 };
                 let result = client.put("estimates/default_additional_text", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::DeleteDefaultAdditionalText => {
                 let result = client.delete("estimates/default_additional_text").await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
@@ -3,9 +3,13 @@
 use anyhow::Result;
 use clap::{Subcommand, ValueEnum};
 use serde_json::json;
+use std::path::PathBuf;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::upload::Attachment;
+use freeagent::api::{FreeAgentClient, QueryBuilder, ResourceKind};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Clone, ValueEnum)]
 pub enum ExpenseView {
@@ -32,11 +36,11 @@ pub enum ExpenseCommands {
         #[arg(long)]
         view: Option<ExpenseView>,
         
-        /// Filter by user URL
+        /// Filter by user ID, URL or name
         #[arg(long)]
         user: Option<String>,
         
-        /// Filter by project URL
+        /// Filter by project ID, URL or name
         #[arg(long)]
         project: Option<String>,
         
@@ -65,11 +69,11 @@ pub enum ExpenseCommands {
     
     /// Create a new expense
     Create {
-        /// User URL (required)
+        /// User ID, URL or name (required)
         #[arg(long)]
         user: String,
         
-        /// Category URL (required)
+        /// Category nominal code, URL or name (required)
         #[arg(long)]
         category: String,
         
@@ -85,7 +89,7 @@ pub enum ExpenseCommands {
         #[arg(long)]
         description: Option<String>,
         
-        /// Project URL
+        /// Project ID, URL or name
         #[arg(long)]
         project: Option<String>,
         
@@ -100,6 +104,10 @@ pub enum ExpenseCommands {
         /// Manual sales tax amount
         #[arg(long)]
         manual_sales_tax_amount: Option<String>,
+        
+        /// File to attach (PDF, PNG, JPEG or GIF, up to 5 MB)
+        #[arg(long, value_name = "PATH")]
+        attach: Option<PathBuf>,
     },
     
     /// Update an expense
@@ -118,6 +126,10 @@ pub enum ExpenseCommands {
         /// Description
         #[arg(long)]
         description: Option<String>,
+        
+        /// File to attach (PDF, PNG, JPEG or GIF, up to 5 MB)
+        #[arg(long, value_name = "PATH")]
+        attach: Option<PathBuf>,
     },
     
     /// Delete an expense
@@ -132,7 +144,7 @@ pub enum ExpenseCommands {
 }
 
 impl ExpenseCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List {
                 view,
@@ -145,19 +157,18 @@ impl ExpenseCommands {
             } => {
                 let params = QueryBuilder::new()
                     .add("view", view.as_ref().map(|v| v.as_str()))
-                    .add("user", user.clone())
-                    .add("project", project.clone())
+                    .add("user", client.resolve_opt(ResourceKind::User, user.as_deref()).await?)
+                    .add("project", client.resolve_opt(ResourceKind::Project, project.as_deref()).await?)
                     .add("from_date", from_date.clone())
                     .add("to_date", to_date.clone())
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("expenses", params).await?;
-                print_output(&result, format);
+                print_list(client, "expenses", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("expenses/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 user,
@@ -169,10 +180,11 @@ impl ExpenseCommands {
                 currency,
                 rebill_to_project,
                 manual_sales_tax_amount,
+                attach,
             } => {
                 let mut expense = serde_json::Map::new();
-                expense.insert("user".to_string(), json!(user));
-                expense.insert("category".to_string(), json!(category));
+                expense.insert("user".to_string(), json!(client.resolve(ResourceKind::User, user).await?));
+                expense.insert("category".to_string(), json!(client.resolve(ResourceKind::Category, category).await?));
                 expense.insert("dated_on".to_string(), json!(dated_on));
                 expense.insert("gross_value".to_string(), json!(gross_value));
                 
@@ -180,7 +192,7 @@ impl ExpenseCommands {
                     expense.insert("description".to_string(), json!(v));
                 }
                 if let Some(v) = project {
-                    expense.insert("project".to_string(), json!(v));
+                    expense.insert("project".to_string(), json!(client.resolve(ResourceKind::Project, v).await?));
                 }
                 if let Some(v) = currency {
                     expense.insert("currency".to_string(), json!(v));
@@ -191,12 +203,15 @@ impl ExpenseCommands {
                 if let Some(v) = manual_sales_tax_amount {
                     expense.insert("manual_sales_tax_amount".to_string(), json!(v));
                 }
+                if let Some(path) = attach {
+                    expense.insert("attachment".to_string(), json!(Attachment::from_path(path)?));
+                }
                 
                 let body = json!({ "expense": expense });
                 let result = client.post("expenses", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
-            Self::Update { id, dated_on, gross_value, description } => {
+            Self::Update { id, dated_on, gross_value, description, attach } => {
                 let mut expense = serde_json::Map::new();
                 
                 if let Some(v) = dated_on {
@@ -208,10 +223,13 @@ impl ExpenseCommands {
                 if let Some(v) = description {
                     expense.insert("description".to_string(), json!(v));
                 }
+                if let Some(path) = attach {
+                    expense.insert("attachment".to_string(), json!(Attachment::from_path(path)?));
+                }
                 
                 let body = json!({ "expense": expense });
                 let result = client.put(&format!("expenses/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -219,7 +237,7 @@ impl ExpenseCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("expenses/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
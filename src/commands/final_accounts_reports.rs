@@ -3,8 +3,10 @@
 use anyhow::Result;
 use clap::Subcommand;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Subcommand)]
 pub enum FinalAccountsReportCommands {
@@ -39,21 +41,20 @@ pub enum FinalAccountsReportCommands {
 }
 
 impl FinalAccountsReportCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { page, per_page } => {
                 let params = QueryBuilder::new()
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("final_accounts_reports", params).await?;
-                print_output(&result, format);
+                print_list(client, "final_accounts_reports", params, options).await?;
             }
             Self::Get { period_ends_on } => {
                 let result = client
                     .get(&format!("final_accounts_reports/{}", period_ends_on), None)
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkAsFiled { period_ends_on } => {
                 let result = client
@@ -62,7 +63,7 @@ impl FinalAccountsReportCommands {
                         None::<()>,
                     )
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkAsUnfiled { period_ends_on } => {
                 let result = client
@@ -71,7 +72,7 @@ impl FinalAccountsReportCommands {
                         None::<()>,
                     )
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
 
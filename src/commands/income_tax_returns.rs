@@ -3,8 +3,8 @@
 use anyhow::Result;
 use clap::Subcommand;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
 
 #[derive(Debug, Subcommand)]
 pub enum IncomeTaxReturnCommands {
@@ -75,7 +75,7 @@ pub enum IncomeTaxReturnCommands {
 }
 
 impl IncomeTaxReturnCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { user_id, page, per_page } => {
                 let params = QueryBuilder::new()
@@ -83,9 +83,9 @@ impl IncomeTaxReturnCommands {
                     .add_i32("per_page", *per_page)
                     .build();
                 let result = client
-                    .get(&format!("users/{}/self_assessment_returns", user_id), params)
+                    .list(&format!("users/{}/self_assessment_returns", user_id), params)
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Get { user_id, period_ends_on } => {
                 let result = client
@@ -97,7 +97,7 @@ impl IncomeTaxReturnCommands {
                         None,
                     )
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkAsFiled { user_id, period_ends_on } => {
                 let result = client
@@ -109,7 +109,7 @@ impl IncomeTaxReturnCommands {
                         None::<()>,
                     )
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkAsUnfiled { user_id, period_ends_on } => {
                 let result = client
@@ -121,7 +121,7 @@ impl IncomeTaxReturnCommands {
                         None::<()>,
                     )
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkPaymentAsPaid { user_id, period_ends_on, payment_date } => {
                 let result = client
@@ -133,7 +133,7 @@ impl IncomeTaxReturnCommands {
                         None::<()>,
                     )
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkPaymentAsUnpaid { user_id, period_ends_on, payment_date } => {
                 let result = client
@@ -145,7 +145,7 @@ impl IncomeTaxReturnCommands {
                         None::<()>,
                     )
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
 
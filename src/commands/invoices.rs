@@ -3,9 +3,13 @@
 use anyhow::Result;
 use clap::{Subcommand, ValueEnum};
 use serde_json::{json, Value};
+use std::path::PathBuf;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::download;
+use freeagent::api::{FreeAgentClient, QueryBuilder, ResourceKind};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Clone, ValueEnum)]
 pub enum InvoiceView {
@@ -57,6 +61,16 @@ impl EcStatus {
     }
 }
 
+/// File name for an archived invoice PDF: `<dated_on>_<reference>.pdf`,
+/// falling back to the invoice ID when there is no reference
+fn archive_file_name(invoice: &Value, id: &str) -> String {
+    let reference = invoice["reference"].as_str().unwrap_or(id);
+    match invoice["dated_on"].as_str() {
+        Some(dated_on) => format!("{}_{}.pdf", dated_on, reference),
+        None => format!("{}.pdf", reference),
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum InvoiceCommands {
     /// List all invoices
@@ -65,11 +79,11 @@ pub enum InvoiceCommands {
         #[arg(long)]
         view: Option<InvoiceView>,
         
-        /// Filter by contact URL
+        /// Filter by contact ID, URL or name
         #[arg(long)]
         contact: Option<String>,
         
-        /// Filter by project URL
+        /// Filter by project ID, URL or name
         #[arg(long)]
         project: Option<String>,
         
@@ -110,11 +124,11 @@ pub enum InvoiceCommands {
     
     /// Create a new invoice
     Create {
-        /// Contact URL (required)
+        /// Contact ID, URL or name (required)
         #[arg(long)]
         contact: String,
         
-        /// Project URL
+        /// Project ID, URL or name
         #[arg(long)]
         project: Option<String>,
         
@@ -183,10 +197,41 @@ pub enum InvoiceCommands {
         yes: bool,
     },
     
-    /// Get invoice PDF URL
+    /// Get invoice PDF, optionally saving it to disk
     Pdf {
         /// Invoice ID
         id: String,
+        
+        /// Write the decoded PDF to this file or directory instead of printing JSON
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    
+    /// Download PDFs for every invoice in a date range
+    Download {
+        /// Directory to write PDFs into
+        #[arg(long, value_name = "DIR")]
+        output: PathBuf,
+        
+        /// Filter from date (YYYY-MM-DD)
+        #[arg(long)]
+        from_date: Option<String>,
+        
+        /// Filter to date (YYYY-MM-DD)
+        #[arg(long)]
+        to_date: Option<String>,
+        
+        /// Filter by view
+        #[arg(long)]
+        view: Option<InvoiceView>,
+        
+        /// Filter by contact ID, URL or name
+        #[arg(long)]
+        contact: Option<String>,
+        
+        /// Skip invoices whose PDF already exists in the output directory
+        #[arg(long)]
+        skip_existing: bool,
     },
     
     /// Send invoice by email
@@ -247,7 +292,7 @@ pub enum InvoiceCommands {
 }
 
 impl InvoiceCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List {
                 view,
@@ -262,8 +307,8 @@ impl InvoiceCommands {
             } => {
                 let params = QueryBuilder::new()
                     .add("view", view.as_ref().map(|v| v.as_str()))
-                    .add("contact", contact.clone())
-                    .add("project", project.clone())
+                    .add("contact", client.resolve_opt(ResourceKind::Contact, contact.as_deref()).await?)
+                    .add("project", client.resolve_opt(ResourceKind::Project, project.as_deref()).await?)
                     .add("from_date", from_date.clone())
                     .add("to_date", to_date.clone())
                     .add("updated_since", updated_since.clone())
@@ -271,15 +316,14 @@ impl InvoiceCommands {
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("invoices", params).await?;
-                print_output(&result, format);
+                print_list(client, "invoices", params, options).await?;
             }
             Self::Get { id, nested_invoice_items } => {
                 let params = QueryBuilder::new()
                     .add_bool("nested_invoice_items", if *nested_invoice_items { Some(true) } else { None })
                     .build();
                 let result = client.get(&format!("invoices/{}", id), params).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 contact,
@@ -294,10 +338,10 @@ impl InvoiceCommands {
                 items_json,
             } => {
                 let mut invoice = serde_json::Map::new();
-                invoice.insert("contact".to_string(), json!(contact));
+                invoice.insert("contact".to_string(), json!(client.resolve(ResourceKind::Contact, contact).await?));
                 
                 if let Some(v) = project {
-                    invoice.insert("project".to_string(), json!(v));
+                    invoice.insert("project".to_string(), json!(client.resolve(ResourceKind::Project, v).await?));
                 }
                 if let Some(v) = dated_on {
                     invoice.insert("dated_on".to_string(), json!(v));
@@ -327,7 +371,7 @@ impl InvoiceCommands {
                 
                 let body = json!({ "invoice": invoice });
                 let result = client.post("invoices", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update {
                 id,
@@ -353,7 +397,7 @@ impl InvoiceCommands {
                 
                 let body = json!({ "invoice": invoice });
                 let result = client.put(&format!("invoices/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -361,11 +405,51 @@ impl InvoiceCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("invoices/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
-            Self::Pdf { id } => {
+            Self::Pdf { id, output } => {
                 let result = client.get(&format!("invoices/{}/pdf", id), None).await?;
-                print_output(&result, format);
+                match output {
+                    Some(output) => {
+                        let path = download::output_path(Some(output), &format!("invoice-{}.pdf", id));
+                        let bytes = download::save_pdf(&result, &path)?;
+                        print_output(&json!({ "path": path, "bytes": bytes }), options)?;
+                    }
+                    None => print_output(&result, options)?,
+                }
+            }
+            Self::Download {
+                output,
+                from_date,
+                to_date,
+                view,
+                contact,
+                skip_existing,
+            } => {
+                let params = QueryBuilder::new()
+                    .add("view", view.as_ref().map(|v| v.as_str()))
+                    .add("contact", client.resolve_opt(ResourceKind::Contact, contact.as_deref()).await?)
+                    .add("from_date", from_date.clone())
+                    .add("to_date", to_date.clone())
+                    .build();
+                let invoices = client.list_all("invoices", params).await?;
+
+                let mut downloaded = Vec::new();
+                for invoice in invoices["invoices"].as_array().into_iter().flatten() {
+                    let Some(url) = invoice["url"].as_str() else {
+                        continue;
+                    };
+                    let id = url.rsplit('/').next().unwrap_or_default();
+                    let path = output.join(download::sanitize_file_name(&archive_file_name(invoice, id)));
+                    if *skip_existing && path.exists() {
+                        downloaded.push(json!({ "invoice": url, "path": path, "skipped": true }));
+                        continue;
+                    }
+                    let pdf = client.get(&format!("invoices/{}/pdf", id), None).await?;
+                    let bytes = download::save_pdf(&pdf, &path)?;
+                    downloaded.push(json!({ "invoice": url, "path": path, "bytes": bytes }));
+                }
+                print_output(&json!({ "downloaded": downloaded }), options)?;
             }
             Self::SendEmail { id, email_to, subject, body } => {
                 let mut email = serde_json::Map::new();
@@ -376,23 +460,23 @@ impl InvoiceCommands {
                 }));
                 
                 let result = client.post(&format!("invoices/{}/send_email", id), Some(json!(email))).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkAsSent { id } => {
                 let result = client.put(&format!("invoices/{}/transitions/mark_as_sent", id), None::<()>).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkAsDraft { id } => {
                 let result = client.put(&format!("invoices/{}/transitions/mark_as_draft", id), None::<()>).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkAsCancelled { id } => {
                 let result = client.put(&format!("invoices/{}/transitions/mark_as_cancelled", id), None::<()>).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkAsScheduled { id } => {
                 let result = client.put(&format!("invoices/{}/transitions/mark_as_scheduled", id), None::<()>).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::UpdateDefaultAdditionalText { text, data } => {
                 let using_structured = text.is_some();
@@ -401,7 +485,7 @@ impl InvoiceCommands {
                         if using_structured {
                             anyhow::bail!("Use either --text or --data, not both");
                         }
-                        serde_json::from_str(&raw)?
+                        serde_json::from_str(raw)?
                     }
                     None => {
                         if text.is_none() {
@@ -411,11 +495,11 @@ impl InvoiceCommands {
                     }
                 };
                 let result = client.put("invoices/default_additional_text", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::DeleteDefaultAdditionalText => {
                 let result = client.delete("invoices/default_additional_text").await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
@@ -4,8 +4,10 @@ use anyhow::{bail, Result};
 use clap::Subcommand;
 use serde_json::Value;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 fn build_journal_set_body(
     description: Option<String>,
@@ -152,7 +154,7 @@ pub enum JournalSetCommands {
 }
 
 impl JournalSetCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List {
                 from_date,
@@ -170,16 +172,15 @@ impl JournalSetCommands {
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("journal_sets", params).await?;
-                print_output(&result, format);
+                print_list(client, "journal_sets", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("journal_sets/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::OpeningBalances => {
                 let result = client.get("journal_sets/opening_balances", None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 description,
@@ -197,7 +198,7 @@ impl JournalSetCommands {
                     true,
                 )?;
                 let result = client.post("journal_sets", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update {
                 id,
@@ -216,7 +217,7 @@ impl JournalSetCommands {
                     false,
                 )?;
                 let result = client.put(&format!("journal_sets/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -224,7 +225,7 @@ impl JournalSetCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("journal_sets/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
 
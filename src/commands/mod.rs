@@ -1,10 +1,12 @@
 //! CLI command modules.
 
 pub mod auth;
+pub mod cache;
 pub mod company;
 pub mod users;
 pub mod contacts;
 pub mod projects;
+pub mod raw;
 pub mod tasks;
 pub mod invoices;
 pub mod bills;
@@ -25,6 +27,7 @@ pub mod stock_items;
 pub mod account_managers;
 pub mod clients;
 pub mod practice;
+pub mod profiles;
 pub mod bank_feeds;
 pub mod bank_transaction_explanations;
 pub mod corporation_tax_returns;
@@ -45,3 +48,26 @@ pub mod properties;
 pub mod sales_tax_periods;
 pub mod capital_asset_types;
 pub mod cis_bands;
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+use freeagent::api::FreeAgentClient;
+use freeagent::output::{print_output, OutputOptions};
+
+/// Fetch and print a list endpoint, printing each page as it arrives when
+/// the output allows streaming
+pub async fn print_list(
+    client: &FreeAgentClient,
+    endpoint: &str,
+    params: Option<HashMap<String, String>>,
+    options: &OutputOptions,
+) -> Result<()> {
+    if options.streams_lists() {
+        return client
+            .list_pages(endpoint, params, |page| print_output(&page, options))
+            .await;
+    }
+    let result = client.list(endpoint, params).await?;
+    print_output(&result, options)
+}
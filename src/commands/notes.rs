@@ -4,8 +4,10 @@ use anyhow::Result;
 use clap::Subcommand;
 use serde_json::json;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Subcommand)]
 pub enum NoteCommands {
@@ -63,7 +65,7 @@ pub enum NoteCommands {
 }
 
 impl NoteCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { parent, page, per_page } => {
                 let params = QueryBuilder::new()
@@ -71,12 +73,11 @@ impl NoteCommands {
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("notes", params).await?;
-                print_output(&result, format);
+                print_list(client, "notes", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("notes/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create { parent, text } => {
                 let body = json!({
@@ -86,7 +87,7 @@ impl NoteCommands {
                     }
                 });
                 let result = client.post("notes", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update { id, text } => {
                 let body = json!({
@@ -95,7 +96,7 @@ impl NoteCommands {
                     }
                 });
                 let result = client.put(&format!("notes/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -103,7 +104,7 @@ impl NoteCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("notes/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
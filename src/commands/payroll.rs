@@ -3,8 +3,8 @@
 use anyhow::Result;
 use clap::Subcommand;
 
-use crate::api::FreeAgentClient;
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::FreeAgentClient;
+use freeagent::output::{print_output, OutputOptions};
 
 #[derive(Debug, Subcommand)]
 pub enum PayrollCommands {
@@ -43,15 +43,15 @@ pub enum PayrollCommands {
 }
 
 impl PayrollCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::ListYear { year } => {
                 let result = client.get(&format!("payroll/{}", year), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::GetPeriod { year, period } => {
                 let result = client.get(&format!("payroll/{}/{}", year, period), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkPaymentAsPaid { year, payment_date } => {
                 let result = client
@@ -60,7 +60,7 @@ impl PayrollCommands {
                         None::<()>,
                     )
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkPaymentAsUnpaid { year, payment_date } => {
                 let result = client
@@ -69,7 +69,7 @@ impl PayrollCommands {
                         None::<()>,
                     )
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
 
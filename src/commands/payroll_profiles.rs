@@ -3,8 +3,8 @@
 use anyhow::Result;
 use clap::Subcommand;
 
-use crate::api::FreeAgentClient;
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::FreeAgentClient;
+use freeagent::output::{print_output, OutputOptions};
 
 #[derive(Debug, Subcommand)]
 pub enum PayrollProfileCommands {
@@ -16,11 +16,11 @@ pub enum PayrollProfileCommands {
 }
 
 impl PayrollProfileCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::ListYear { year } => {
                 let result = client.get(&format!("payroll_profiles/{}", year), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
 
@@ -4,9 +4,12 @@ use anyhow::{bail, Result};
 use clap::Subcommand;
 use serde_json::Value;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder, ResourceKind};
+use freeagent::output::{print_output, OutputOptions};
 
+use super::print_list;
+
+#[allow(clippy::too_many_arguments)]
 fn build_price_list_item_body(
     code: Option<String>,
     item_type: Option<String>,
@@ -156,7 +159,7 @@ pub enum PriceListItemCommands {
         #[arg(long)]
         second_sales_tax_rate: Option<String>,
 
-        /// Category URL
+        /// Category nominal code, URL or name
         #[arg(long)]
         category: Option<String>,
 
@@ -206,7 +209,7 @@ pub enum PriceListItemCommands {
         #[arg(long)]
         second_sales_tax_rate: Option<String>,
 
-        /// Category URL
+        /// Category nominal code, URL or name
         #[arg(long)]
         category: Option<String>,
 
@@ -231,7 +234,7 @@ pub enum PriceListItemCommands {
 }
 
 impl PriceListItemCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { sort, page, per_page } => {
                 let params = QueryBuilder::new()
@@ -239,12 +242,11 @@ impl PriceListItemCommands {
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("price_list_items", params).await?;
-                print_output(&result, format);
+                print_list(client, "price_list_items", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("price_list_items/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 code,
@@ -268,13 +270,13 @@ impl PriceListItemCommands {
                     vat_status.clone(),
                     sales_tax_rate.clone(),
                     second_sales_tax_rate.clone(),
-                    category.clone(),
+                    client.resolve_opt(ResourceKind::Category, category.as_deref()).await?,
                     stock_item.clone(),
                     data.clone(),
                     true,
                 )?;
                 let result = client.post("price_list_items", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update {
                 id,
@@ -299,13 +301,13 @@ impl PriceListItemCommands {
                     vat_status.clone(),
                     sales_tax_rate.clone(),
                     second_sales_tax_rate.clone(),
-                    category.clone(),
+                    client.resolve_opt(ResourceKind::Category, category.as_deref()).await?,
                     stock_item.clone(),
                     data.clone(),
                     false,
                 )?;
                 let result = client.put(&format!("price_list_items/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -313,7 +315,7 @@ impl PriceListItemCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("price_list_item/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
 
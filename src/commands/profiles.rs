@@ -0,0 +1,104 @@
+//! Profile commands.
+
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use serde_json::json;
+use std::fs;
+
+use freeagent::api::ResponseCache;
+use freeagent::auth::profile::{self, Config, DEFAULT_PROFILE};
+use freeagent::auth::token::TokenStorage;
+use freeagent::output::{print_output, print_success, OutputOptions};
+
+#[derive(Debug, Subcommand)]
+pub enum ProfileCommands {
+    /// List profiles with their environment and company
+    List,
+
+    /// Make a profile the default for future commands
+    Use {
+        /// Profile name
+        name: String,
+    },
+
+    /// Delete a profile's tokens, cache and settings
+    Remove {
+        /// Profile name
+        name: String,
+
+        /// Skip confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+}
+
+impl ProfileCommands {
+    /// `current` is the profile selected for this invocation
+    pub fn execute(&self, current: &str, options: &OutputOptions) -> Result<()> {
+        let mut config = Config::load()?;
+
+        match self {
+            Self::List => {
+                let mut names = profile::known_profiles(&config)?;
+                if !names.iter().any(|name| name == current) {
+                    names.push(current.to_string());
+                    names.sort();
+                }
+                let mut profiles = Vec::new();
+                for name in names {
+                    let settings = config.settings(&name);
+                    // Checked without loading so encrypted tokens need no passphrase
+                    let logged_in = TokenStorage::for_profile(&name)?.has_tokens()?;
+                    profiles.push(json!({
+                        "name": name,
+                        "active": name == current,
+                        "logged_in": logged_in,
+                        "sandbox": settings.sandbox,
+                        "company": settings.company,
+                    }));
+                }
+                print_output(&json!({ "profiles": profiles }), options)?;
+            }
+            Self::Use { name } => {
+                profile::validate_name(name)?;
+                config.active_profile = (name != DEFAULT_PROFILE).then(|| name.clone());
+                config.save()?;
+                print_success(&format!("Now using profile '{}'", name));
+            }
+            Self::Remove { name, yes } => {
+                profile::validate_name(name)?;
+                if !config.profiles.contains_key(name)
+                    && !profile::known_profiles(&config)?.contains(name)
+                {
+                    bail!("No profile named '{}'", name);
+                }
+                if !yes {
+                    eprintln!("Use --yes to confirm deletion");
+                    return Ok(());
+                }
+
+                TokenStorage::for_profile(name)?.delete()?;
+                let cache_dir = ResponseCache::profile_dir(name)?;
+                if cache_dir.exists() {
+                    fs::remove_dir_all(&cache_dir).with_context(|| {
+                        format!("Failed to delete cache directory {}", cache_dir.display())
+                    })?;
+                }
+                if name != DEFAULT_PROFILE {
+                    // Only remove the directory once it is empty so nothing
+                    // unexpected is deleted along with it
+                    let _ = fs::remove_dir(profile::profile_dir(name)?);
+                }
+
+                config.profiles.remove(name);
+                if config.active() == name {
+                    config.active_profile = None;
+                }
+                config.save()?;
+                print_success(&format!("Removed profile '{}'", name));
+            }
+        }
+
+        Ok(())
+    }
+}
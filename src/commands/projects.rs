@@ -4,8 +4,10 @@ use anyhow::Result;
 use clap::{Subcommand, ValueEnum};
 use serde_json::json;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder, ResourceKind};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Clone, ValueEnum)]
 pub enum ProjectView {
@@ -53,7 +55,7 @@ pub enum ProjectCommands {
         #[arg(long)]
         view: Option<ProjectView>,
         
-        /// Filter by contact URL
+        /// Filter by contact ID, URL or name
         #[arg(long)]
         contact: Option<String>,
         
@@ -78,7 +80,7 @@ pub enum ProjectCommands {
     
     /// Create a new project
     Create {
-        /// Contact URL (required)
+        /// Contact ID, URL or name (required)
         #[arg(long)]
         contact: String,
         
@@ -149,7 +151,7 @@ pub enum ProjectCommands {
 }
 
 impl ProjectCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List {
                 view,
@@ -160,17 +162,16 @@ impl ProjectCommands {
             } => {
                 let params = QueryBuilder::new()
                     .add("view", view.as_ref().map(|v| v.as_str()))
-                    .add("contact", contact.clone())
+                    .add("contact", client.resolve_opt(ResourceKind::Contact, contact.as_deref()).await?)
                     .add("updated_since", updated_since.clone())
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("projects", params).await?;
-                print_output(&result, format);
+                print_list(client, "projects", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("projects/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 contact,
@@ -182,7 +183,7 @@ impl ProjectCommands {
                 hours_per_day,
             } => {
                 let mut project = serde_json::Map::new();
-                project.insert("contact".to_string(), json!(contact));
+                project.insert("contact".to_string(), json!(client.resolve(ResourceKind::Contact, contact).await?));
                 project.insert("name".to_string(), json!(name));
                 
                 if let Some(v) = budget {
@@ -203,7 +204,7 @@ impl ProjectCommands {
                 
                 let body = json!({ "project": project });
                 let result = client.post("projects", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update { id, name, budget, status } => {
                 let mut project = serde_json::Map::new();
@@ -220,7 +221,7 @@ impl ProjectCommands {
                 
                 let body = json!({ "project": project });
                 let result = client.put(&format!("projects/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -228,15 +229,15 @@ impl ProjectCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("projects/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Invoices { id } => {
                 let result = client.get(&format!("projects/{}/invoices", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Timeline { id } => {
                 let result = client.get(&format!("projects/{}/timeline", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
@@ -4,9 +4,12 @@ use anyhow::{bail, Result};
 use clap::Subcommand;
 use serde_json::Value;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
 
+use super::print_list;
+
+#[allow(clippy::too_many_arguments)]
 fn build_property_body(
     address1: Option<String>,
     address2: Option<String>,
@@ -157,19 +160,18 @@ pub enum PropertyCommands {
 }
 
 impl PropertyCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { page, per_page } => {
                 let params = QueryBuilder::new()
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("properties", params).await?;
-                print_output(&result, format);
+                print_list(client, "properties", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("properties/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 address1,
@@ -191,7 +193,7 @@ impl PropertyCommands {
                     true,
                 )?;
                 let result = client.post("properties", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update {
                 id,
@@ -214,7 +216,7 @@ impl PropertyCommands {
                     false,
                 )?;
                 let result = client.put(&format!("properties/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -222,7 +224,7 @@ impl PropertyCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("properties/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
 
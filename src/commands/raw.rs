@@ -0,0 +1,125 @@
+//! Raw API passthrough command.
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use reqwest::Method;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Read;
+
+use freeagent::api::FreeAgentClient;
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
+
+fn parse_method(raw: &str) -> Result<Method, String> {
+    match raw.to_ascii_uppercase().as_str() {
+        "GET" => Ok(Method::GET),
+        "POST" => Ok(Method::POST),
+        "PUT" => Ok(Method::PUT),
+        "PATCH" => Ok(Method::PATCH),
+        "DELETE" => Ok(Method::DELETE),
+        other => Err(format!(
+            "unsupported method '{}' (expected GET, POST, PUT, PATCH or DELETE)",
+            other
+        )),
+    }
+}
+
+fn parse_param(raw: &str) -> Result<(String, String), String> {
+    match raw.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got '{}'", raw)),
+    }
+}
+
+/// Read a `--data` value: inline JSON, `@path` for a file, or `-` for stdin
+fn read_data(raw: &str) -> Result<Value> {
+    let text = if raw == "-" {
+        let mut buf = String::new();
+        std::io::stdin()
+            .read_to_string(&mut buf)
+            .context("Failed to read request body from stdin")?;
+        buf
+    } else if let Some(path) = raw.strip_prefix('@') {
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?
+    } else {
+        raw.to_string()
+    };
+    serde_json::from_str(&text).context("--data must be valid JSON")
+}
+
+#[derive(Debug, Args)]
+pub struct ApiArgs {
+    /// HTTP method: GET, POST, PUT, PATCH or DELETE
+    #[arg(value_parser = parse_method)]
+    method: Method,
+
+    /// Endpoint path relative to the API base URL (e.g. bills/123), or a full URL on the API host
+    path: String,
+
+    /// Query parameter as KEY=VALUE (repeatable)
+    #[arg(long = "param", value_name = "KEY=VALUE", value_parser = parse_param)]
+    params: Vec<(String, String)>,
+
+    /// JSON request body: inline JSON, @file, or - to read stdin
+    #[arg(long)]
+    data: Option<String>,
+}
+
+impl ApiArgs {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
+        let params: HashMap<String, String> = self.params.iter().cloned().collect();
+        let params = if params.is_empty() { None } else { Some(params) };
+        let body = self.data.as_deref().map(read_data).transpose()?;
+
+        if self.method == Method::GET {
+            if body.is_some() {
+                bail!("--data cannot be used with GET");
+            }
+            return print_list(client, &self.path, params, options).await;
+        }
+        let result = client
+            .request(self.method.clone(), &self.path, params, body)
+            .await?;
+        print_output(&result, options)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_methods_case_insensitively() {
+        assert_eq!(parse_method("get"), Ok(Method::GET));
+        assert_eq!(parse_method("Patch"), Ok(Method::PATCH));
+        assert!(parse_method("TRACE").is_err());
+    }
+
+    #[test]
+    fn parses_params_with_equals_in_value() {
+        assert_eq!(
+            parse_param("q=a=b"),
+            Ok(("q".to_string(), "a=b".to_string()))
+        );
+        assert!(parse_param("novalue").is_err());
+        assert!(parse_param("=x").is_err());
+    }
+
+    #[test]
+    fn reads_inline_and_file_data() -> Result<()> {
+        assert_eq!(read_data(r#"{"a":1}"#)?, serde_json::json!({"a": 1}));
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("body.json");
+        std::fs::write(&path, r#"{"bill_item": {}}"#)?;
+        let value = read_data(&format!("@{}", path.display()))?;
+        assert_eq!(value, serde_json::json!({"bill_item": {}}));
+
+        assert!(read_data("not json").is_err());
+        Ok(())
+    }
+}
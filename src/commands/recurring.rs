@@ -4,14 +4,16 @@ use anyhow::Result;
 use clap::Subcommand;
 use serde_json::json;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder, ResourceKind};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Subcommand)]
 pub enum RecurringInvoiceCommands {
     /// List all recurring invoices
     List {
-        /// Filter by contact URL
+        /// Filter by contact ID, URL or name
         #[arg(long)]
         contact: Option<String>,
         
@@ -32,7 +34,7 @@ pub enum RecurringInvoiceCommands {
     
     /// Create a new recurring invoice
     Create {
-        /// Contact URL (required)
+        /// Contact ID, URL or name (required)
         #[arg(long)]
         contact: String,
         
@@ -87,20 +89,19 @@ pub enum RecurringInvoiceCommands {
 }
 
 impl RecurringInvoiceCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { contact, page, per_page } => {
                 let params = QueryBuilder::new()
-                    .add("contact", contact.clone())
+                    .add("contact", client.resolve_opt(ResourceKind::Contact, contact.as_deref()).await?)
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("recurring_invoices", params).await?;
-                print_output(&result, format);
+                print_list(client, "recurring_invoices", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("recurring_invoices/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 contact,
@@ -111,7 +112,7 @@ impl RecurringInvoiceCommands {
                 currency,
             } => {
                 let mut recurring = serde_json::Map::new();
-                recurring.insert("contact".to_string(), json!(contact));
+                recurring.insert("contact".to_string(), json!(client.resolve(ResourceKind::Contact, contact).await?));
                 
                 if let Some(v) = recurring_profile_name {
                     recurring.insert("recurring_profile_name".to_string(), json!(v));
@@ -131,7 +132,7 @@ impl RecurringInvoiceCommands {
                 
                 let body = json!({ "recurring_invoice": recurring });
                 let result = client.post("recurring_invoices", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update { id, recurring_profile_name, frequency_period, frequency } => {
                 let mut recurring = serde_json::Map::new();
@@ -148,7 +149,7 @@ impl RecurringInvoiceCommands {
                 
                 let body = json!({ "recurring_invoice": recurring });
                 let result = client.put(&format!("recurring_invoices/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -156,7 +157,7 @@ impl RecurringInvoiceCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("recurring_invoices/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
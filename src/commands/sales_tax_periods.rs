@@ -4,9 +4,12 @@ use anyhow::{bail, Result};
 use clap::Subcommand;
 use serde_json::Value;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
 
+use super::print_list;
+
+#[allow(clippy::too_many_arguments)]
 fn build_sales_tax_period_body(
     sales_tax_name: Option<String>,
     sales_tax_registration_status: Option<String>,
@@ -263,19 +266,18 @@ pub enum SalesTaxPeriodCommands {
 }
 
 impl SalesTaxPeriodCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { page, per_page } => {
                 let params = QueryBuilder::new()
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("sales_tax_periods", params).await?;
-                print_output(&result, format);
+                print_list(client, "sales_tax_periods", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("sales_tax_periods/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 sales_tax_name,
@@ -311,7 +313,7 @@ impl SalesTaxPeriodCommands {
                     true,
                 )?;
                 let result = client.post("sales_tax_periods", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update {
                 id,
@@ -348,7 +350,7 @@ impl SalesTaxPeriodCommands {
                     false,
                 )?;
                 let result = client.put(&format!("sales_tax_periods/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -356,7 +358,7 @@ impl SalesTaxPeriodCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("sales_tax_periods/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
 
@@ -4,8 +4,10 @@ use anyhow::Result;
 use clap::Subcommand;
 use serde_json::json;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Subcommand)]
 pub enum StockItemCommands {
@@ -71,19 +73,18 @@ pub enum StockItemCommands {
 }
 
 impl StockItemCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { page, per_page } => {
                 let params = QueryBuilder::new()
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("stock_items", params).await?;
-                print_output(&result, format);
+                print_list(client, "stock_items", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("stock_items/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create { description, opening_quantity, opening_balance, code } => {
                 let mut item = serde_json::Map::new();
@@ -101,7 +102,7 @@ impl StockItemCommands {
                 
                 let body = json!({ "stock_item": item });
                 let result = client.post("stock_items", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update { id, description, code } => {
                 let mut item = serde_json::Map::new();
@@ -115,7 +116,7 @@ impl StockItemCommands {
                 
                 let body = json!({ "stock_item": item });
                 let result = client.put(&format!("stock_items/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -123,7 +124,7 @@ impl StockItemCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("stock_items/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
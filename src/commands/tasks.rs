@@ -4,14 +4,16 @@ use anyhow::Result;
 use clap::Subcommand;
 use serde_json::json;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder, ResourceKind};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Subcommand)]
 pub enum TaskCommands {
     /// List all tasks
     List {
-        /// Filter by project URL
+        /// Filter by project ID, URL or name
         #[arg(long)]
         project: Option<String>,
         
@@ -32,7 +34,7 @@ pub enum TaskCommands {
     
     /// Create a new task
     Create {
-        /// Project URL (required)
+        /// Project ID, URL or name (required)
         #[arg(long)]
         project: String,
         
@@ -83,20 +85,19 @@ pub enum TaskCommands {
 }
 
 impl TaskCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { project, page, per_page } => {
                 let params = QueryBuilder::new()
-                    .add("project", project.clone())
+                    .add("project", client.resolve_opt(ResourceKind::Project, project.as_deref()).await?)
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("tasks", params).await?;
-                print_output(&result, format);
+                print_list(client, "tasks", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("tasks/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 project,
@@ -106,7 +107,7 @@ impl TaskCommands {
                 billing_period,
             } => {
                 let mut task = serde_json::Map::new();
-                task.insert("project".to_string(), json!(project));
+                task.insert("project".to_string(), json!(client.resolve(ResourceKind::Project, project).await?));
                 task.insert("name".to_string(), json!(name));
                 
                 if let Some(v) = billable {
@@ -121,7 +122,7 @@ impl TaskCommands {
                 
                 let body = json!({ "task": task });
                 let result = client.post("tasks", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update { id, name, billable, billing_rate } => {
                 let mut task = serde_json::Map::new();
@@ -138,7 +139,7 @@ impl TaskCommands {
                 
                 let body = json!({ "task": task });
                 let result = client.put(&format!("tasks/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -146,7 +147,7 @@ impl TaskCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("tasks/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
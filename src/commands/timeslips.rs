@@ -4,22 +4,24 @@ use anyhow::Result;
 use clap::Subcommand;
 use serde_json::json;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder, ResourceKind};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Subcommand)]
 pub enum TimeslipCommands {
     /// List all timeslips
     List {
-        /// Filter by user URL
+        /// Filter by user ID, URL or name
         #[arg(long)]
         user: Option<String>,
         
-        /// Filter by project URL
+        /// Filter by project ID, URL or name
         #[arg(long)]
         project: Option<String>,
         
-        /// Filter by task URL
+        /// Filter by task ID, URL or name
         #[arg(long)]
         task: Option<String>,
         
@@ -48,15 +50,15 @@ pub enum TimeslipCommands {
     
     /// Create a new timeslip
     Create {
-        /// User URL (required)
+        /// User ID, URL or name (required)
         #[arg(long)]
         user: String,
         
-        /// Project URL (required)
+        /// Project ID, URL or name (required)
         #[arg(long)]
         project: String,
         
-        /// Task URL (required)
+        /// Task ID, URL or name (required)
         #[arg(long)]
         task: String,
         
@@ -119,7 +121,7 @@ pub enum TimeslipCommands {
 }
 
 impl TimeslipCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List {
                 user,
@@ -130,21 +132,25 @@ impl TimeslipCommands {
                 page,
                 per_page,
             } => {
+                let project = client.resolve_opt(ResourceKind::Project, project.as_deref()).await?;
+                let task = match task {
+                    Some(task) => Some(client.resolve_task(task, project.as_deref()).await?),
+                    None => None,
+                };
                 let params = QueryBuilder::new()
-                    .add("user", user.clone())
-                    .add("project", project.clone())
-                    .add("task", task.clone())
+                    .add("user", client.resolve_opt(ResourceKind::User, user.as_deref()).await?)
+                    .add("project", project)
+                    .add("task", task)
                     .add("from_date", from_date.clone())
                     .add("to_date", to_date.clone())
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("timeslips", params).await?;
-                print_output(&result, format);
+                print_list(client, "timeslips", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("timeslips/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 user,
@@ -156,7 +162,9 @@ impl TimeslipCommands {
                 comment,
             } => {
                 let mut timeslip = serde_json::Map::new();
-                timeslip.insert("user".to_string(), json!(user));
+                timeslip.insert("user".to_string(), json!(client.resolve(ResourceKind::User, user).await?));
+                let project = client.resolve(ResourceKind::Project, project).await?;
+                let task = client.resolve_task(task, Some(&project)).await?;
                 timeslip.insert("project".to_string(), json!(project));
                 timeslip.insert("task".to_string(), json!(task));
                 timeslip.insert("dated_on".to_string(), json!(dated_on));
@@ -173,7 +181,7 @@ impl TimeslipCommands {
                 
                 let body = json!({ "timeslip": timeslip });
                 let result = client.post("timeslips", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update { id, dated_on, hours, comment } => {
                 let mut timeslip = serde_json::Map::new();
@@ -190,7 +198,7 @@ impl TimeslipCommands {
                 
                 let body = json!({ "timeslip": timeslip });
                 let result = client.put(&format!("timeslips/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -198,15 +206,15 @@ impl TimeslipCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("timeslips/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::StartTimer { id } => {
                 let result = client.put(&format!("timeslips/{}/timer", id), None::<()>).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::StopTimer { id } => {
                 let result = client.delete(&format!("timeslips/{}/timer", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
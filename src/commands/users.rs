@@ -4,8 +4,10 @@ use anyhow::Result;
 use clap::Subcommand;
 use serde_json::json;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Subcommand)]
 pub enum UserCommands {
@@ -121,23 +123,22 @@ pub enum UserCommands {
 }
 
 impl UserCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { page, per_page } => {
                 let params = QueryBuilder::new()
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("users", params).await?;
-                print_output(&result, format);
+                print_list(client, "users", params, options).await?;
             }
             Self::Me => {
                 let result = client.get("users/me", None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("users/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Create {
                 first_name,
@@ -164,7 +165,7 @@ impl UserCommands {
                 
                 let body = json!({ "user": user });
                 let result = client.post("users", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Update {
                 id,
@@ -194,7 +195,7 @@ impl UserCommands {
                 
                 let body = json!({ "user": user });
                 let result = client.put(&format!("users/{}", id), Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::Delete { id, yes } => {
                 if !yes {
@@ -202,7 +203,7 @@ impl UserCommands {
                     return Ok(());
                 }
                 let result = client.delete(&format!("users/{}", id)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::UpdateMe {
                 first_name,
@@ -235,7 +236,7 @@ impl UserCommands {
 
                 let body = json!({ "user": user });
                 let result = client.put("users/me", Some(body)).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
@@ -3,8 +3,10 @@
 use anyhow::Result;
 use clap::Subcommand;
 
-use crate::api::{FreeAgentClient, QueryBuilder};
-use crate::output::{print_output, OutputFormat};
+use freeagent::api::{FreeAgentClient, QueryBuilder};
+use freeagent::output::{print_output, OutputOptions};
+
+use super::print_list;
 
 #[derive(Debug, Subcommand)]
 pub enum VatCommands {
@@ -57,27 +59,26 @@ pub enum VatCommands {
 }
 
 impl VatCommands {
-    pub async fn execute(&self, client: &FreeAgentClient, format: OutputFormat) -> Result<()> {
+    pub async fn execute(&self, client: &FreeAgentClient, options: &OutputOptions) -> Result<()> {
         match self {
             Self::List { page, per_page } => {
                 let params = QueryBuilder::new()
                     .add_i32("page", *page)
                     .add_i32("per_page", *per_page)
                     .build();
-                let result = client.get("vat_returns", params).await?;
-                print_output(&result, format);
+                print_list(client, "vat_returns", params, options).await?;
             }
             Self::Get { id } => {
                 let result = client.get(&format!("vat_returns/{}", id), None).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkAsFiled { id } => {
                 let result = client.put(&format!("vat_returns/{}/transitions/mark_as_filed", id), None::<()>).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkAsUnfiled { id } => {
                 let result = client.put(&format!("vat_returns/{}/transitions/mark_as_unfiled", id), None::<()>).await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkPaymentAsPaid { period_ends_on, payment_date } => {
                 let result = client
@@ -89,7 +90,7 @@ impl VatCommands {
                         None::<()>,
                     )
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
             Self::MarkPaymentAsUnpaid { period_ends_on, payment_date } => {
                 let result = client
@@ -101,7 +102,7 @@ impl VatCommands {
                         None::<()>,
                     )
                     .await?;
-                print_output(&result, format);
+                print_output(&result, options)?;
             }
         }
         
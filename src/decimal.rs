@@ -0,0 +1,110 @@
+//! Exact decimal arithmetic for FreeAgent's string-encoded amounts, shared by
+//! table totals, sorting and the `--query` aggregate functions.
+
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// An exact decimal such as FreeAgent's `"1200.50"` amounts, so totals don't
+/// pick up floating point error
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Decimal {
+    units: i128,
+    scale: u32,
+}
+
+impl Decimal {
+    pub(crate) const ZERO: Self = Self { units: 0, scale: 0 };
+    const MAX_SCALE: u32 = 18;
+
+    pub(crate) fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(s) => s.trim().parse().ok(),
+            Value::Number(n) => n.to_string().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn rescaled(self, scale: u32) -> i128 {
+        self.units * 10i128.pow(scale - self.scale)
+    }
+
+    pub(crate) fn add(self, other: Self) -> Self {
+        let scale = self.scale.max(other.scale);
+        Self {
+            units: self.rescaled(scale).saturating_add(other.rescaled(scale)),
+            scale,
+        }
+    }
+
+    /// The nearest `f64`, for results that must be JSON numbers
+    pub(crate) fn to_f64(self) -> f64 {
+        self.units as f64 / 10f64.powi(self.scale as i32)
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if whole.is_empty() || !all_digits(whole) || !all_digits(fraction) {
+            return Err(());
+        }
+        let scale = fraction.len() as u32;
+        if scale > Self::MAX_SCALE || whole.len() > 20 {
+            return Err(());
+        }
+        let units: i128 = format!("{}{}", whole, fraction).parse().map_err(|_| ())?;
+        Ok(Self {
+            units: if negative { -units } else { units },
+            scale,
+        })
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        self.rescaled(scale).cmp(&other.rescaled(scale))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.units < 0 { "-" } else { "" };
+        let units = self.units.unsigned_abs();
+        if self.scale == 0 {
+            return write!(f, "{}{}", sign, units);
+        }
+        let divisor = 10u128.pow(self.scale);
+        write!(
+            f,
+            "{}{}.{:0width$}",
+            sign,
+            units / divisor,
+            units % divisor,
+            width = self.scale as usize
+        )
+    }
+}
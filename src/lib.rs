@@ -0,0 +1,44 @@
+//! FreeAgent API library.
+//!
+//! This crate powers the `freeagent` CLI and can also be used directly by other
+//! tools that need to talk to the FreeAgent API:
+//!
+//! - [`api`]: the HTTP client with automatic token refresh, pagination and retry
+//! - [`auth`]: OAuth2 login, token refresh and token storage
+//! - [`models`]: typed models for core resources
+//! - [`output`]: JSON and table formatting used by the CLI
+//! - [`query`]: JMESPath expressions behind `--query`
+//!
+//! The library follows semantic versioning together with the CLI; [`VERSION`]
+//! reports the version in use.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use freeagent::api::FreeAgentClient;
+//! use freeagent::models::InvoiceFilter;
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! // Uses the tokens stored by `freeagent login`
+//! let client = FreeAgentClient::new(false).await?;
+//! let filter = InvoiceFilter {
+//!     view: Some("overdue".to_string()),
+//!     ..Default::default()
+//! };
+//! for invoice in client.list_invoices(&filter).await? {
+//!     println!("{:?} due {:?}", invoice.reference, invoice.due_on);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod api;
+pub mod auth;
+pub mod models;
+pub mod output;
+pub mod query;
+
+mod decimal;
+
+/// Version of the library and CLI
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
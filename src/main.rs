@@ -22,20 +22,25 @@
 //! freeagent contacts create --first-name "John" --last-name "Doe"
 //! ```
 
-mod api;
-mod auth;
 mod commands;
-mod output;
 mod update;
 
 use anyhow::Result;
-use clap::{CommandFactory, Parser, Subcommand};
+use clap::builder::BoolishValueParser;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 use clap_complete::{generate, Shell};
 use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use api::FreeAgentClient;
 use commands::*;
-use output::OutputFormat;
+use freeagent::api::{FreeAgentClient, HttpLogger, LogLevel, Pagination, ResponseCache};
+use freeagent::auth;
+use freeagent::output::{
+    print_error, ErrorFormat, OutputFormat, OutputOptions, SortKey, TableOptions,
+};
+use freeagent::query::Query;
 
 /// FreeAgent CLI - Interact with the FreeAgent accounting API
 #[derive(Parser)]
@@ -49,10 +54,78 @@ struct Cli {
     #[arg(short, long, value_enum, default_value = "json", global = true)]
     format: OutputFormat,
 
+    /// Whether --format was given rather than defaulted
+    #[arg(skip)]
+    format_explicit: bool,
+
+    /// JMESPath expression selecting what to print, e.g. "invoices[?status=='Overdue'].reference"
+    #[arg(long, global = true, value_name = "EXPR")]
+    query: Option<String>,
+
+    /// Columns to show in table, CSV and TSV output, e.g. reference,total_value
+    #[arg(long, global = true, value_name = "FIELDS", value_delimiter = ',', env = "FREEAGENT_TABLE_COLUMNS")]
+    columns: Vec<String>,
+
+    /// Order list rows by a field, numerically for amounts; append :desc to reverse
+    #[arg(long, global = true, value_name = "FIELD[:desc]")]
+    sort_by: Option<SortKey>,
+
+    /// Add a table footer row summing these fields, e.g. total_value,amount
+    #[arg(long, global = true, value_name = "FIELDS", value_delimiter = ',')]
+    totals: Vec<String>,
+
+    /// Error output format on stderr
+    #[arg(long, value_enum, default_value = "text", global = true)]
+    error_format: ErrorFormat,
+
     /// Use sandbox API instead of production
     #[arg(long, global = true)]
     sandbox: bool,
 
+    /// Use the tokens and settings of a named profile
+    #[arg(long, global = true, value_name = "NAME", env = auth::profile::PROFILE_ENV)]
+    profile: Option<String>,
+
+    /// Fetch every page of list results
+    #[arg(long, global = true)]
+    all: bool,
+
+    /// Maximum number of list results to return, following pages as needed
+    #[arg(long, global = true, value_name = "N")]
+    limit: Option<usize>,
+
+    /// Record API requests and responses into this directory
+    #[arg(long, global = true, value_name = "DIR", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Serve API responses from a recorded directory instead of the network
+    #[arg(long, global = true, value_name = "DIR")]
+    replay: Option<PathBuf>,
+
+    /// Print write requests instead of sending them; GETs still run
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Log HTTP requests and responses to stderr
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Also log HTTP headers and bodies (implies --verbose)
+    #[arg(long, global = true)]
+    trace: bool,
+
+    /// Write the HTTP log to this file instead of stderr (implies --verbose)
+    #[arg(long, global = true, value_name = "FILE")]
+    log_file: Option<PathBuf>,
+
+    /// Cache reference data such as categories and the company on disk
+    #[arg(long, global = true, env = "FREEAGENT_CACHE", value_parser = BoolishValueParser::new())]
+    cache: bool,
+
+    /// Bypass the cache even if --cache or FREEAGENT_CACHE is set
+    #[arg(long, global = true, env = "FREEAGENT_NO_CACHE", value_parser = BoolishValueParser::new())]
+    no_cache: bool,
+
     /// Disable automatic update checks
     #[arg(long, global = true, env = "FREEAGENT_NO_UPDATE")]
     no_update: bool,
@@ -62,12 +135,25 @@ struct Cli {
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 enum Commands {
     /// Login to FreeAgent using OAuth2
-    Login,
+    Login {
+        /// Print the authorization URL and paste the redirect URL back instead of using a local callback
+        ///
+        /// Paste the full URL from the address bar, not just the code: its
+        /// state parameter is checked so a login started elsewhere cannot be
+        /// slipped in.
+        #[arg(long)]
+        no_browser: bool,
+    },
     
     /// Logout and delete stored tokens
     Logout,
+
+    /// Authentication and token management
+    #[command(subcommand)]
+    Auth(commands::auth::AuthCommands),
     
     /// Show current authentication status
     Status,
@@ -79,6 +165,17 @@ enum Commands {
         yes: bool,
     },
 
+    /// Send a raw request to any API endpoint
+    Api(raw::ApiArgs),
+
+    /// Manage the local response cache
+    #[command(subcommand)]
+    Cache(cache::CacheCommands),
+
+    /// Manage named profiles for different companies and environments
+    #[command(subcommand)]
+    Profiles(profiles::ProfileCommands),
+
     /// Generate shell completions
     Completions {
         /// Shell to generate completions for
@@ -263,10 +360,40 @@ enum Commands {
     CisBands(cis_bands::CisBandCommands),
 }
 
+/// Build the HTTP logger requested by `--verbose`, `--trace` or `--log-file`
+fn http_logger(verbose: bool, trace: bool, log_file: Option<&Path>) -> Result<Option<Arc<HttpLogger>>> {
+    let level = if trace {
+        LogLevel::Trace
+    } else if verbose || log_file.is_some() {
+        LogLevel::Verbose
+    } else {
+        return Ok(None);
+    };
+
+    let logger = match log_file {
+        Some(path) => HttpLogger::file(level, path)?,
+        None => HttpLogger::stderr(level),
+    };
+    Ok(Some(Arc::new(logger)))
+}
+
 #[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+async fn main() {
+    let matches = Cli::command().get_matches();
+    let mut cli = match Cli::from_arg_matches(&matches) {
+        Ok(cli) => cli,
+        Err(err) => err.exit(),
+    };
+    cli.format_explicit = matches.value_source("format") == Some(ValueSource::CommandLine);
+    let error_format = cli.error_format;
+
+    if let Err(err) = run(cli).await {
+        let exit_code = print_error(&err, error_format);
+        std::process::exit(exit_code);
+    }
+}
 
+async fn run(cli: Cli) -> Result<()> {
     if let Commands::Completions { shell } = &cli.command {
         let mut cmd = Cli::command();
         let stdout = io::stdout();
@@ -277,84 +404,142 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    if update::maybe_auto_update(cli.no_update).await? {
+    // Parse before any request so a typo fails fast
+    let query = cli.query.as_deref().map(Query::parse).transpose()?;
+    let output = OutputOptions {
+        format: cli.format,
+        query,
+        table: TableOptions {
+            columns: cli.columns.clone(),
+            sort_by: cli.sort_by.clone(),
+            totals: cli.totals.clone(),
+        },
+    };
+
+    if update::maybe_auto_update(cli.no_update || cli.replay.is_some()).await? {
         return Ok(());
     }
 
+    let profile = auth::profile::resolve(cli.profile.as_deref())?;
+    // A profile logged in to the sandbox keeps using it without --sandbox
+    let sandbox = cli.sandbox || auth::profile::Config::load()?.settings(&profile).sandbox;
+    let explicit_output = cli.format_explicit.then_some(&output);
+
     match cli.command {
         // Auth commands don't need a client
-        Commands::Login => {
-            let oauth = auth::OAuthManager::new(cli.sandbox)?;
-            oauth.login().await?;
+        Commands::Login { no_browser } => {
+            commands::auth::AuthCommands::Login { no_browser }
+                .execute(&profile, cli.sandbox, None)
+                .await?;
         }
         Commands::Logout => {
-            let oauth = auth::OAuthManager::new(cli.sandbox)?;
-            oauth.logout()?;
+            commands::auth::AuthCommands::Logout.execute(&profile, sandbox, None).await?;
         }
         Commands::Status => {
-            commands::auth::AuthCommands::Status.execute(cli.sandbox).await?;
+            commands::auth::AuthCommands::Status
+                .execute(&profile, sandbox, explicit_output)
+                .await?;
+        }
+        Commands::Auth(cmd) => {
+            // Logging in picks the environment rather than following the profile
+            let sandbox = if matches!(cmd, commands::auth::AuthCommands::Login { .. }) {
+                cli.sandbox
+            } else {
+                sandbox
+            };
+            cmd.execute(&profile, sandbox, explicit_output).await?;
         }
         Commands::Update { yes } => {
             update::run_update(yes).await?;
         }
+        Commands::Cache(cmd) => {
+            cmd.execute(&profile)?;
+        }
+        Commands::Profiles(cmd) => {
+            cmd.execute(&profile, &output)?;
+        }
         Commands::Completions { .. } => {
             unreachable!("completions handled before update checks");
         }
         
         // All other commands need an authenticated client
         cmd => {
-            let client = FreeAgentClient::new(cli.sandbox).await?;
+            let client = match (&cli.replay, &cli.record) {
+                (Some(dir), _) => FreeAgentClient::replay(sandbox, dir)?,
+                (None, Some(dir)) => FreeAgentClient::for_profile(&profile, sandbox).await?.with_recording(dir),
+                (None, None) => FreeAgentClient::for_profile(&profile, sandbox).await?,
+            };
+            let mut client = client
+                .with_pagination(Pagination {
+                    all: cli.all,
+                    limit: cli.limit,
+                })
+                .with_dry_run(cli.dry_run);
+            if let Some(logger) = http_logger(cli.verbose, cli.trace, cli.log_file.as_deref())? {
+                client = client.with_logger(logger);
+            }
+            // Recording and replaying need every request to reach the cassette
+            if cli.cache && !cli.no_cache && cli.record.is_none() && cli.replay.is_none() {
+                client = client.with_cache(ResponseCache::new(ResponseCache::profile_dir(&profile)?));
+            }
             
             match cmd {
-                Commands::Company(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Users(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Contacts(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Projects(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Tasks(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Invoices(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Bills(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Expenses(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::CreditNotes(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Estimates(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::RecurringInvoices(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::BankAccounts(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::BankTransactions(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Timeslips(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Categories(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Accounting(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Vat(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Attachments(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Notes(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::CapitalAssets(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::StockItems(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::AccountManagers(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Practice(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Clients(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::BankFeeds(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::BankTransactionExplanations(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::CorporationTaxReturns(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::CreditNoteReconciliations(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Currencies(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::DepreciationProfiles(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::EcMoss(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::EmailAddresses(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::EstimateItems(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::FinalAccountsReports(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::HirePurchases(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::IncomeTaxReturns(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::JournalSets(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Payroll(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::PayrollProfiles(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::PriceListItems(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::Properties(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::SalesTaxPeriods(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::CapitalAssetTypes(cmd) => cmd.execute(&client, cli.format).await?,
-                Commands::CisBands(cmd) => cmd.execute(&client, cli.format).await?,
+                Commands::Api(args) => args.execute(&client, &output).await?,
+                Commands::Company(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Users(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Contacts(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Projects(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Tasks(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Invoices(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Bills(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Expenses(cmd) => cmd.execute(&client, &output).await?,
+                Commands::CreditNotes(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Estimates(cmd) => cmd.execute(&client, &output).await?,
+                Commands::RecurringInvoices(cmd) => cmd.execute(&client, &output).await?,
+                Commands::BankAccounts(cmd) => cmd.execute(&client, &output).await?,
+                Commands::BankTransactions(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Timeslips(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Categories(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Accounting(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Vat(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Attachments(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Notes(cmd) => cmd.execute(&client, &output).await?,
+                Commands::CapitalAssets(cmd) => cmd.execute(&client, &output).await?,
+                Commands::StockItems(cmd) => cmd.execute(&client, &output).await?,
+                Commands::AccountManagers(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Practice(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Clients(cmd) => cmd.execute(&client, &output).await?,
+                Commands::BankFeeds(cmd) => cmd.execute(&client, &output).await?,
+                Commands::BankTransactionExplanations(cmd) => cmd.execute(&client, &output).await?,
+                Commands::CorporationTaxReturns(cmd) => cmd.execute(&client, &output).await?,
+                Commands::CreditNoteReconciliations(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Currencies(cmd) => cmd.execute(&client, &output).await?,
+                Commands::DepreciationProfiles(cmd) => cmd.execute(&client, &output).await?,
+                Commands::EcMoss(cmd) => cmd.execute(&client, &output).await?,
+                Commands::EmailAddresses(cmd) => cmd.execute(&client, &output).await?,
+                Commands::EstimateItems(cmd) => cmd.execute(&client, &output).await?,
+                Commands::FinalAccountsReports(cmd) => cmd.execute(&client, &output).await?,
+                Commands::HirePurchases(cmd) => cmd.execute(&client, &output).await?,
+                Commands::IncomeTaxReturns(cmd) => cmd.execute(&client, &output).await?,
+                Commands::JournalSets(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Payroll(cmd) => cmd.execute(&client, &output).await?,
+                Commands::PayrollProfiles(cmd) => cmd.execute(&client, &output).await?,
+                Commands::PriceListItems(cmd) => cmd.execute(&client, &output).await?,
+                Commands::Properties(cmd) => cmd.execute(&client, &output).await?,
+                Commands::SalesTaxPeriods(cmd) => cmd.execute(&client, &output).await?,
+                Commands::CapitalAssetTypes(cmd) => cmd.execute(&client, &output).await?,
+                Commands::CisBands(cmd) => cmd.execute(&client, &output).await?,
                 Commands::Completions { .. } => {
                     unreachable!("completions handled before update checks");
                 }
                 // Already handled above
-                Commands::Login | Commands::Logout | Commands::Status | Commands::Update { .. } => {
+                Commands::Login { .. }
+                | Commands::Logout
+                | Commands::Status
+                | Commands::Auth(_)
+                | Commands::Update { .. }
+                | Commands::Cache(_)
+                | Commands::Profiles(_) => {
                     unreachable!()
                 }
             }
@@ -0,0 +1,65 @@
+//! Bank transaction models.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use super::{impl_resource, BankTransactionExplanation, ListFilter};
+use crate::api::QueryBuilder;
+
+/// A transaction on a bank account
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BankTransaction {
+    #[serde(skip_serializing)]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank_account: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dated_on: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unexplained_amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_manual: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank_transaction_explanations: Option<Vec<BankTransactionExplanation>>,
+    #[serde(skip_serializing)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Fields not modelled above
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+impl_resource!(BankTransaction, "bank_transactions", "bank_transaction", "bank_transactions");
+
+/// Filters for listing bank transactions
+#[derive(Debug, Clone, Default)]
+pub struct BankTransactionFilter {
+    /// Bank account URL (required by the API)
+    pub bank_account: String,
+    /// View name, e.g. `unexplained`
+    pub view: Option<String>,
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+}
+
+impl ListFilter for BankTransactionFilter {
+    fn to_params(&self) -> Option<HashMap<String, String>> {
+        QueryBuilder::new()
+            .add("bank_account", Some(self.bank_account.clone()))
+            .add("view", self.view.clone())
+            .add("from_date", self.from_date.map(|d| d.to_string()))
+            .add("to_date", self.to_date.map(|d| d.to_string()))
+            .add_i32("page", self.page)
+            .add_i32("per_page", self.per_page)
+            .build()
+    }
+}
@@ -0,0 +1,78 @@
+//! Bank transaction explanation models.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use super::{impl_resource, ListFilter};
+use crate::api::QueryBuilder;
+
+/// An explanation allocating (part of) a bank transaction
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BankTransactionExplanation {
+    #[serde(skip_serializing)]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank_account: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank_transaction: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dated_on: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gross_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sales_tax_rate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sales_tax_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paid_invoice: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paid_bill: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paid_user: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transfer_bank_account: Option<String>,
+    #[serde(skip_serializing)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Fields not modelled above
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+impl_resource!(
+    BankTransactionExplanation,
+    "bank_transaction_explanations",
+    "bank_transaction_explanation",
+    "bank_transaction_explanations"
+);
+
+/// Filters for listing bank transaction explanations
+#[derive(Debug, Clone, Default)]
+pub struct BankTransactionExplanationFilter {
+    /// Bank account URL (required by the API)
+    pub bank_account: String,
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    /// ISO 8601 timestamp
+    pub updated_since: Option<String>,
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+}
+
+impl ListFilter for BankTransactionExplanationFilter {
+    fn to_params(&self) -> Option<HashMap<String, String>> {
+        QueryBuilder::new()
+            .add("bank_account", Some(self.bank_account.clone()))
+            .add("from_date", self.from_date.map(|d| d.to_string()))
+            .add("to_date", self.to_date.map(|d| d.to_string()))
+            .add("updated_since", self.updated_since.clone())
+            .add_i32("page", self.page)
+            .add_i32("per_page", self.per_page)
+            .build()
+    }
+}
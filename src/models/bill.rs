@@ -0,0 +1,75 @@
+//! Bill models.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use super::{impl_resource, ListFilter};
+use crate::api::QueryBuilder;
+
+/// A bill from a supplier
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Bill {
+    #[serde(skip_serializing)]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dated_on: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_on: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sales_tax_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paid_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comments: Option<String>,
+    #[serde(skip_serializing)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Fields not modelled above
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+impl_resource!(Bill, "bills", "bill", "bills");
+
+/// Filters for listing bills
+#[derive(Debug, Clone, Default)]
+pub struct BillFilter {
+    /// View name, e.g. `open`
+    pub view: Option<String>,
+    /// Contact URL
+    pub contact: Option<String>,
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+}
+
+impl ListFilter for BillFilter {
+    fn to_params(&self) -> Option<HashMap<String, String>> {
+        QueryBuilder::new()
+            .add("view", self.view.clone())
+            .add("contact", self.contact.clone())
+            .add("from_date", self.from_date.map(|d| d.to_string()))
+            .add("to_date", self.to_date.map(|d| d.to_string()))
+            .add_i32("page", self.page)
+            .add_i32("per_page", self.per_page)
+            .build()
+    }
+}
@@ -0,0 +1,30 @@
+//! Category models.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::impl_resource;
+
+/// An accounting category, identified by its nominal code
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Category {
+    #[serde(skip_serializing)]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nominal_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tax_reporting_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowable_for_tax: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_sales_tax_rate: Option<String>,
+    /// Fields not modelled above
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+impl_resource!(Category, "categories", "category", "categories");
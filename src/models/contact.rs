@@ -0,0 +1,91 @@
+//! Contact models.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use super::{impl_resource, ListFilter};
+use crate::api::QueryBuilder;
+
+/// A client or supplier contact
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Contact {
+    #[serde(skip_serializing)]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organisation_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub billing_email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address1: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub town: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub postcode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_payment_terms_in_days: Option<i64>,
+    #[serde(skip_serializing)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Fields not modelled above
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+impl_resource!(Contact, "contacts", "contact", "contacts");
+
+impl Contact {
+    /// Organisation name, falling back to the person's full name
+    pub fn display_name(&self) -> String {
+        if let Some(org) = self.organisation_name.as_deref().filter(|s| !s.is_empty()) {
+            return org.to_string();
+        }
+        [self.first_name.as_deref(), self.last_name.as_deref()]
+            .into_iter()
+            .flatten()
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Filters for listing contacts
+#[derive(Debug, Clone, Default)]
+pub struct ContactFilter {
+    /// View name, e.g. `active`
+    pub view: Option<String>,
+    /// Sort order, e.g. `name` or `-updated_at`
+    pub sort: Option<String>,
+    /// ISO 8601 timestamp
+    pub updated_since: Option<String>,
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+}
+
+impl ListFilter for ContactFilter {
+    fn to_params(&self) -> Option<HashMap<String, String>> {
+        QueryBuilder::new()
+            .add("view", self.view.clone())
+            .add("sort", self.sort.clone())
+            .add("updated_since", self.updated_since.clone())
+            .add_i32("page", self.page)
+            .add_i32("per_page", self.per_page)
+            .build()
+    }
+}
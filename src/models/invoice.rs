@@ -0,0 +1,118 @@
+//! Invoice models.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use super::{impl_resource, ListFilter};
+use crate::api::QueryBuilder;
+
+/// An invoice
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Invoice {
+    #[serde(skip_serializing)]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dated_on: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_on: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_terms_in_days: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exchange_rate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub net_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sales_tax_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paid_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comments: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ec_status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice_items: Option<Vec<InvoiceItem>>,
+    #[serde(skip_serializing)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Fields not modelled above
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+impl_resource!(Invoice, "invoices", "invoice", "invoices");
+
+/// A line item on an invoice
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InvoiceItem {
+    /// Sent back on update, since it identifies an existing item
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sales_tax_rate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Fields not modelled above
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// Filters for listing invoices
+#[derive(Debug, Clone, Default)]
+pub struct InvoiceFilter {
+    /// View name, e.g. `open_or_overdue`
+    pub view: Option<String>,
+    /// Contact URL
+    pub contact: Option<String>,
+    /// Project URL
+    pub project: Option<String>,
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    /// ISO 8601 timestamp
+    pub updated_since: Option<String>,
+    pub nested_invoice_items: bool,
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+}
+
+impl ListFilter for InvoiceFilter {
+    fn to_params(&self) -> Option<HashMap<String, String>> {
+        QueryBuilder::new()
+            .add("view", self.view.clone())
+            .add("contact", self.contact.clone())
+            .add("project", self.project.clone())
+            .add("from_date", self.from_date.map(|d| d.to_string()))
+            .add("to_date", self.to_date.map(|d| d.to_string()))
+            .add("updated_since", self.updated_since.clone())
+            .add_bool("nested_invoice_items", self.nested_invoice_items.then_some(true))
+            .add_i32("page", self.page)
+            .add_i32("per_page", self.per_page)
+            .build()
+    }
+}
@@ -0,0 +1,127 @@
+//! Typed models for core FreeAgent resources.
+//!
+//! Monetary amounts are kept as the decimal strings returned by the API so no
+//! precision is lost. Fields not modelled explicitly are preserved in `extra`.
+//!
+//! `url`, `created_at`, `updated_at` and `extra` are read from responses but
+//! never serialized, so they stay out of create and update request bodies.
+
+pub mod bank_transaction;
+pub mod bank_transaction_explanation;
+pub mod bill;
+pub mod category;
+pub mod contact;
+pub mod invoice;
+pub mod project;
+pub mod timeslip;
+pub mod user;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+
+pub use bank_transaction::{BankTransaction, BankTransactionFilter};
+pub use bank_transaction_explanation::{BankTransactionExplanation, BankTransactionExplanationFilter};
+pub use bill::{Bill, BillFilter};
+pub use category::Category;
+pub use contact::{Contact, ContactFilter};
+pub use invoice::{Invoice, InvoiceFilter, InvoiceItem};
+pub use project::{Project, ProjectFilter};
+pub use timeslip::{Timeslip, TimeslipFilter};
+pub use user::{User, UserFilter};
+
+/// A FreeAgent resource addressable at `<base_url>/<PATH>/<id>`
+pub trait Resource: DeserializeOwned + Serialize {
+    /// Collection path, e.g. `invoices`
+    const PATH: &'static str;
+    /// JSON key wrapping a single record, e.g. `invoice`
+    const SINGULAR: &'static str;
+    /// JSON key wrapping a collection, e.g. `invoices`
+    const PLURAL: &'static str;
+
+    /// Canonical API URL of the record
+    fn url(&self) -> Option<&str>;
+
+    /// Record ID, taken from the last segment of its URL
+    fn id(&self) -> Option<&str> {
+        self.url().and_then(|url| url.rsplit('/').next())
+    }
+}
+
+/// Query parameters for a list endpoint
+pub trait ListFilter {
+    /// Build the query parameters for the request
+    fn to_params(&self) -> Option<HashMap<String, String>>;
+}
+
+macro_rules! impl_resource {
+    ($ty:ty, $path:literal, $singular:literal, $plural:literal) => {
+        impl $crate::models::Resource for $ty {
+            const PATH: &'static str = $path;
+            const SINGULAR: &'static str = $singular;
+            const PLURAL: &'static str = $plural;
+
+            fn url(&self) -> Option<&str> {
+                self.url.as_deref()
+            }
+        }
+    };
+}
+
+pub(crate) use impl_resource;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn resource_id_is_last_url_segment() {
+        let invoice: Invoice = serde_json::from_value(json!({
+            "url": "https://api.freeagent.com/v2/invoices/42",
+            "total_value": "120.50",
+            "due_on": "2024-02-14",
+        }))
+        .expect("invoice should deserialize");
+        assert_eq!(invoice.id(), Some("42"));
+        assert_eq!(invoice.total_value.as_deref(), Some("120.50"));
+        assert_eq!(
+            invoice.due_on,
+            chrono::NaiveDate::from_ymd_opt(2024, 2, 14)
+        );
+    }
+
+    #[test]
+    fn read_only_fields_are_kept_but_not_serialized() {
+        let value = json!({
+            "url": "https://api.freeagent.com/v2/contacts/7",
+            "organisation_name": "Acme",
+            "uses_contact_invoice_sequence": true,
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-02T00:00:00Z",
+        });
+        let contact: Contact = serde_json::from_value(value).expect("contact");
+        assert_eq!(contact.id(), Some("7"));
+        assert_eq!(contact.extra["uses_contact_invoice_sequence"], true);
+        assert!(contact.updated_at.is_some());
+        assert_eq!(
+            serde_json::to_value(&contact).expect("serialize"),
+            json!({"organisation_name": "Acme"})
+        );
+    }
+
+    #[test]
+    fn filters_build_query_params() {
+        let filter = InvoiceFilter {
+            view: Some("overdue".to_string()),
+            nested_invoice_items: true,
+            per_page: Some(50),
+            ..Default::default()
+        };
+        let params = filter.to_params().expect("params");
+        assert_eq!(params.get("view").map(String::as_str), Some("overdue"));
+        assert_eq!(params.get("nested_invoice_items").map(String::as_str), Some("true"));
+        assert_eq!(params.get("per_page").map(String::as_str), Some("50"));
+        assert!(InvoiceFilter::default().to_params().is_none());
+    }
+}
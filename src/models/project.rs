@@ -0,0 +1,70 @@
+//! Project models.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use super::{impl_resource, ListFilter};
+use crate::api::QueryBuilder;
+
+/// A project for a contact
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Project {
+    #[serde(skip_serializing)]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget_units: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normal_billing_rate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub billing_period: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub starts_on: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ends_on: Option<NaiveDate>,
+    #[serde(skip_serializing)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Fields not modelled above
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+impl_resource!(Project, "projects", "project", "projects");
+
+/// Filters for listing projects
+#[derive(Debug, Clone, Default)]
+pub struct ProjectFilter {
+    /// View name, e.g. `active`
+    pub view: Option<String>,
+    /// Contact URL
+    pub contact: Option<String>,
+    /// ISO 8601 timestamp
+    pub updated_since: Option<String>,
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+}
+
+impl ListFilter for ProjectFilter {
+    fn to_params(&self) -> Option<HashMap<String, String>> {
+        QueryBuilder::new()
+            .add("view", self.view.clone())
+            .add("contact", self.contact.clone())
+            .add("updated_since", self.updated_since.clone())
+            .add_i32("page", self.page)
+            .add_i32("per_page", self.per_page)
+            .build()
+    }
+}
@@ -1,41 +1,149 @@
 //! Output formatting utilities.
 
 use colored::*;
+use serde_json::json;
 use comfy_table::{presets::UTF8_FULL, ColumnConstraint, ContentArrangement, Table, Width};
 use serde_json::Value;
+use std::cmp::Ordering;
 use std::collections::HashSet;
 use std::env;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::api::error::{ApiError, EXIT_GENERAL};
+use crate::decimal::Decimal;
+use crate::query::Query;
 
 const DEFAULT_MAX_TABLE_COLUMNS: usize = 8;
 const DEFAULT_MAX_CELL_WIDTH: usize = 40;
-const TABLE_COLUMNS_ENV: &str = "FREEAGENT_TABLE_COLUMNS";
 const TABLE_MAX_COLUMNS_ENV: &str = "FREEAGENT_TABLE_MAX_COLUMNS";
 const TABLE_MAX_CELL_WIDTH_ENV: &str = "FREEAGENT_TABLE_MAX_CELL_WIDTH";
 const TABLE_WIDTH_ENV: &str = "FREEAGENT_TABLE_WIDTH";
 
 /// Output format options
-#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
 pub enum OutputFormat {
     /// JSON output (default)
+    #[default]
     Json,
-    /// Table output for lists
+    /// Table output; single records as field/value pairs
     Table,
     /// Compact JSON (single line)
     Compact,
+    /// Comma-separated values, nested objects flattened into dotted columns
+    Csv,
+    /// Tab-separated values, nested objects flattened into dotted columns
+    Tsv,
+    /// One JSON object per line, streamed as list pages arrive
+    Ndjson,
+}
+
+/// Error output options
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ErrorFormat {
+    /// Human-readable message (default)
+    #[default]
+    Text,
+    /// JSON object with the error kind, status and field errors
+    Json,
+}
+
+/// How [`print_output`] presents a response
+#[derive(Debug, Clone, Default)]
+pub struct OutputOptions {
+    /// Output format (`--format`)
+    pub format: OutputFormat,
+    /// Expression applied before formatting (`--query`)
+    pub query: Option<Query>,
+    /// Columns, sorting and totals for list output
+    pub table: TableOptions,
 }
 
-impl Default for OutputFormat {
-    fn default() -> Self {
-        Self::Json
+impl OutputOptions {
+    /// Whether list pages can be printed as they arrive: NDJSON output that
+    /// no query or sort needs to see whole
+    pub fn streams_lists(&self) -> bool {
+        self.format == OutputFormat::Ndjson && self.query.is_none() && self.table.sort_by.is_none()
     }
 }
 
-/// Format and print output
-pub fn print_output(data: &Value, format: OutputFormat) {
-    match format {
+/// Column selection, sorting and totals for list output
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableOptions {
+    /// Columns to show, in order (`--columns`)
+    pub columns: Vec<String>,
+    /// Field to order rows by (`--sort-by`)
+    pub sort_by: Option<SortKey>,
+    /// Fields summed in a footer row of table output (`--totals`)
+    pub totals: Vec<String>,
+}
+
+/// A `--sort-by` value: `field` or `field:desc`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: String,
+    pub descending: bool,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (field, descending) = match value.rsplit_once(':') {
+            Some((field, "desc")) => (field, true),
+            Some((field, "asc")) => (field, false),
+            Some((_, order)) => {
+                return Err(format!("unknown sort order '{}' (expected asc or desc)", order))
+            }
+            None => (value, false),
+        };
+        let field = field.trim();
+        if field.is_empty() {
+            return Err("missing field name".to_string());
+        }
+        Ok(Self {
+            field: field.to_string(),
+            descending,
+        })
+    }
+}
+
+/// Apply the query in `options`, then print `data` in its format
+pub fn print_output(data: &Value, options: &OutputOptions) -> anyhow::Result<()> {
+    let queried;
+    let data = match &options.query {
+        Some(query) => {
+            queried = query.apply(data)?;
+            &queried
+        }
+        None => data,
+    };
+
+    match options.format {
         OutputFormat::Json => print_json(data),
-        OutputFormat::Table => print_table(data),
+        OutputFormat::Table => print_table(data, &options.table),
         OutputFormat::Compact => print_compact(data),
+        OutputFormat::Csv => print_delimited(data, ',', &options.table),
+        OutputFormat::Tsv => print_delimited(data, '\t', &options.table),
+        OutputFormat::Ndjson => print_ndjson(data, &options.table),
+    }
+    Ok(())
+}
+
+/// Print each record of a list on its own line, or a single response as one line
+fn print_ndjson(data: &Value, options: &TableOptions) {
+    let mut out = io::stdout().lock();
+    for line in ndjson_lines(data, options) {
+        if writeln!(out, "{}", line).is_err() {
+            return;
+        }
+    }
+}
+
+fn ndjson_lines(data: &Value, options: &TableOptions) -> Vec<String> {
+    match find_array(data) {
+        Some(arr) => sorted_records(arr, options).into_iter().map(Value::to_string).collect(),
+        None => vec![data.to_string()],
     }
 }
 
@@ -55,57 +163,368 @@ fn print_compact(data: &Value) {
     }
 }
 
-/// Print as table (for list responses)
-fn print_table(data: &Value) {
-    // Try to find an array in the response
-    let array = find_array(data);
-    
-    match array {
-        Some(arr) if !arr.is_empty() => {
-            let mut table = Table::new();
-            table.load_preset(UTF8_FULL);
-            table.set_content_arrangement(ContentArrangement::DynamicFullWidth);
-            if let Some(width) = resolve_table_width() {
-                table.set_width(width);
-            }
-            
-            // Get headers from first object
-            if let Some(first) = arr.first() {
-                if let Some(obj) = first.as_object() {
-                    let headers = select_headers(obj);
-                    table.set_header(&headers);
-                    let max_cell_width = max_cell_width();
-                    let column_constraint = ColumnConstraint::UpperBoundary(Width::Fixed(
-                        max_cell_width.min(u16::MAX as usize) as u16,
-                    ));
-                    table.set_constraints(std::iter::repeat(column_constraint).take(headers.len()));
-                    
-                    // Add rows
-                    for item in arr {
-                        if let Some(item_obj) = item.as_object() {
-                            let row: Vec<String> = headers
-                                .iter()
-                                .map(|h| format_value_with_width(item_obj.get(h), max_cell_width))
-                                .collect();
-                            table.add_row(row);
-                        }
-                    }
-                    
-                    println!("{}", table);
-                    return;
+/// Nested collections shown as sub-tables beneath a single record
+const SUB_TABLE_FIELDS: &[&str] = &[
+    "invoice_items",
+    "bill_items",
+    "credit_note_items",
+    "estimate_items",
+    "journal_entries",
+    "bank_transaction_explanations",
+];
+
+/// Print as table: lists as rows, single records as field/value pairs
+fn print_table(data: &Value, options: &TableOptions) {
+    if is_record(data) || find_array(data).is_none() {
+        if let Some(text) = record_view(data, options) {
+            print!("{}", text);
+            return;
+        }
+    } else if let Some(table) = table(data, options) {
+        println!("{}", table);
+        return;
+    }
+    print_json(data);
+}
+
+/// Whether `data` is a single-record response such as `{"invoice": {...}}`
+fn is_record(data: &Value) -> bool {
+    data.as_object()
+        .is_some_and(|obj| obj.len() == 1 && obj.values().all(Value::is_object))
+}
+
+/// Render a list response as a table, or `None` if it has no records
+fn table(data: &Value, options: &TableOptions) -> Option<Table> {
+    let arr = find_array(data).filter(|arr| !arr.is_empty())?;
+    if !arr.first()?.is_object() {
+        return None;
+    }
+
+    // Records may omit empty fields, so offer every column seen in any of them
+    let mut columns = serde_json::Map::new();
+    for record in arr.iter().filter_map(Value::as_object) {
+        merge_fields(&mut columns, record);
+    }
+
+    let mut headers = select_headers(
+        &columns,
+        max_columns_setting().unwrap_or(DEFAULT_MAX_TABLE_COLUMNS),
+        &options.columns,
+    );
+    // A total is no use if its column was left out
+    for name in &options.totals {
+        if !headers.contains(name) && object_field(&columns, name).is_some() {
+            headers.push(name.clone());
+        }
+    }
+    let max_cell_width = max_cell_width();
+    let mut table = new_table(&headers, max_cell_width);
+
+    // Add rows
+    let records = sorted_records(arr, options);
+    for item in &records {
+        if let Some(item_obj) = item.as_object() {
+            let row: Vec<String> = headers
+                .iter()
+                .map(|h| format_value_with_width(object_field(item_obj, h), max_cell_width))
+                .collect();
+            table.add_row(row);
+        }
+    }
+
+    if !options.totals.is_empty() {
+        table.add_row(totals_row(&headers, &records, &options.totals));
+    }
+
+    Some(table)
+}
+
+/// Add the fields of `record` that `columns` lacks, descending into nested
+/// objects so dotted columns are found wherever they appear
+fn merge_fields(columns: &mut serde_json::Map<String, Value>, record: &serde_json::Map<String, Value>) {
+    for (key, value) in record {
+        match columns.get_mut(key) {
+            Some(Value::Object(existing)) => {
+                if let Value::Object(nested) = value {
+                    merge_fields(existing, nested);
                 }
             }
-            
-            // Fallback to JSON
-            print_json(data);
+            Some(existing) if existing.is_null() => *existing = value.clone(),
+            Some(_) => {}
+            None => {
+                columns.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Render a single record as a field/value table followed by a sub-table for
+/// each of its item collections, or `None` if `data` is not an object
+fn record_view(data: &Value, options: &TableOptions) -> Option<String> {
+    let record = single_record(data)?.as_object()?;
+
+    let mut fields = serde_json::Map::new();
+    let mut collections = Vec::new();
+    for (key, value) in record {
+        match value.as_array() {
+            Some(items) if SUB_TABLE_FIELDS.contains(&key.as_str()) => collections.push((key, items)),
+            _ => {
+                let mut single = serde_json::Map::new();
+                single.insert(key.clone(), value.clone());
+                flatten_into(&mut fields, "", &single);
+            }
+        }
+    }
+
+    // Values wrap rather than truncate, as there is a whole row for each
+    let mut table = new_table(&["Field".to_string(), "Value".to_string()], usize::MAX);
+    for key in select_headers(&fields, usize::MAX, &options.columns) {
+        table.add_row(vec![key.clone(), format_record_value(&fields[&key])]);
+    }
+    let mut out = format!("{}\n", table);
+
+    let max_cell_width = max_cell_width();
+    for (key, items) in collections {
+        out.push_str(&format!("\n{} ({})\n", key, items.len()));
+        // Items may omit empty fields, so offer every column seen in any of them
+        let mut columns = serde_json::Map::new();
+        for item in items.iter().filter_map(Value::as_object) {
+            for (name, value) in item {
+                columns.entry(name.clone()).or_insert_with(|| value.clone());
+            }
         }
-        _ => {
-            // Not a list, print as JSON
-            print_json(data);
+        if columns.is_empty() {
+            continue;
+        }
+        let headers = select_headers(
+            &columns,
+            max_columns_setting().unwrap_or(DEFAULT_MAX_TABLE_COLUMNS),
+            &[],
+        );
+        let mut sub_table = new_table(&headers, max_cell_width);
+        for item in items.iter().filter_map(Value::as_object) {
+            sub_table.add_row(
+                headers
+                    .iter()
+                    .map(|h| format_value_with_width(item.get(h), max_cell_width))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        out.push_str(&format!("{}\n", sub_table));
+    }
+
+    Some(out)
+}
+
+/// A table with the shared style, `headers` and cells capped at `max_cell_width`
+fn new_table(headers: &[String], max_cell_width: usize) -> Table {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_content_arrangement(ContentArrangement::DynamicFullWidth);
+    if let Some(width) = resolve_table_width() {
+        table.set_width(width);
+    }
+    table.set_header(headers);
+    if max_cell_width != usize::MAX {
+        let column_constraint = ColumnConstraint::UpperBoundary(Width::Fixed(
+            max_cell_width.min(u16::MAX as usize) as u16,
+        ));
+        table.set_constraints(std::iter::repeat_n(column_constraint, headers.len()));
+    }
+    table
+}
+
+/// Format a record view value in full, listing plain arrays inline
+fn format_record_value(value: &Value) -> String {
+    match value {
+        Value::Array(items) if !items.is_empty() && items.iter().all(|item| !item.is_object() && !item.is_array()) => {
+            items.iter().map(|item| format_cell(Some(item))).collect::<Vec<_>>().join(", ")
         }
+        Value::Array(_) | Value::Object(_) => format_value_with_width(Some(value), usize::MAX),
+        other => format_cell(Some(other)),
     }
 }
 
+/// Footer cells summing `totals` across `records`, labelled in the first free column
+fn totals_row(headers: &[String], records: &[&Value], totals: &[String]) -> Vec<String> {
+    let mut row: Vec<String> = headers
+        .iter()
+        .map(|header| {
+            if !totals.contains(header) {
+                return String::new();
+            }
+            records
+                .iter()
+                .filter_map(|record| field(record, header).and_then(Decimal::from_value))
+                .fold(Decimal::ZERO, Decimal::add)
+                .to_string()
+        })
+        .collect();
+    if let Some(cell) = row.first_mut().filter(|cell| cell.is_empty()) {
+        *cell = "Total".to_string();
+    }
+    row
+}
+
+/// `records` in `--sort-by` order (or as received without one)
+fn sorted_records<'a>(records: &'a [Value], options: &TableOptions) -> Vec<&'a Value> {
+    let mut records: Vec<&Value> = records.iter().collect();
+    if let Some(key) = &options.sort_by {
+        // A stable sort keeps the API's order for equal values
+        records.sort_by(|a, b| compare_fields(field(a, &key.field), field(b, &key.field), key.descending));
+    }
+    records
+}
+
+/// Look up `name` in a record, following `parent.child` into nested objects
+fn field<'a>(record: &'a Value, name: &str) -> Option<&'a Value> {
+    object_field(record.as_object()?, name)
+}
+
+fn object_field<'a>(record: &'a serde_json::Map<String, Value>, name: &str) -> Option<&'a Value> {
+    if let Some(value) = record.get(name) {
+        return Some(value);
+    }
+    let (first, rest) = name.split_once('.')?;
+    rest.split('.').try_fold(record.get(first)?, |value, part| value.get(part))
+}
+
+/// Compare sort fields, numerically when both are numbers or decimal strings.
+/// Missing values go last in either direction.
+fn compare_fields(a: Option<&Value>, b: Option<&Value>, descending: bool) -> Ordering {
+    let a = a.filter(|value| !value.is_null());
+    let b = b.filter(|value| !value.is_null());
+    let ordering = match (a, b) {
+        (None, None) => return Ordering::Equal,
+        (None, Some(_)) => return Ordering::Greater,
+        (Some(_), None) => return Ordering::Less,
+        (Some(a), Some(b)) => match (Decimal::from_value(a), Decimal::from_value(b)) {
+            (Some(x), Some(y)) => x.cmp(&y),
+            _ => format_cell(Some(a)).cmp(&format_cell(Some(b))),
+        },
+    };
+    if descending {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
+/// Print as CSV or TSV, one row per list item (or one row for a single record)
+fn print_delimited(data: &Value, delimiter: char, options: &TableOptions) {
+    match delimited(data, delimiter, options) {
+        Some(text) => print!("{}", text),
+        None => print_json(data),
+    }
+}
+
+/// Render `data` as delimited text, or `None` if it has no records
+fn delimited(data: &Value, delimiter: char, options: &TableOptions) -> Option<String> {
+    let records: Vec<&Value> = match find_array(data) {
+        Some(arr) => sorted_records(arr, options),
+        None => vec![single_record(data)?],
+    };
+
+    // A list of plain values, e.g. from `--query 'invoices[].reference'`
+    if !records.is_empty() && records.iter().all(|record| !record.is_object()) {
+        let mut out = String::new();
+        for record in records {
+            let cell = format_cell(Some(record));
+            write_delimited_row(&mut out, std::iter::once(cell.as_str()), delimiter);
+        }
+        return Some(out);
+    }
+
+    let rows: Vec<serde_json::Map<String, Value>> = records
+        .into_iter()
+        .filter_map(Value::as_object)
+        .map(|obj| {
+            let mut row = serde_json::Map::new();
+            flatten_into(&mut row, "", obj);
+            row
+        })
+        .collect();
+    if rows.is_empty() {
+        return Some(String::new());
+    }
+
+    // Items may omit empty fields, so offer every column seen in any row
+    let mut columns = serde_json::Map::new();
+    for row in &rows {
+        for (key, value) in row {
+            if !columns.contains_key(key) {
+                columns.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    // Spreadsheets have room for every column unless a limit is set
+    let headers = select_headers(
+        &columns,
+        max_columns_setting().unwrap_or(usize::MAX),
+        &options.columns,
+    );
+
+    let mut out = String::new();
+    write_delimited_row(&mut out, headers.iter().map(String::as_str), delimiter);
+    for row in &rows {
+        let cells: Vec<String> = headers.iter().map(|h| format_cell(row.get(h))).collect();
+        write_delimited_row(&mut out, cells.iter().map(String::as_str), delimiter);
+    }
+    Some(out)
+}
+
+/// The object to print for a non-list response, unwrapping `{"invoice": {...}}`
+fn single_record(data: &Value) -> Option<&Value> {
+    let obj = data.as_object()?;
+    match obj.values().next() {
+        Some(inner) if obj.len() == 1 && inner.is_object() => Some(inner),
+        _ => Some(data),
+    }
+}
+
+/// Copy `obj` into `row`, naming nested fields `parent.child`
+fn flatten_into(row: &mut serde_json::Map<String, Value>, prefix: &str, obj: &serde_json::Map<String, Value>) {
+    for (key, value) in obj {
+        let name = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Value::Object(nested) if !nested.is_empty() => flatten_into(row, &name, nested),
+            _ => {
+                row.insert(name, value.clone());
+            }
+        }
+    }
+}
+
+/// Format a value for a CSV/TSV cell; strings (including amounts) are kept verbatim
+fn format_cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+fn write_delimited_row<'a>(out: &mut String, cells: impl Iterator<Item = &'a str>, delimiter: char) {
+    for (i, cell) in cells.enumerate() {
+        if i > 0 {
+            out.push(delimiter);
+        }
+        // RFC 4180 quoting, which spreadsheets also accept for tab-separated files
+        if cell.contains([delimiter, '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&cell.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(cell);
+        }
+    }
+    out.push('\n');
+}
+
 /// Find an array in the JSON response
 fn find_array(data: &Value) -> Option<&Vec<Value>> {
     // If it's already an array
@@ -125,25 +544,29 @@ fn find_array(data: &Value) -> Option<&Vec<Value>> {
     None
 }
 
-fn select_headers(obj: &serde_json::Map<String, Value>) -> Vec<String> {
-    if let Ok(columns) = env::var(TABLE_COLUMNS_ENV) {
-        let selected: Vec<String> = columns
-            .split(',')
-            .map(|col| col.trim())
-            .filter(|col| !col.is_empty())
-            .filter(|col| obj.contains_key(*col))
-            .map(|col| col.to_string())
-            .collect();
-        if !selected.is_empty() {
-            return selected;
-        }
-    }
-
-    let max_columns = env::var(TABLE_MAX_COLUMNS_ENV)
+fn max_columns_setting() -> Option<usize> {
+    env::var(TABLE_MAX_COLUMNS_ENV)
         .ok()
         .and_then(|value| value.parse::<usize>().ok())
         .filter(|value| *value > 0)
-        .unwrap_or(DEFAULT_MAX_TABLE_COLUMNS);
+}
+
+/// The `--columns` found in `obj`, or as many of its fields as fit
+fn select_headers(
+    obj: &serde_json::Map<String, Value>,
+    max_columns: usize,
+    requested: &[String],
+) -> Vec<String> {
+    let selected: Vec<String> = requested
+        .iter()
+        .map(|col| col.trim())
+        .filter(|col| !col.is_empty())
+        .filter(|col| object_field(obj, col).is_some())
+        .map(|col| col.to_string())
+        .collect();
+    if !selected.is_empty() {
+        return selected;
+    }
 
     if obj.len() <= max_columns {
         return obj.keys().cloned().collect();
@@ -261,6 +684,36 @@ fn truncate_string(value: &str, max_width: usize) -> String {
     }
 }
 
+/// Print an error to stderr and return the process exit code for it
+pub fn print_error(err: &anyhow::Error, format: ErrorFormat) -> i32 {
+    let api_error = err.chain().find_map(|e| e.downcast_ref::<ApiError>());
+    let exit_code = api_error.map(ApiError::exit_code).unwrap_or(EXIT_GENERAL);
+
+    match format {
+        ErrorFormat::Text => eprintln!("Error: {:?}", err),
+        ErrorFormat::Json => {
+            let value = match api_error {
+                Some(api_error) => {
+                    let mut value = api_error.to_json();
+                    value["error"]["message"] = json!(format!("{:#}", err));
+                    value
+                }
+                None => json!({
+                    "error": {
+                        "kind": "error",
+                        "exit_code": exit_code,
+                        "status": null,
+                        "message": format!("{:#}", err),
+                    }
+                }),
+            };
+            eprintln!("{}", value);
+        }
+    }
+
+    exit_code
+}
+
 /// Print success message
 pub fn print_success(message: &str) {
     println!("{} {}", "✓".green(), message);
@@ -341,4 +794,186 @@ mod tests {
             "[object]"
         );
     }
+
+    #[test]
+    fn csv_flattens_nested_objects_and_keeps_amounts_exact() {
+        let value = json!({
+            "invoices": [
+                {"reference": "INV-1", "total_value": "1200.50", "contact": {"name": "Acme, Inc."}},
+                {"reference": "INV-2", "total_value": "99.00", "paid": true, "items": [1, 2]}
+            ]
+        });
+        let csv = delimited(&value, ',', &TableOptions::default()).expect("rows");
+        assert_eq!(
+            csv,
+            "contact.name,items,paid,reference,total_value\n\
+             \"Acme, Inc.\",,,INV-1,1200.50\n\
+             ,\"[1,2]\",true,INV-2,99.00\n"
+        );
+    }
+
+    #[test]
+    fn delimited_quotes_special_characters() {
+        let value = json!([{"note": "say \"hi\"\nthen\tleave"}]);
+        assert_eq!(
+            delimited(&value, ',', &TableOptions::default()).unwrap(),
+            "note\n\"say \"\"hi\"\"\nthen\tleave\"\n"
+        );
+        assert_eq!(
+            delimited(&value, '\t', &TableOptions::default()).unwrap(),
+            "note\n\"say \"\"hi\"\"\nthen\tleave\"\n"
+        );
+    }
+
+    #[test]
+    fn delimited_prints_single_records_as_one_row() {
+        let value = json!({"company": {"name": "Acme", "address": {"town": "Leeds"}}});
+        assert_eq!(
+            delimited(&value, '\t', &TableOptions::default()).unwrap(),
+            "address.town\tname\nLeeds\tAcme\n"
+        );
+        assert!(delimited(&json!("text"), ',', &TableOptions::default()).is_none());
+        assert_eq!(delimited(&json!(["a,b", "c"]), ',', &TableOptions::default()).unwrap(), "\"a,b\"\nc\n");
+    }
+
+    #[test]
+    fn ndjson_prints_one_record_per_line() {
+        let value = json!({"bank_transactions": [{"id": 1}, {"id": 2, "note": "a\nb"}]});
+        assert_eq!(
+            ndjson_lines(&value, &TableOptions::default()),
+            vec![r#"{"id":1}"#, r#"{"id":2,"note":"a\nb"}"#]
+        );
+        assert!(ndjson_lines(&json!({"bank_transactions": []}), &TableOptions::default()).is_empty());
+        assert_eq!(
+            ndjson_lines(&json!({"company": {"name": "Acme"}}), &TableOptions::default()),
+            vec![r#"{"company":{"name":"Acme"}}"#]
+        );
+    }
+
+    #[test]
+    fn sort_key_parses_direction() {
+        let key: SortKey = "total_value:desc".parse().unwrap();
+        assert_eq!(key, SortKey { field: "total_value".to_string(), descending: true });
+        let key: SortKey = "contact.name".parse().unwrap();
+        assert!(!key.descending);
+        assert!("dated_on:sideways".parse::<SortKey>().is_err());
+        assert!(":desc".parse::<SortKey>().is_err());
+    }
+
+    #[test]
+    fn sorting_compares_decimal_strings_numerically() {
+        let value = json!({"invoices": [
+            {"reference": "A", "total_value": "99.50"},
+            {"reference": "B"},
+            {"reference": "C", "total_value": "1200.00"},
+            {"reference": "D", "total_value": "-5"}
+        ]});
+        let options = TableOptions {
+            sort_by: Some("total_value:desc".parse().unwrap()),
+            ..TableOptions::default()
+        };
+        assert_eq!(
+            delimited(&value, ',', &options).unwrap(),
+            "reference,total_value\nC,1200.00\nA,99.50\nD,-5\nB,\n"
+        );
+    }
+
+    #[test]
+    fn totals_sum_amounts_exactly() {
+        let records = [json!({"reference": "A", "amount": "0.10", "gross_value": 2}),
+            json!({"reference": "B", "amount": "0.2"}),
+            json!({"reference": "C", "amount": "-1200.005"})];
+        let records: Vec<&Value> = records.iter().collect();
+        let headers = ["reference".to_string(), "amount".to_string(), "gross_value".to_string()];
+        let totals = ["amount".to_string(), "gross_value".to_string()];
+        assert_eq!(
+            totals_row(&headers, &records, &totals),
+            vec!["Total", "-1199.705", "2"]
+        );
+    }
+
+    #[test]
+    fn table_honours_columns_and_adds_footer() {
+        let value = json!({"bills": [
+            {"id": 1, "reference": "B-1", "total_value": "10.00", "status": "Open"},
+            {"id": 2, "reference": "B-2", "total_value": "2.50", "status": "Paid"}
+        ]});
+        let options = TableOptions {
+            columns: vec!["reference".to_string()],
+            sort_by: Some("total_value".parse().unwrap()),
+            totals: vec!["total_value".to_string()],
+        };
+        let rendered = table(&value, &options).expect("table").to_string();
+        assert!(!rendered.contains("status"));
+        assert!(rendered.find("B-2").unwrap() < rendered.find("B-1").unwrap());
+        assert!(rendered.contains("Total"));
+        assert!(rendered.contains("12.50"));
+    }
+
+    #[test]
+    fn table_columns_reach_into_nested_objects() {
+        let value = json!({"invoices": [
+            {"reference": "INV-1", "contact": {"name": "Acme"}, "status": "Open"},
+            {"reference": "INV-2", "contact": {"name": "Bob"}, "status": "Paid"}
+        ]});
+        let options = TableOptions {
+            columns: vec!["reference".to_string(), "contact.name".to_string()],
+            ..Default::default()
+        };
+        let rendered = table(&value, &options).expect("table").to_string();
+        assert!(rendered.contains("contact.name"));
+        assert!(rendered.contains("Acme") && rendered.contains("Bob"));
+        assert!(!rendered.contains("status"));
+    }
+
+    #[test]
+    fn table_keeps_columns_the_first_record_lacks() {
+        let value = json!({"invoices": [
+            {"reference": "INV-1", "due_on": "2024-02-01"},
+            {"reference": "INV-2", "due_on": "2024-02-15", "paid_on": "2024-02-10", "paid_value": "50.00"},
+            {"reference": "INV-3", "due_on": "2024-03-01", "paid_value": "25.50"}
+        ]});
+        let options = TableOptions {
+            columns: vec!["due_on".to_string(), "paid_on".to_string()],
+            totals: vec!["paid_value".to_string()],
+            ..Default::default()
+        };
+        let rendered = table(&value, &options).expect("table").to_string();
+        assert!(rendered.contains("paid_on") && rendered.contains("2024-02-10"));
+        assert!(rendered.contains("paid_value") && rendered.contains("75.50"));
+    }
+
+    #[test]
+    fn record_view_lists_fields_and_item_sub_tables() {
+        let value = json!({"invoice": {
+            "reference": "INV-7",
+            "total_value": "120.00",
+            "contact": {"name": "Acme"},
+            "tags": ["a", "b"],
+            "comments": "A long comment that would be truncated in a list but is shown in full here",
+            "invoice_items": [
+                {"description": "Consulting", "price": "100.00", "quantity": "1.0"},
+                {"description": "Travel", "price": "20.00"}
+            ]
+        }});
+        let text = record_view(&value, &TableOptions::default()).expect("record");
+        assert!(text.contains("Field") && text.contains("Value"));
+        assert!(text.contains("contact.name") && text.contains("Acme"));
+        assert!(text.contains("a, b"));
+        assert!(text.contains("shown in full here"));
+        assert!(!text.contains("[2 items]"));
+
+        let (record, items) = text.split_once("invoice_items (2)").expect("sub-table title");
+        assert!(record.contains("INV-7") && !record.contains("Consulting"));
+        assert!(items.contains("description") && items.contains("quantity"));
+        assert!(items.contains("Consulting") && items.contains("Travel"));
+
+        let options = TableOptions {
+            columns: vec!["reference".to_string()],
+            ..TableOptions::default()
+        };
+        let text = record_view(&value, &options).unwrap();
+        assert!(text.contains("INV-7") && !text.contains("120.00"));
+        assert!(record_view(&json!([1, 2]), &options).is_none());
+    }
 }
@@ -0,0 +1,379 @@
+//! [JMESPath](https://jmespath.org) queries over JSON responses, used by
+//! `--query`.
+//!
+//! Expressions are evaluated by the `jmespath` crate with its standard
+//! functions. FreeAgent returns amounts as strings such as `"1200.50"`, so
+//! `sum`, `avg`, `max`, `min`, `sort`, `sort_by`, `max_by` and `min_by` are
+//! replaced by versions that also accept numeric strings. They add and order
+//! these values exactly, using the same decimals as table totals. Comparison
+//! operators follow the specification, so a string amount needs
+//! `to_number(...)` before `<` or `>`.
+
+use anyhow::{anyhow, Result};
+use jmespath::ast::Ast;
+use jmespath::functions::{ArgumentType, CustomFunction, Signature};
+use jmespath::{
+    interpret, Context, ErrorReason, Expression, JmespathError, Rcvar, Runtime, RuntimeError,
+    SearchResult, Variable,
+};
+use serde_json::{Number, Value};
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::LazyLock;
+
+use crate::decimal::Decimal;
+
+/// Standard functions plus the decimal-aware replacements described above
+static RUNTIME: LazyLock<Runtime> = LazyLock::new(|| {
+    let mut runtime = Runtime::new();
+    runtime.register_builtin_functions();
+    register_decimal_functions(&mut runtime);
+    runtime
+});
+
+/// A parsed query expression
+#[derive(Clone)]
+pub struct Query {
+    expr: Expression<'static>,
+}
+
+impl Query {
+    /// Parse an expression such as `invoices[?status=='Overdue'].reference`
+    pub fn parse(source: &str) -> Result<Self> {
+        let expr = RUNTIME
+            .compile(source)
+            .map_err(|err| anyhow!("Invalid query '{}': {}", source, err))?;
+        if let Some(name) = unknown_function(expr.as_ast()) {
+            return Err(anyhow!("Invalid query '{}': unknown function '{}'", source, name));
+        }
+        Ok(Self { expr })
+    }
+
+    /// The expression as written
+    pub fn as_str(&self) -> &str {
+        self.expr.as_str()
+    }
+
+    /// Evaluate the query against `data`
+    pub fn apply(&self, data: &Value) -> Result<Value> {
+        let result = self
+            .expr
+            .search(data)
+            .map_err(|err| anyhow!("Query '{}' failed: {}", self.as_str(), err))?;
+        serde_json::to_value(&*result).map_err(Into::into)
+    }
+}
+
+impl fmt::Debug for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Query").field(&self.as_str()).finish()
+    }
+}
+
+/// The first function called in `ast` that the runtime doesn't provide, so
+/// a typo fails before any request is made
+fn unknown_function(ast: &Ast) -> Option<String> {
+    let children: Vec<&Ast> = match ast {
+        Ast::Function { name, args, .. } => {
+            if RUNTIME.get_function(name).is_none() {
+                return Some(name.clone());
+            }
+            args.iter().collect()
+        }
+        Ast::Comparison { lhs, rhs, .. }
+        | Ast::Projection { lhs, rhs, .. }
+        | Ast::And { lhs, rhs, .. }
+        | Ast::Or { lhs, rhs, .. }
+        | Ast::Subexpr { lhs, rhs, .. } => vec![lhs, rhs],
+        Ast::Condition { predicate, then, .. } => vec![predicate, then],
+        Ast::Expref { ast, .. } => vec![ast],
+        Ast::Flatten { node, .. } | Ast::Not { node, .. } | Ast::ObjectValues { node, .. } => {
+            vec![node]
+        }
+        Ast::MultiList { elements, .. } => elements.iter().collect(),
+        Ast::MultiHash { elements, .. } => elements.iter().map(|pair| &pair.value).collect(),
+        Ast::Identity { .. }
+        | Ast::Field { .. }
+        | Ast::Index { .. }
+        | Ast::Literal { .. }
+        | Ast::Slice { .. } => Vec::new(),
+    };
+    children.into_iter().find_map(unknown_function)
+}
+
+fn register_decimal_functions(runtime: &mut Runtime) {
+    let amounts = || {
+        ArgumentType::TypedArray(Box::new(ArgumentType::Union(vec![
+            ArgumentType::Number,
+            ArgumentType::String,
+        ])))
+    };
+    let function = |inputs: Vec<ArgumentType>, f: fn(&[Rcvar], &mut Context<'_>) -> SearchResult| {
+        Box::new(CustomFunction::new(Signature::new(inputs, None), Box::new(f)))
+    };
+
+    runtime.register_function("sum", function(vec![amounts()], sum));
+    runtime.register_function("avg", function(vec![amounts()], avg));
+    runtime.register_function("max", function(vec![amounts()], max));
+    runtime.register_function("min", function(vec![amounts()], min));
+    runtime.register_function("sort", function(vec![amounts()], sort));
+    let by = || vec![ArgumentType::Array, ArgumentType::Expref];
+    runtime.register_function("sort_by", function(by(), sort_by));
+    runtime.register_function("max_by", function(by(), max_by));
+    runtime.register_function("min_by", function(by(), min_by));
+}
+
+fn sum(args: &[Rcvar], ctx: &mut Context<'_>) -> SearchResult {
+    let total = total(&args[0], ctx)?;
+    Ok(number(total.to_f64()))
+}
+
+fn avg(args: &[Rcvar], ctx: &mut Context<'_>) -> SearchResult {
+    let count = args[0].as_array().map_or(0, Vec::len);
+    if count == 0 {
+        return Ok(Rcvar::new(Variable::Null));
+    }
+    let total = total(&args[0], ctx)?;
+    Ok(number(total.to_f64() / count as f64))
+}
+
+fn max(args: &[Rcvar], _ctx: &mut Context<'_>) -> SearchResult {
+    Ok(extreme(array(&args[0]), |values| values.max_by(|a, b| compare(a, b))))
+}
+
+fn min(args: &[Rcvar], _ctx: &mut Context<'_>) -> SearchResult {
+    Ok(extreme(array(&args[0]), |values| values.min_by(|a, b| compare(a, b))))
+}
+
+fn sort(args: &[Rcvar], _ctx: &mut Context<'_>) -> SearchResult {
+    let mut values = array(&args[0]).to_vec();
+    values.sort_by(|a, b| compare(a, b));
+    Ok(Rcvar::new(Variable::Array(values)))
+}
+
+fn sort_by(args: &[Rcvar], ctx: &mut Context<'_>) -> SearchResult {
+    let mut keyed = keyed(args, ctx)?;
+    keyed.sort_by(|a, b| compare(&a.1, &b.1));
+    Ok(Rcvar::new(Variable::Array(
+        keyed.into_iter().map(|(value, _)| value).collect(),
+    )))
+}
+
+fn max_by(args: &[Rcvar], ctx: &mut Context<'_>) -> SearchResult {
+    let keyed = keyed(args, ctx)?;
+    Ok(keyed
+        .into_iter()
+        .max_by(|a, b| compare(&a.1, &b.1))
+        .map_or_else(|| Rcvar::new(Variable::Null), |(value, _)| value))
+}
+
+fn min_by(args: &[Rcvar], ctx: &mut Context<'_>) -> SearchResult {
+    let keyed = keyed(args, ctx)?;
+    Ok(keyed
+        .into_iter()
+        .min_by(|a, b| compare(&a.1, &b.1))
+        .map_or_else(|| Rcvar::new(Variable::Null), |(value, _)| value))
+}
+
+fn array(value: &Rcvar) -> &[Rcvar] {
+    value.as_array().map_or(&[], Vec::as_slice)
+}
+
+fn extreme<'a>(
+    values: &'a [Rcvar],
+    pick: impl FnOnce(std::slice::Iter<'a, Rcvar>) -> Option<&'a Rcvar>,
+) -> Rcvar {
+    pick(values.iter()).cloned().unwrap_or_else(|| Rcvar::new(Variable::Null))
+}
+
+/// Each element of the first argument paired with the second argument, an
+/// `&expression`, evaluated against it
+fn keyed(args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Vec<(Rcvar, Rcvar)>, JmespathError> {
+    let Some(ast) = args[1].as_expref() else {
+        return Err(invalid_type(ctx, "expref", &args[1], 1));
+    };
+    array(&args[0])
+        .iter()
+        .map(|value| Ok((value.clone(), interpret(value, ast, ctx)?)))
+        .collect()
+}
+
+/// Exact sum of numbers and numeric strings
+fn total(values: &Rcvar, ctx: &Context<'_>) -> Result<Decimal, JmespathError> {
+    array(values).iter().try_fold(Decimal::ZERO, |total, value| {
+        decimal(value)
+            .map(|amount| total.add(amount))
+            .ok_or_else(|| invalid_type(ctx, "array[number|numeric string]", value, 0))
+    })
+}
+
+/// Numbers and numeric strings compare as exact decimals; anything else
+/// falls back to the standard ordering, which sorts other strings lexically
+fn compare(a: &Variable, b: &Variable) -> Ordering {
+    match (decimal(a), decimal(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+fn decimal(value: &Variable) -> Option<Decimal> {
+    match value {
+        Variable::String(s) => Decimal::from_value(&Value::String(s.clone())),
+        Variable::Number(n) => Decimal::from_value(&Value::Number(n.clone())),
+        _ => None,
+    }
+}
+
+fn number(value: f64) -> Rcvar {
+    Rcvar::new(Number::from_f64(value).map_or(Variable::Null, Variable::Number))
+}
+
+fn invalid_type(ctx: &Context<'_>, expected: &str, actual: &Variable, position: usize) -> JmespathError {
+    let reason = ErrorReason::Runtime(RuntimeError::InvalidType {
+        expected: expected.to_string(),
+        actual: actual.get_type().to_string(),
+        position,
+    });
+    JmespathError::from_ctx(ctx, reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn query(expr: &str, data: &Value) -> Value {
+        Query::parse(expr)
+            .expect("query should parse")
+            .apply(data)
+            .expect("query should evaluate")
+    }
+
+    fn invoices() -> Value {
+        json!({
+            "invoices": [
+                {"reference": "INV-1", "status": "Overdue", "total_value": "1200.50", "dated_on": "2024-03-01",
+                 "contact": {"name": "Acme"}, "items": [{"price": "1"}, {"price": "2"}]},
+                {"reference": "INV-2", "status": "Paid", "total_value": "99.00", "dated_on": "2024-01-15",
+                 "contact": {"name": "Bob"}, "items": []},
+                {"reference": "INV-3", "status": "Overdue", "total_value": "15.25", "dated_on": "2024-02-10",
+                 "contact": {"name": "Cy"}, "items": [{"price": "3"}]}
+            ]
+        })
+    }
+
+    #[test]
+    fn selects_fields_and_projections() {
+        let data = invoices();
+        assert_eq!(query("invoices[0].reference", &data), json!("INV-1"));
+        assert_eq!(query("invoices[-1].contact.name", &data), json!("Cy"));
+        assert_eq!(
+            query("invoices[].reference", &data),
+            json!(["INV-1", "INV-2", "INV-3"])
+        );
+        assert_eq!(query("invoices[*].contact.name | [1]", &data), json!("Bob"));
+        assert_eq!(query("invoices[1:].reference", &data), json!(["INV-2", "INV-3"]));
+        assert_eq!(query("invoices[::-1].reference | [0]", &data), json!("INV-3"));
+        assert_eq!(
+            query("invoices[].items[].price", &data),
+            json!(["1", "2", "3"])
+        );
+        assert_eq!(query("invoices[0].*", &data).as_array().map(Vec::len), Some(6));
+        assert_eq!(query("missing.field", &data), Value::Null);
+    }
+
+    #[test]
+    fn filters_and_multiselects() {
+        let data = invoices();
+        assert_eq!(
+            query("invoices[?status=='Overdue'].{ref:reference,total:total_value}", &data),
+            json!([
+                {"ref": "INV-1", "total": "1200.50"},
+                {"ref": "INV-3", "total": "15.25"}
+            ])
+        );
+        assert_eq!(
+            query("invoices[?status=='Overdue' && to_number(total_value) > `100`].reference", &data),
+            json!(["INV-1"])
+        );
+        assert_eq!(
+            query("invoices[?status=='Paid' || !(status == 'Paid')].reference", &data),
+            json!(["INV-1", "INV-2", "INV-3"])
+        );
+        assert_eq!(
+            query("invoices[?to_number(total_value) == `99`].[reference, status]", &data),
+            json!([["INV-2", "Paid"]])
+        );
+        assert_eq!(query("invoices[?length(items) > `1`] | length(@)", &data), json!(1));
+    }
+
+    #[test]
+    fn evaluates_functions() {
+        let data = invoices();
+        assert_eq!(
+            query("sort_by(invoices, &dated_on)[].reference", &data),
+            json!(["INV-2", "INV-3", "INV-1"])
+        );
+        assert_eq!(
+            query("max_by(invoices, &to_number(total_value)).reference", &data),
+            json!("INV-1")
+        );
+        assert_eq!(
+            query("join(', ', invoices[?starts_with(reference, 'INV')].contact.name)", &data),
+            json!("Acme, Bob, Cy")
+        );
+        assert_eq!(query("invoices[0].to_number(total_value)", &data), json!(1200.5));
+        assert_eq!(query("not_null(missing, `\"fallback\"`)", &data), json!("fallback"));
+        assert_eq!(query("type(invoices)", &data), json!("array"));
+        assert_eq!(query("contains(invoices[].status, 'Paid')", &data), json!(true));
+    }
+
+    #[test]
+    fn aggregates_amount_strings_exactly() {
+        let data = json!({"bills": [
+            {"reference": "B1", "total_value": "0.10"},
+            {"reference": "B2", "total_value": "0.20"},
+            {"reference": "B3", "total_value": "99.00"},
+            {"reference": "B4", "total_value": "1200.50"}
+        ]});
+        assert_eq!(query("sum(bills[].total_value)", &data), json!(1299.8));
+        assert_eq!(query("sum(bills[:2].total_value)", &data), json!(0.3));
+        assert_eq!(query("avg(bills[:2].total_value)", &data), json!(0.15));
+        assert_eq!(query("max(bills[].total_value)", &data), json!("1200.50"));
+        assert_eq!(query("min(bills[].total_value)", &data), json!("0.10"));
+        assert_eq!(
+            query("sort(bills[].total_value)", &data),
+            json!(["0.10", "0.20", "99.00", "1200.50"])
+        );
+        assert_eq!(
+            query("sort_by(bills, &total_value)[-1].reference", &data),
+            json!("B4")
+        );
+        assert_eq!(query("min_by(bills, &total_value).reference", &data), json!("B1"));
+        assert_eq!(query("avg(`[]`)", &data), Value::Null);
+        assert_eq!(query("sum(`[1, 2.5]`)", &data), json!(3.5));
+        assert_eq!(query("sort(`[\"b\", \"a\"]`)", &data), json!(["a", "b"]));
+    }
+
+    #[test]
+    fn reports_invalid_expressions() {
+        for (expr, message) in [
+            ("invoices[", "Invalid query"),
+            ("invoices[?status==]", "Invalid query"),
+            ("nope(invoices)", "unknown function 'nope'"),
+            ("length(reverse(nope(a)))", "unknown function 'nope'"),
+            ("a = b", "Invalid query"),
+            ("`unterminated", "Invalid query"),
+        ] {
+            let err = Query::parse(expr).unwrap_err().to_string();
+            assert!(err.contains(message), "{}: {}", expr, err);
+        }
+
+        let err = Query::parse("sum(invoices[].status)")
+            .unwrap()
+            .apply(&invoices())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Query 'sum(invoices[].status)' failed"), "{}", err);
+    }
+}
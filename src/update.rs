@@ -156,7 +156,7 @@ async fn perform_update(client: &Client, release: &Release) -> Result<()> {
         .context("Could not find a matching release asset for this platform")?;
 
     let tarball_bytes = download_bytes(client, &asset.browser_download_url).await?;
-    verify_checksum(client, release, &asset, &tarball_bytes).await?;
+    verify_checksum(client, release, asset, &tarball_bytes).await?;
 
     let temp_dir = TempDir::new()?;
     extract_tarball(&tarball_bytes, temp_dir.path())?;
@@ -0,0 +1,111 @@
+use assert_cmd::Command;
+use httpmock::Method::{GET, POST};
+use httpmock::MockServer;
+use serial_test::serial;
+use serde_json::json;
+use std::fs;
+use tempfile::tempdir;
+
+mod common;
+
+use common::{apply_env, write_tokens_file, EnvGuard};
+
+#[test]
+#[serial]
+fn api_command_sends_params_and_body_from_file() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v2/bill_items")
+            .query_param("bill", "42")
+            .header("Authorization", "Bearer test-access")
+            .json_body(json!({"bill_item": {"description": "Paper"}}));
+        then.status(201)
+            .json_body(json!({"bill_item": {"url": "https://api.freeagent.com/v2/bill_items/1"}}));
+    });
+
+    let body_file = temp_dir.path().join("body.json");
+    fs::write(&body_file, r#"{"bill_item": {"description": "Paper"}}"#).expect("write body");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2")).args([
+        "api",
+        "post",
+        "bill_items",
+        "--param",
+        "bill=42",
+        "--data",
+        &format!("@{}", body_file.display()),
+    ]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let result: serde_json::Value = serde_json::from_slice(&output).expect("stdout should be JSON");
+    assert_eq!(result["bill_item"]["url"], "https://api.freeagent.com/v2/bill_items/1");
+    mock.assert();
+}
+
+#[test]
+#[serial]
+fn api_command_prints_csv() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/invoices");
+        then.status(200).json_body(json!({"invoices": [
+            {"reference": "INV-1", "total_value": "1200.50", "contact_name": "Acme, Inc."},
+            {"reference": "INV-2", "total_value": "99.00", "contact_name": "Bob"}
+        ]}));
+    });
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2"))
+        .args(["api", "get", "invoices", "--format", "csv"]);
+    cmd.assert().success().stdout(
+        "contact_name,reference,total_value\n\"Acme, Inc.\",INV-1,1200.50\nBob,INV-2,99.00\n",
+    );
+}
+
+#[test]
+#[serial]
+fn api_command_never_sends_the_token_to_another_host() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    let foreign = MockServer::start();
+    let stolen = foreign.mock(|when, then| {
+        when.any_request();
+        then.status(200).json_body(json!({}));
+    });
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2"))
+        .args(["api", "get", &foreign.url("/steal")]);
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("Refusing to send credentials"));
+    stolen.assert_hits(0);
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2"))
+        .args(["api", "get", &server.url("/v2/company")]);
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/company");
+        then.status(200).json_body(json!({"company": {"name": "Acme"}}));
+    });
+    cmd.assert().success();
+}
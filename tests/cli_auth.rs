@@ -1,104 +1,17 @@
 use assert_cmd::Command;
 use chrono::{Duration, Utc};
-use directories::ProjectDirs;
+use httpmock::prelude::*;
 use predicates::prelude::*;
 use serial_test::serial;
 use serde_json::json;
 use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::Stdio;
 use tempfile::tempdir;
 
-struct EnvGuard {
-    home: Option<String>,
-    xdg_config_home: Option<String>,
-    appdata: Option<String>,
-    local_appdata: Option<String>,
-    userprofile: Option<String>,
-}
-
-impl EnvGuard {
-    fn new(temp_path: &str) -> Self {
-        let guard = Self {
-            home: std::env::var("HOME").ok(),
-            xdg_config_home: std::env::var("XDG_CONFIG_HOME").ok(),
-            appdata: std::env::var("APPDATA").ok(),
-            local_appdata: std::env::var("LOCALAPPDATA").ok(),
-            userprofile: std::env::var("USERPROFILE").ok(),
-        };
-
-        std::env::set_var("HOME", temp_path);
-        std::env::set_var("XDG_CONFIG_HOME", temp_path);
-        std::env::set_var("APPDATA", temp_path);
-        std::env::set_var("LOCALAPPDATA", temp_path);
-        std::env::set_var("USERPROFILE", temp_path);
-
-        guard
-    }
-}
-
-impl Drop for EnvGuard {
-    fn drop(&mut self) {
-        if let Some(value) = &self.home {
-            std::env::set_var("HOME", value);
-        } else {
-            std::env::remove_var("HOME");
-        }
-
-        if let Some(value) = &self.xdg_config_home {
-            std::env::set_var("XDG_CONFIG_HOME", value);
-        } else {
-            std::env::remove_var("XDG_CONFIG_HOME");
-        }
-
-        if let Some(value) = &self.appdata {
-            std::env::set_var("APPDATA", value);
-        } else {
-            std::env::remove_var("APPDATA");
-        }
-
-        if let Some(value) = &self.local_appdata {
-            std::env::set_var("LOCALAPPDATA", value);
-        } else {
-            std::env::remove_var("LOCALAPPDATA");
-        }
-
-        if let Some(value) = &self.userprofile {
-            std::env::set_var("USERPROFILE", value);
-        } else {
-            std::env::remove_var("USERPROFILE");
-        }
-    }
-}
-
-fn apply_env(cmd: &mut Command, temp_path: &str) {
-    cmd.env("HOME", temp_path)
-        .env("XDG_CONFIG_HOME", temp_path)
-        .env("APPDATA", temp_path)
-        .env("LOCALAPPDATA", temp_path)
-        .env("USERPROFILE", temp_path)
-        .env("NO_COLOR", "1");
-}
-
-fn token_file_path() -> std::path::PathBuf {
-    ProjectDirs::from("com", "freeagent", "freeagent-cli")
-        .expect("project dirs")
-        .config_dir()
-        .join("tokens.json")
-}
+mod common;
 
-fn write_tokens_file(path: &std::path::Path) {
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).expect("create config dir");
-    }
-    let now = Utc::now();
-    let payload = json!({
-        "access_token": "test-access",
-        "refresh_token": "test-refresh",
-        "expires_at": (now + Duration::hours(1)).to_rfc3339(),
-        "sandbox": false,
-        "last_refreshed": now.to_rfc3339(),
-    });
-    fs::write(path, serde_json::to_string_pretty(&payload).unwrap()).expect("write tokens");
-}
+use common::{apply_env, token_file_path, write_tokens_file, EnvGuard};
 
 #[test]
 #[serial]
@@ -122,8 +35,7 @@ fn status_reports_logged_in_when_tokens_present() {
     let temp_path = temp_dir.path().to_str().expect("temp path");
     let _guard = EnvGuard::new(temp_path);
 
-    let token_path = token_file_path();
-    write_tokens_file(&token_path);
+    write_tokens_file();
 
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
     apply_env(&mut cmd, temp_path);
@@ -142,7 +54,7 @@ fn logout_deletes_tokens_file() {
     let _guard = EnvGuard::new(temp_path);
 
     let token_path = token_file_path();
-    write_tokens_file(&token_path);
+    write_tokens_file();
     assert!(token_path.exists());
 
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
@@ -154,3 +66,443 @@ fn logout_deletes_tokens_file() {
 
     assert!(!token_path.exists());
 }
+
+#[test]
+#[serial]
+fn plaintext_tokens_migrate_to_encrypted_store() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+
+    let token_path = token_file_path();
+    write_tokens_file();
+    let encrypted_path = token_path.with_file_name("tokens.enc");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_TOKEN_STORE", "encrypted")
+        .env("FREEAGENT_TOKEN_PASSPHRASE", "correct horse")
+        .arg("status");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Token store: encrypted"))
+        .stderr(predicate::str::contains("Moved tokens"));
+
+    assert!(!token_path.exists());
+    let encrypted = fs::read_to_string(&encrypted_path).expect("encrypted tokens written");
+    assert!(!encrypted.contains("test-access"));
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_TOKEN_STORE", "encrypted")
+        .env("FREEAGENT_TOKEN_PASSPHRASE", "wrong")
+        .arg("status");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("passphrase"));
+}
+
+#[test]
+#[serial]
+fn login_no_browser_exchanges_pasted_redirect_url_with_pkce() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+
+    let server = MockServer::start();
+    let token_mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/token")
+            .body_contains("code=pasted-code")
+            .body_contains("code_verifier=")
+            .body_contains("redirect_uri=http%3A%2F%2Flocalhost%3A8484%2Fcallback");
+        then.status(200).json_body(json!({
+            "access_token": "new-access",
+            "refresh_token": "new-refresh",
+            "token_type": "bearer",
+            "expires_in": 3600
+        }));
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/company");
+        then.status(200)
+            .json_body(json!({ "company": { "name": "Acme Ltd" } }));
+    });
+
+    // The state is random, so read it from the printed URL before answering
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin!("freeagent"))
+        .envs([
+            ("HOME", temp_path),
+            ("XDG_CONFIG_HOME", temp_path),
+            ("APPDATA", temp_path),
+            ("LOCALAPPDATA", temp_path),
+            ("USERPROFILE", temp_path),
+            ("FREEAGENT_TOKEN_STORE", "file"),
+            ("NO_COLOR", "1"),
+            ("FREEAGENT_CLIENT_ID", "client-id"),
+            ("FREEAGENT_CLIENT_SECRET", "client-secret"),
+        ])
+        .env("FREEAGENT_TOKEN_URL", server.url("/token"))
+        .env("FREEAGENT_API_URL", server.url("/v2"))
+        .args(["login", "--no-browser"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("spawn login");
+    let mut stdout = BufReader::new(child.stdout.take().expect("stdout"));
+    let auth_url = (&mut stdout)
+        .lines()
+        .map(|line| line.expect("read stdout"))
+        .find(|line| line.contains("state="))
+        .expect("authorization URL");
+    let auth_url = url::Url::parse(&auth_url).expect("authorization URL parses");
+    assert!(auth_url.query_pairs().any(|(key, _)| key == "code_challenge"));
+    let state = auth_url
+        .query_pairs()
+        .find(|(key, _)| key == "state")
+        .map(|(_, value)| value.into_owned())
+        .expect("state parameter");
+
+    let mut stdin = child.stdin.take().expect("stdin");
+    writeln!(stdin, "http://localhost:8484/callback?code=pasted-code&state={}", state)
+        .expect("write redirect URL");
+    drop(stdin);
+    let mut rest = String::new();
+    stdout.read_to_string(&mut rest).expect("read stdout");
+    assert!(child.wait().expect("wait for login").success());
+    assert!(rest.contains("Paste the full URL"));
+    assert!(rest.contains("Successfully authenticated"));
+
+    token_mock.assert();
+    let saved = fs::read_to_string(token_file_path()).expect("tokens saved");
+    assert!(saved.contains("new-access"));
+}
+
+#[test]
+#[serial]
+fn login_no_browser_rejects_a_bare_code() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_CLIENT_ID", "client-id")
+        .env("FREEAGENT_CLIENT_SECRET", "client-secret")
+        .args(["login", "--no-browser"])
+        .write_stdin("pasted-code\n");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("a bare code cannot be used"));
+
+    assert!(!token_file_path().exists());
+}
+
+#[test]
+#[serial]
+fn login_no_browser_rejects_mismatched_state() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_CLIENT_ID", "client-id")
+        .env("FREEAGENT_CLIENT_SECRET", "client-secret")
+        .args(["login", "--no-browser"])
+        .write_stdin("http://localhost:8484/callback?code=abc&state=forged\n");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("State mismatch"));
+
+    assert!(!token_file_path().exists());
+}
+
+#[test]
+#[serial]
+fn env_refresh_token_authenticates_without_stored_tokens() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    let sink = temp_dir.path().join("ci/refresh-token");
+
+    let server = MockServer::start();
+    let token_mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/token")
+            .body_contains("grant_type=refresh_token")
+            .body_contains("refresh_token=ci-refresh");
+        then.status(200).json_body(json!({
+            "access_token": "ci-access",
+            "refresh_token": "ci-refresh-rotated",
+            "token_type": "bearer",
+            "expires_in": 3600
+        }));
+    });
+    let company_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v2/company")
+            .header("authorization", "Bearer ci-access");
+        then.status(200)
+            .json_body(json!({ "company": { "name": "Acme Ltd" } }));
+    });
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_CLIENT_ID", "client-id")
+        .env("FREEAGENT_CLIENT_SECRET", "client-secret")
+        .env("FREEAGENT_TOKEN_URL", server.url("/token"))
+        .env("FREEAGENT_API_URL", server.url("/v2"))
+        .env("FREEAGENT_REFRESH_TOKEN", "ci-refresh")
+        .env("FREEAGENT_TOKEN_SINK", &sink)
+        .args(["company", "get"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Acme Ltd"));
+
+    token_mock.assert();
+    company_mock.assert();
+    assert_eq!(
+        fs::read_to_string(&sink).expect("rotated token written"),
+        "ci-refresh-rotated\n"
+    );
+    assert!(!token_file_path().exists());
+}
+
+#[test]
+#[serial]
+fn stderr_token_sink_keeps_stdout_clean() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(POST).path("/token");
+        then.status(200).json_body(json!({
+            "access_token": "ci-access",
+            "refresh_token": "ci-refresh-rotated",
+            "token_type": "bearer",
+            "expires_in": 3600
+        }));
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/company");
+        then.status(200)
+            .json_body(json!({ "company": { "name": "Acme Ltd" } }));
+    });
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_CLIENT_ID", "client-id")
+        .env("FREEAGENT_CLIENT_SECRET", "client-secret")
+        .env("FREEAGENT_TOKEN_URL", server.url("/token"))
+        .env("FREEAGENT_API_URL", server.url("/v2"))
+        .env("FREEAGENT_REFRESH_TOKEN", "ci-refresh")
+        .env("FREEAGENT_TOKEN_SINK", "-")
+        .args(["company", "get", "--format", "compact"]);
+    cmd.assert()
+        .success()
+        .stdout("{\"company\":{\"name\":\"Acme Ltd\"}}\n")
+        .stderr(predicate::str::contains("FREEAGENT_REFRESH_TOKEN=ci-refresh-rotated"));
+}
+
+#[test]
+#[serial]
+fn concurrent_processes_refresh_tokens_once() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+
+    let token_path = token_file_path();
+    fs::create_dir_all(token_path.parent().unwrap()).expect("create config dir");
+    let expired = Utc::now() - Duration::hours(1);
+    let payload = json!({
+        "access_token": "stale-access",
+        "refresh_token": "old-refresh",
+        "expires_at": expired.to_rfc3339(),
+        "sandbox": false,
+        "last_refreshed": expired.to_rfc3339(),
+    });
+    fs::write(&token_path, payload.to_string()).expect("write tokens");
+
+    let server = MockServer::start();
+    // FreeAgent rotates refresh tokens, so only the first refresh could succeed
+    let token_mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/token")
+            .body_contains("refresh_token=old-refresh");
+        then.status(200)
+            .delay(std::time::Duration::from_millis(500))
+            .json_body(json!({
+                "access_token": "fresh-access",
+                "refresh_token": "new-refresh",
+                "token_type": "bearer",
+                "expires_in": 3600
+            }));
+    });
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/v2/company")
+            .header("authorization", "Bearer fresh-access");
+        then.status(200)
+            .json_body(json!({ "company": { "name": "Acme Ltd" } }));
+    });
+
+    let children: Vec<_> = (0..2)
+        .map(|_| {
+            let mut cmd = std::process::Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+            cmd.env("HOME", temp_path)
+                .env("XDG_CONFIG_HOME", temp_path)
+                .env("APPDATA", temp_path)
+                .env("LOCALAPPDATA", temp_path)
+                .env("USERPROFILE", temp_path)
+                .env("FREEAGENT_TOKEN_STORE", "file")
+                .env("NO_COLOR", "1")
+                .env("FREEAGENT_CLIENT_ID", "client-id")
+                .env("FREEAGENT_CLIENT_SECRET", "client-secret")
+                .env("FREEAGENT_TOKEN_URL", server.url("/token"))
+                .env("FREEAGENT_API_URL", server.url("/v2"))
+                .args(["company", "get"])
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .spawn()
+                .expect("spawn freeagent")
+        })
+        .collect();
+    for mut child in children {
+        assert!(child.wait().expect("wait for freeagent").success());
+    }
+
+    token_mock.assert_hits(1);
+    let saved = fs::read_to_string(&token_path).expect("tokens saved");
+    assert!(saved.contains("new-refresh"));
+}
+
+#[test]
+#[serial]
+fn status_json_reports_expiry_user_and_company() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/users/me");
+        then.status(200)
+            .json_body(json!({ "user": { "email": "ada@example.com" } }));
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/company");
+        then.status(200)
+            .json_body(json!({ "company": { "name": "Acme Ltd" } }));
+    });
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2"))
+        .args(["status", "--format", "json"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let status: serde_json::Value = serde_json::from_slice(&output).expect("status is JSON");
+
+    assert_eq!(status["logged_in"], true);
+    assert_eq!(status["environment"], "production");
+    assert_eq!(status["expired"], false);
+    assert!(status["expires_at"].is_string());
+    assert!(status["last_refreshed"].is_string());
+    assert_eq!(status["user"]["email"], "ada@example.com");
+    assert_eq!(status["company"]["name"], "Acme Ltd");
+}
+
+#[test]
+#[serial]
+fn auth_export_and_import_move_tokens_between_machines() {
+    let source_dir = tempdir().expect("temp dir");
+    let source_path = source_dir.path().to_str().expect("temp path");
+    let target_dir = tempdir().expect("temp dir");
+    let target_path = target_dir.path().to_str().expect("temp path");
+
+    let bundle_path = source_dir.path().join("bundle.json");
+    {
+        let _guard = EnvGuard::new(source_path);
+        write_tokens_file();
+
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+        apply_env(&mut cmd, source_path);
+        cmd.env("FREEAGENT_TOKEN_PASSPHRASE", "correct horse")
+            .args(["auth", "export", "--encrypt"]);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let bundle = String::from_utf8(output).expect("utf8 bundle");
+        assert!(bundle.contains("ciphertext"));
+        assert!(!bundle.contains("test-refresh"));
+        fs::write(&bundle_path, bundle).expect("write bundle");
+    }
+
+    let _guard = EnvGuard::new(target_path);
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/company");
+        then.status(200)
+            .json_body(json!({ "company": { "name": "Acme Ltd" } }));
+    });
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, target_path);
+    cmd.env("FREEAGENT_TOKEN_PASSPHRASE", "correct horse")
+        .env("FREEAGENT_API_URL", server.url("/v2"))
+        .args(["--profile", "laptop", "auth", "import"])
+        .arg(&bundle_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Imported production tokens into profile 'laptop'"));
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, target_path);
+    cmd.args(["--profile", "laptop", "auth", "token", "--print"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::eq("test-access\n"));
+}
+
+#[test]
+#[serial]
+fn response_cache_is_opt_in_and_cleared_on_logout() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    let company = server.mock(|when, then| {
+        when.method(GET).path("/v2/company");
+        then.status(200).json_body(json!({ "company": { "name": "Acme Ltd" } }));
+    });
+    let company_get = |env: &[(&str, &str)]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+        apply_env(&mut cmd, temp_path);
+        cmd.env("FREEAGENT_API_URL", server.url("/v2"))
+            .envs(env.iter().copied())
+            .args(["company", "get"]);
+        cmd.assert().success();
+    };
+
+    company_get(&[]);
+    company_get(&[]);
+    company.assert_hits(2);
+
+    company_get(&[("FREEAGENT_CACHE", "1")]);
+    company_get(&[("FREEAGENT_CACHE", "yes")]);
+    company.assert_hits(3);
+    company_get(&[("FREEAGENT_CACHE", "1"), ("FREEAGENT_NO_CACHE", "1")]);
+    company.assert_hits(4);
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.arg("logout");
+    cmd.assert().success();
+    write_tokens_file();
+
+    company_get(&[("FREEAGENT_CACHE", "1")]);
+    company.assert_hits(5);
+}
@@ -0,0 +1,128 @@
+use assert_cmd::Command;
+use httpmock::Method::GET;
+use httpmock::MockServer;
+use serial_test::serial;
+use serde_json::json;
+use std::fs;
+use tempfile::tempdir;
+
+mod common;
+
+use common::{apply_env, write_tokens_file, EnvGuard};
+
+#[test]
+#[serial]
+fn invoice_pdf_output_writes_decoded_file() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/invoices/7/pdf");
+        then.status(200).json_body(json!({"pdf": {"content": "JVBERi0xLjQ="}}));
+    });
+
+    let out_dir = temp_dir.path().join("pdfs");
+    fs::create_dir_all(&out_dir).expect("create output dir");
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2")).args([
+        "invoices",
+        "pdf",
+        "7",
+        "--output",
+        out_dir.to_str().unwrap(),
+    ]);
+    cmd.assert().success();
+
+    assert_eq!(fs::read(out_dir.join("invoice-7.pdf")).expect("pdf written"), b"%PDF-1.4");
+}
+
+#[test]
+#[serial]
+fn invoices_download_archives_every_invoice_in_range() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    let list = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v2/invoices")
+            .query_param("from_date", "2024-01-01")
+            .query_param("to_date", "2024-01-31");
+        then.status(200).json_body(json!({"invoices": [
+            {"url": "https://api.freeagent.com/v2/invoices/1", "reference": "INV/001", "dated_on": "2024-01-05"},
+            {"url": "https://api.freeagent.com/v2/invoices/2", "reference": "INV/002", "dated_on": "2024-01-20"}
+        ]}));
+    });
+    for id in ["1", "2"] {
+        server.mock(|when, then| {
+            when.method(GET).path(format!("/v2/invoices/{}/pdf", id));
+            then.status(200).json_body(json!({"pdf": {"content": "JVBERi0xLjQ="}}));
+        });
+    }
+
+    let out_dir = temp_dir.path().join("archive");
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2")).args([
+        "invoices",
+        "download",
+        "--from-date",
+        "2024-01-01",
+        "--to-date",
+        "2024-01-31",
+        "--output",
+        out_dir.to_str().unwrap(),
+    ]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let result: serde_json::Value = serde_json::from_slice(&output).expect("stdout should be JSON");
+    assert_eq!(result["downloaded"].as_array().map(Vec::len), Some(2));
+
+    list.assert();
+    assert!(out_dir.join("2024-01-05_INV_001.pdf").exists());
+    assert!(out_dir.join("2024-01-20_INV_002.pdf").exists());
+}
+
+#[test]
+#[serial]
+fn attachments_download_streams_content() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    let content_src = server.url("/storage/receipt.png?signature=abc");
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/attachments/5");
+        then.status(200).json_body(json!({"attachment": {
+            "url": "https://api.freeagent.com/v2/attachments/5",
+            "file_name": "receipt.png",
+            "content_src": content_src,
+        }}));
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/storage/receipt.png");
+        then.status(200).body("PNGDATA");
+    });
+
+    let out_dir = temp_dir.path().join("attachments");
+    fs::create_dir_all(&out_dir).expect("create output dir");
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2")).args([
+        "attachments",
+        "download",
+        "5",
+        "--output",
+        out_dir.to_str().unwrap(),
+    ]);
+    cmd.assert().success();
+
+    assert_eq!(fs::read(out_dir.join("receipt.png")).expect("file written"), b"PNGDATA");
+}
@@ -0,0 +1,44 @@
+use assert_cmd::Command;
+use httpmock::Method::POST;
+use httpmock::MockServer;
+use serial_test::serial;
+use serde_json::json;
+use tempfile::tempdir;
+
+mod common;
+
+use common::{apply_env, write_tokens_file, EnvGuard};
+
+#[test]
+#[serial]
+fn dry_run_prints_request_without_sending_it() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    let create = server.mock(|when, then| {
+        when.method(POST).path("/v2/invoices");
+        then.status(201).json_body(json!({"invoice": {}}));
+    });
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2")).args([
+        "--dry-run",
+        "invoices",
+        "create",
+        "--contact",
+        "https://api.freeagent.com/v2/contacts/1",
+        "--dated-on",
+        "2024-01-15",
+    ]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let result: serde_json::Value = serde_json::from_slice(&output).expect("stdout should be JSON");
+    assert_eq!(result["dry_run"], true);
+    assert_eq!(result["method"], "POST");
+    assert_eq!(result["url"], server.url("/v2/invoices"));
+    assert_eq!(result["body"]["invoice"]["dated_on"], "2024-01-15");
+    create.assert_hits(0);
+}
@@ -0,0 +1,107 @@
+use assert_cmd::Command;
+use httpmock::Method::{GET, POST};
+use httpmock::MockServer;
+use predicates::prelude::*;
+use serial_test::serial;
+use serde_json::json;
+use std::fs;
+use tempfile::tempdir;
+
+mod common;
+
+use common::{apply_env, write_tokens_file, EnvGuard};
+
+#[test]
+#[serial]
+fn not_found_exits_with_documented_code() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/invoices/999");
+        then.status(404)
+            .json_body(json!({"errors": {"error": {"message": "Resource not found"}}}));
+    });
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2"))
+        .args(["invoices", "get", "999"]);
+    cmd.assert()
+        .code(5)
+        .stderr(predicate::str::contains("Resource not found"));
+}
+
+#[test]
+#[serial]
+fn validation_errors_render_as_json() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(POST).path("/v2/invoices");
+        then.status(422).json_body(json!({
+            "errors": [{"message": "Dated on can't be blank", "field": "dated_on"}]
+        }));
+    });
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2")).args([
+        "--error-format",
+        "json",
+        "invoices",
+        "create",
+        "--contact",
+        "https://api.freeagent.com/v2/contacts/1",
+    ]);
+    let output = cmd.assert().code(6).get_output().stderr.clone();
+    let error: serde_json::Value =
+        serde_json::from_slice(&output).expect("stderr should be JSON");
+    assert_eq!(error["error"]["kind"], "validation");
+    assert_eq!(error["error"]["errors"][0]["field"], "dated_on");
+}
+
+#[test]
+#[serial]
+fn trace_logs_failed_request_without_secrets() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/invoices/999");
+        then.status(404)
+            .header("X-RateLimit-Remaining", "118")
+            .json_body(json!({"errors": {"error": {"message": "Resource not found"}}}));
+    });
+
+    let log_file = temp_dir.path().join("http.log");
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2")).args([
+        "--trace",
+        "--log-file",
+        log_file.to_str().unwrap(),
+        "invoices",
+        "get",
+        "999",
+    ]);
+    cmd.assert().code(5);
+
+    let log = fs::read_to_string(log_file).expect("log file written");
+    assert!(log.contains("> GET http://"));
+    assert!(log.contains("< 404 Not Found"));
+    assert!(log.contains("x-ratelimit-remaining: 118"));
+    assert!(log.contains("Bearer [REDACTED]"));
+    assert!(log.contains("Resource not found"));
+    assert!(!log.contains("test-access"));
+}
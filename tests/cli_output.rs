@@ -0,0 +1,231 @@
+use assert_cmd::Command;
+use httpmock::Method::GET;
+use httpmock::MockServer;
+use serial_test::serial;
+use serde_json::json;
+use tempfile::tempdir;
+
+mod common;
+
+use common::{apply_env, write_tokens_file, EnvGuard};
+
+#[test]
+#[serial]
+fn ndjson_streams_one_record_per_line_across_pages() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    let next = server.url("/v2/contacts-next?page=2");
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/contacts").query_param("per_page", "100");
+        then.status(200)
+            .header("Link", format!("<{}>; rel=\"next\"", next))
+            .json_body(json!({"contacts": [{"id": 1, "name": "Ada"}, {"id": 2, "name": "Bob"}]}));
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/contacts-next");
+        then.status(200)
+            .json_body(json!({"contacts": [{"id": 3, "name": "Cy"}]}));
+    });
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2"))
+        .args(["contacts", "list", "--all", "--format", "ndjson"]);
+    cmd.assert().success().stdout(
+        "{\"id\":1,\"name\":\"Ada\"}\n{\"id\":2,\"name\":\"Bob\"}\n{\"id\":3,\"name\":\"Cy\"}\n",
+    );
+}
+
+fn mock_invoices(server: &MockServer) {
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/invoices");
+        then.status(200).json_body(json!({"invoices": [
+            {"reference": "INV-1", "status": "Overdue", "total_value": "1200.50"},
+            {"reference": "INV-2", "status": "Paid", "total_value": "99.00"},
+            {"reference": "INV-3", "status": "Overdue", "total_value": "15.25"}
+        ]}));
+    });
+}
+
+#[test]
+#[serial]
+fn query_projects_rows_for_table_output() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    mock_invoices(&server);
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2")).args([
+        "invoices",
+        "list",
+        "--format",
+        "table",
+        "--query",
+        "invoices[?status=='Overdue'].{ref:reference,total:total_value}",
+    ]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let table = String::from_utf8(output).expect("utf8 table");
+    assert!(table.contains("ref") && table.contains("total"));
+    assert!(table.contains("INV-1") && table.contains("15.25"));
+    assert!(!table.contains("INV-2"));
+    assert!(!table.contains("status"));
+}
+
+#[test]
+#[serial]
+fn query_applies_to_csv_and_rejects_invalid_expressions() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    mock_invoices(&server);
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2"))
+        .args(["invoices", "list", "--format", "csv", "--query", "invoices[].reference"]);
+    cmd.assert().success().stdout("INV-1\nINV-2\nINV-3\n");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2"))
+        .args(["invoices", "list", "--query", "invoices[?status=="]);
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("Invalid query"));
+}
+
+#[test]
+#[serial]
+fn sort_by_orders_amounts_numerically_and_totals_add_a_footer() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    mock_invoices(&server);
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2")).args([
+        "invoices",
+        "list",
+        "--format",
+        "csv",
+        "--columns",
+        "reference,total_value",
+        "--sort-by",
+        "total_value:desc",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout("reference,total_value\nINV-1,1200.50\nINV-2,99.00\nINV-3,15.25\n");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2")).args([
+        "invoices",
+        "list",
+        "--format",
+        "table",
+        "--columns",
+        "reference",
+        "--totals",
+        "total_value",
+    ]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let table = String::from_utf8(output).expect("utf8 table");
+    assert!(!table.contains("status"));
+    assert!(table.contains("Total") && table.contains("1314.75"));
+}
+
+#[test]
+#[serial]
+fn table_shows_single_records_with_item_sub_tables() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/v2/invoices/7")
+            .query_param("nested_invoice_items", "true");
+        then.status(200).json_body(json!({"invoice": {
+            "reference": "INV-7",
+            "total_value": "120.00",
+            "invoice_items": [
+                {"description": "Consulting", "price": "100.00"},
+                {"description": "Travel", "price": "20.00"}
+            ]
+        }}));
+    });
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2")).args([
+        "invoices",
+        "get",
+        "7",
+        "--nested-invoice-items",
+        "--format",
+        "table",
+    ]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let text = String::from_utf8(output).expect("utf8 table");
+    assert!(!text.contains('{'));
+    assert!(text.contains("reference") && text.contains("INV-7"));
+    assert!(text.contains("invoice_items (2)"));
+    assert!(text.contains("Consulting") && text.contains("Travel"));
+}
+
+#[test]
+#[serial]
+fn table_lists_explanations_as_rows_not_a_record() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/bank_transaction_explanations");
+        then.status(200).json_body(json!({"bank_transaction_explanations": [
+            {"description": "Rent", "gross_value": "-950.00"},
+            {"description": "Sale", "gross_value": "120.50"}
+        ]}));
+    });
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2")).args([
+        "bank-transaction-explanations",
+        "list",
+        "--bank-account",
+        "https://api.freeagent.com/v2/bank_accounts/1",
+        "--format",
+        "table",
+        "--sort-by",
+        "gross_value:desc",
+        "--totals",
+        "gross_value",
+    ]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let table = String::from_utf8(output).expect("utf8 table");
+    assert!(!table.contains("Field") && !table.contains("bank_transaction_explanations ("));
+    assert!(table.find("Sale").unwrap() < table.find("Rent").unwrap());
+    assert!(table.contains("Total") && table.contains("-829.50"));
+}
@@ -0,0 +1,109 @@
+use assert_cmd::Command;
+use httpmock::Method::GET;
+use httpmock::MockServer;
+use predicates::prelude::*;
+use serial_test::serial;
+use serde_json::json;
+use std::fs;
+use tempfile::tempdir;
+
+mod common;
+
+use common::{apply_env, config_dir, write_tokens, EnvGuard};
+
+/// A production default profile plus a sandbox `acme` profile
+fn write_profiles() {
+    let dir = config_dir();
+    write_tokens(&dir.join("tokens.json"), "default-access", false);
+    write_tokens(&dir.join("profiles/acme/tokens.json"), "acme-access", true);
+    let config = json!({"profiles": {"acme": {"sandbox": true, "company": "Acme Ltd"}}});
+    fs::write(dir.join("config.json"), config.to_string()).expect("write config");
+}
+
+fn freeagent(temp_path: &str, server: &MockServer) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2"))
+        .env_remove("FREEAGENT_PROFILE");
+    cmd
+}
+
+#[test]
+#[serial]
+fn profile_selects_its_own_tokens_and_environment() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_profiles();
+
+    let server = MockServer::start();
+    let acme = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v2/invoices/1")
+            .header("Authorization", "Bearer acme-access");
+        then.status(200).json_body(json!({"invoice": {"reference": "ACME-1"}}));
+    });
+
+    // The sandbox profile works without --sandbox
+    freeagent(temp_path, &server)
+        .args(["--profile", "acme", "invoices", "get", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ACME-1"));
+
+    freeagent(temp_path, &server)
+        .env("FREEAGENT_PROFILE", "acme")
+        .args(["invoices", "get", "1"])
+        .assert()
+        .success();
+    acme.assert_hits(2);
+}
+
+#[test]
+#[serial]
+fn profiles_can_be_listed_switched_and_removed() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_profiles();
+    let server = MockServer::start();
+
+    let output = freeagent(temp_path, &server)
+        .args(["profiles", "list"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let listed: serde_json::Value = serde_json::from_slice(&output).expect("stdout should be JSON");
+    assert_eq!(
+        listed["profiles"],
+        json!([
+            {"name": "acme", "active": false, "logged_in": true, "sandbox": true, "company": "Acme Ltd"},
+            {"name": "default", "active": true, "logged_in": true, "sandbox": false, "company": null},
+        ])
+    );
+
+    freeagent(temp_path, &server)
+        .args(["profiles", "use", "acme"])
+        .assert()
+        .success();
+    freeagent(temp_path, &server)
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Profile: acme"))
+        .stdout(predicate::str::contains("Company: Acme Ltd"))
+        .stdout(predicate::str::contains("Environment: Sandbox"));
+
+    freeagent(temp_path, &server)
+        .args(["profiles", "remove", "acme", "--yes"])
+        .assert()
+        .success();
+    assert!(!config_dir().join("profiles/acme").exists());
+    freeagent(temp_path, &server)
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Profile: default"));
+}
@@ -0,0 +1,54 @@
+use assert_cmd::Command;
+use httpmock::Method::GET;
+use httpmock::MockServer;
+use predicates::prelude::*;
+use serial_test::serial;
+use serde_json::json;
+use std::fs;
+use tempfile::tempdir;
+
+mod common;
+
+use common::{apply_env, write_tokens_file, EnvGuard};
+
+#[test]
+#[serial]
+fn record_then_replay_company_get() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let cassette_dir = temp_dir.path().join("cassette");
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v2/company")
+            .header("Authorization", "Bearer test-access");
+        then.status(200).json_body(json!({"company": {"name": "Acme Ltd"}}));
+    });
+
+    let mut record = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut record, temp_path);
+    record
+        .env("FREEAGENT_API_URL", server.url("/v2"))
+        .args(["--record", cassette_dir.to_str().unwrap(), "company", "get"]);
+    record
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Acme Ltd"));
+    mock.assert_hits(1);
+
+    // Replay needs neither the server nor stored tokens
+    fs::remove_dir_all(temp_dir.path().join("freeagent-cli")).expect("remove tokens");
+    let mut replay = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut replay, temp_path);
+    replay
+        .env("FREEAGENT_API_URL", server.url("/v2"))
+        .args(["--replay", cassette_dir.to_str().unwrap(), "company", "get"]);
+    replay
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Acme Ltd"));
+    mock.assert_hits(1);
+}
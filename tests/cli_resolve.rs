@@ -0,0 +1,91 @@
+use assert_cmd::Command;
+use httpmock::Method::{GET, POST};
+use httpmock::MockServer;
+use predicates::prelude::*;
+use serial_test::serial;
+use serde_json::json;
+use tempfile::tempdir;
+
+mod common;
+
+use common::{apply_env, write_tokens_file, EnvGuard};
+
+fn contacts() -> serde_json::Value {
+    json!({"contacts": [
+        {"url": "https://api.freeagent.com/v2/contacts/1", "organisation_name": "Acme Ltd"},
+        {"url": "https://api.freeagent.com/v2/contacts/2", "organisation_name": "Acme Holdings"},
+        {"url": "https://api.freeagent.com/v2/contacts/3", "organisation_name": "Globex Corporation"},
+    ]})
+}
+
+#[test]
+#[serial]
+fn resolves_names_and_ids_to_urls() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/contacts").query_param("view", "all");
+        then.status(200).json_body(contacts());
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/projects/7");
+        then.status(200).json_body(json!({"project": {"url": "https://api.freeagent.com/v2/projects/7"}}));
+    });
+    let create = server.mock(|when, then| {
+        when.method(POST).path("/v2/invoices");
+        then.status(201).json_body(json!({"invoice": {}}));
+    });
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2")).args([
+        "--dry-run",
+        "invoices",
+        "create",
+        "--contact",
+        "globex",
+        "--project",
+        "7",
+    ]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let result: serde_json::Value = serde_json::from_slice(&output).expect("stdout should be JSON");
+    assert_eq!(
+        result["body"]["invoice"]["contact"],
+        "https://api.freeagent.com/v2/contacts/3"
+    );
+    assert_eq!(result["body"]["invoice"]["project"], server.url("/v2/projects/7"));
+    create.assert_hits(0);
+}
+
+#[test]
+#[serial]
+fn ambiguous_names_fail_without_sending_the_request() {
+    let temp_dir = tempdir().expect("temp dir");
+    let temp_path = temp_dir.path().to_str().expect("temp path");
+    let _guard = EnvGuard::new(temp_path);
+    write_tokens_file();
+
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/v2/contacts").query_param("view", "all");
+        then.status(200).json_body(contacts());
+    });
+    let create = server.mock(|when, then| {
+        when.method(POST).path("/v2/invoices");
+        then.status(201).json_body(json!({"invoice": {}}));
+    });
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("freeagent"));
+    apply_env(&mut cmd, temp_path);
+    cmd.env("FREEAGENT_API_URL", server.url("/v2"))
+        .args(["invoices", "create", "--contact", "acme"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("matches 2 contacts"))
+        .stderr(predicate::str::contains("Acme Holdings"));
+    create.assert_hits(0);
+}
@@ -0,0 +1,101 @@
+//! Fixtures shared by the CLI integration tests.
+//!
+//! Each test binary uses a different subset, hence the `dead_code` allowance.
+#![allow(dead_code)]
+
+use assert_cmd::Command;
+use chrono::{Duration, Utc};
+use directories::ProjectDirs;
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Points this process's home and config directories at `temp_path` until
+/// dropped, so [`config_dir`] matches what the spawned CLI sees
+pub struct EnvGuard {
+    home: Option<String>,
+    xdg_config_home: Option<String>,
+    appdata: Option<String>,
+    local_appdata: Option<String>,
+    userprofile: Option<String>,
+}
+
+impl EnvGuard {
+    pub fn new(temp_path: &str) -> Self {
+        let guard = Self {
+            home: std::env::var("HOME").ok(),
+            xdg_config_home: std::env::var("XDG_CONFIG_HOME").ok(),
+            appdata: std::env::var("APPDATA").ok(),
+            local_appdata: std::env::var("LOCALAPPDATA").ok(),
+            userprofile: std::env::var("USERPROFILE").ok(),
+        };
+
+        std::env::set_var("HOME", temp_path);
+        std::env::set_var("XDG_CONFIG_HOME", temp_path);
+        std::env::set_var("APPDATA", temp_path);
+        std::env::set_var("LOCALAPPDATA", temp_path);
+        std::env::set_var("USERPROFILE", temp_path);
+
+        guard
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        for (name, value) in [
+            ("HOME", &self.home),
+            ("XDG_CONFIG_HOME", &self.xdg_config_home),
+            ("APPDATA", &self.appdata),
+            ("LOCALAPPDATA", &self.local_appdata),
+            ("USERPROFILE", &self.userprofile),
+        ] {
+            match value {
+                Some(value) => std::env::set_var(name, value),
+                None => std::env::remove_var(name),
+            }
+        }
+    }
+}
+
+/// Isolate a CLI invocation in `temp_path` with file token storage, no update
+/// checks and no colour
+pub fn apply_env(cmd: &mut Command, temp_path: &str) {
+    cmd.env("HOME", temp_path)
+        .env("XDG_CONFIG_HOME", temp_path)
+        .env("APPDATA", temp_path)
+        .env("LOCALAPPDATA", temp_path)
+        .env("USERPROFILE", temp_path)
+        .env("FREEAGENT_NO_UPDATE", "true")
+        .env("FREEAGENT_TOKEN_STORE", "file")
+        .env("NO_COLOR", "1");
+}
+
+pub fn config_dir() -> PathBuf {
+    ProjectDirs::from("com", "freeagent", "freeagent-cli")
+        .expect("project dirs")
+        .config_dir()
+        .to_path_buf()
+}
+
+pub fn token_file_path() -> PathBuf {
+    config_dir().join("tokens.json")
+}
+
+/// Write unexpired tokens to `path`
+pub fn write_tokens(path: &Path, access_token: &str, sandbox: bool) {
+    fs::create_dir_all(path.parent().unwrap()).expect("create config dir");
+    let now = Utc::now();
+    let payload = json!({
+        "access_token": access_token,
+        "refresh_token": "test-refresh",
+        "expires_at": (now + Duration::hours(1)).to_rfc3339(),
+        "sandbox": sandbox,
+        "last_refreshed": now.to_rfc3339(),
+    });
+    fs::write(path, serde_json::to_string_pretty(&payload).unwrap()).expect("write tokens");
+}
+
+/// Log the default profile in to production with the `test-access` token
+pub fn write_tokens_file() {
+    write_tokens(&token_file_path(), "test-access", false);
+}